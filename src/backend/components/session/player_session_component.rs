@@ -17,33 +17,52 @@
 
 use bevy_ecs::{component::Component, entity::Entity, hierarchy::Children, system::Query};
 
-use std::sync::Weak;
+use std::{sync::Weak, time::Duration};
 
 use crate::{
     backend::{
-        data_objects::input_status::InputStatus, world_objects::server_viewport::ServerViewport,
+        data_objects::{
+            input_status::InputStatus, outfitting_request::OutfittingRequest,
+            target_request::TargetRequest, trade_request::TradeRequest,
+        },
+        world_objects::server_viewport::ServerViewport,
     },
+    configuration_file_structures::reference_types::{MissionReference, StarSystemReference},
     connectivity::{
-        client_server_message::ClientServerMessage, player_info::player_session::PlayerSession,
-        server_client_message::ServerClientMessage,
+        client_server_message::ClientServerMessage, outbound_queue::OutboundSender,
+        player_info::player_session::PlayerSession, ship_status_message_data::ShipStatusData,
     },
 };
 
 #[derive(Component)]
 pub struct PlayerSessionComponent {
     pub command_queue_inbound: async_channel::Receiver<ClientServerMessage>,
-    pub command_queue_outbound: async_channel::Sender<ServerClientMessage>,
+    pub command_queue_outbound: OutboundSender,
     pub input_status: InputStatus,
     pub primary_viewport: Option<Entity>,
     pub session: Weak<PlayerSession>,
     pub should_follow: Option<Entity>,
+    pub landed_on: Option<Entity>,
+    pub landing_refused_by: Option<Entity>, // Planetoid which last refused landing, so holding the key doesn't repeat the refusal every tick
+    pub takeoff_requested: bool,
+    pub jump_requested: Option<StarSystemReference>,
+    pub trade_requests: Vec<TradeRequest>,
+    pub market_outdated: bool, // The player should be sent the market they are landed on, even without trading
+    pub outfitting_requests: Vec<OutfittingRequest>,
+    pub shipyard_outdated: bool, // As with the market, for the shipyard and outfitter
+    pub mission_requests: Vec<MissionReference>, // Missions the player has asked to accept
+    pub missions_outdated: bool,
+    pub last_ship_status: Option<ShipStatusData>, // Last status sent, so unchanged statuses are not sent again
+    pub ship_status_cooldown: Duration,
+    pub target_request: Option<TargetRequest>, // Only the latest request matters
+    pub target_info_cooldown: Duration,
 }
 
 impl PlayerSessionComponent {
     pub fn new(
         session: Weak<PlayerSession>,
         command_queue_inbound: async_channel::Receiver<ClientServerMessage>,
-        command_queue_outbound: async_channel::Sender<ServerClientMessage>,
+        command_queue_outbound: OutboundSender,
     ) -> PlayerSessionComponent {
         let session = PlayerSessionComponent {
             command_queue_inbound,
@@ -52,6 +71,20 @@ impl PlayerSessionComponent {
             session,
             primary_viewport: None,
             should_follow: None,
+            landed_on: None,
+            landing_refused_by: None,
+            takeoff_requested: false,
+            jump_requested: None,
+            trade_requests: Vec::new(),
+            market_outdated: false,
+            outfitting_requests: Vec::new(),
+            shipyard_outdated: false,
+            mission_requests: Vec::new(),
+            missions_outdated: false,
+            last_ship_status: None,
+            ship_status_cooldown: Duration::ZERO,
+            target_request: None,
+            target_info_cooldown: Duration::ZERO,
         };
         session
     }
@@ -85,6 +118,18 @@ pub fn process_input_messages_system(
                         crate::connectivity::client_server_message::ControlInput::Fire => {
                             session.input_status.fire = pressed;
                         }
+                        crate::connectivity::client_server_message::ControlInput::Land => {
+                            session.input_status.land = pressed;
+                        }
+                        crate::connectivity::client_server_message::ControlInput::Afterburner => {
+                            session.input_status.afterburner = pressed;
+                        }
+                        crate::connectivity::client_server_message::ControlInput::CycleTarget => {
+                            // Holding the key down cycles once, like every other press of it
+                            if pressed {
+                                session.target_request = Some(TargetRequest::CycleNearest);
+                            }
+                        }
                     },
                     ClientServerMessage::Refresh => {
                         for session_viewport in viewport_children.iter_descendants(entity) {
@@ -96,6 +141,55 @@ pub fn process_input_messages_system(
                             }
                         }
                     }
+                    ClientServerMessage::TakeOff => {
+                        session.takeoff_requested = true;
+                    }
+                    ClientServerMessage::Jump { star_system } => {
+                        session.jump_requested = Some(star_system);
+                    }
+                    ClientServerMessage::BuyCommodity {
+                        commodity,
+                        quantity,
+                    } => {
+                        session
+                            .trade_requests
+                            .push(TradeRequest::Buy { commodity, quantity });
+                    }
+                    ClientServerMessage::SellCommodity {
+                        commodity,
+                        quantity,
+                    } => {
+                        session
+                            .trade_requests
+                            .push(TradeRequest::Sell { commodity, quantity });
+                    }
+                    ClientServerMessage::BuyShip { ship } => {
+                        session
+                            .outfitting_requests
+                            .push(OutfittingRequest::BuyShip { ship });
+                    }
+                    ClientServerMessage::BuyOutfit { outfit } => {
+                        session
+                            .outfitting_requests
+                            .push(OutfittingRequest::BuyOutfit { outfit });
+                    }
+                    ClientServerMessage::SellOutfit { outfit } => {
+                        session
+                            .outfitting_requests
+                            .push(OutfittingRequest::SellOutfit { outfit });
+                    }
+                    ClientServerMessage::AcceptMission { mission } => {
+                        session.mission_requests.push(mission);
+                    }
+                    ClientServerMessage::SelectTarget { id } => {
+                        let target = match id.map(Entity::try_from_bits) {
+                            Some(Ok(has)) => Some(has),
+                            Some(Err(_)) => return, // Could never have been sent to the client
+                            None => None,
+                        };
+
+                        session.target_request = Some(TargetRequest::Select(target));
+                    }
                 }
             }
             Err(e) => match e {
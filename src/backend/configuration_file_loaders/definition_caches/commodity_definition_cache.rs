@@ -0,0 +1,70 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::HashMap;
+
+use crate::configuration_file_structures::{
+    commodity_configuration_file::CommodityRecord, reference_types::CommodityReference,
+};
+
+pub struct CommodityDefinitionCache {
+    commodities: Vec<CommodityRecord>,
+    commodity_reference_names: HashMap<CommodityReference, usize>,
+}
+
+impl CommodityDefinitionCache {
+    pub fn new() -> CommodityDefinitionCache {
+        CommodityDefinitionCache {
+            commodities: Vec::new(),
+            commodity_reference_names: HashMap::new(),
+        }
+    }
+
+    pub fn add_commodity_records(&mut self, records: impl Iterator<Item = CommodityRecord>) -> Result<(), ()> {
+        let mut duplicated_name = false;
+
+        for commodity in records {
+            match self.commodity_reference_names.entry(commodity.commodity_reference.clone()) {
+                std::collections::hash_map::Entry::Occupied(_already_exists) => {
+                    tracing::error!("Duplicated commodity record name {} found", commodity.commodity_reference);
+                    duplicated_name = true;
+                }
+                std::collections::hash_map::Entry::Vacant(empty) => {
+                    // No problem, name is unique
+                    empty.insert(self.commodities.len());
+                    tracing::trace!("Loaded commodity definition {}", commodity.commodity_reference);
+                    self.commodities.push(commodity);
+                }
+            };
+        }
+
+        if duplicated_name {
+            tracing::error!(
+                "Bundle could not be loaded due to duplicate names from self or previously loaded bundles"
+            );
+            Err(())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn get_commodity_record(&self, commodity_reference: &str) -> Option<&CommodityRecord> {
+        self.commodity_reference_names
+            .get(commodity_reference)
+            .map(|index| &self.commodities[*index])
+    }
+}
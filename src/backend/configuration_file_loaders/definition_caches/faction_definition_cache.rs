@@ -0,0 +1,70 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::HashMap;
+
+use crate::configuration_file_structures::{
+    faction_configuration_file::FactionRecord, reference_types::FactionReference,
+};
+
+pub struct FactionDefinitionCache {
+    factions: Vec<FactionRecord>,
+    faction_reference_names: HashMap<FactionReference, usize>,
+}
+
+impl FactionDefinitionCache {
+    pub fn new() -> FactionDefinitionCache {
+        FactionDefinitionCache {
+            factions: Vec::new(),
+            faction_reference_names: HashMap::new(),
+        }
+    }
+
+    pub fn add_faction_records(&mut self, records: impl Iterator<Item = FactionRecord>) -> Result<(), ()> {
+        let mut duplicated_name = false;
+
+        for faction in records {
+            match self.faction_reference_names.entry(faction.faction_reference.clone()) {
+                std::collections::hash_map::Entry::Occupied(_already_exists) => {
+                    tracing::error!("Duplicated faction record name {} found", faction.faction_reference);
+                    duplicated_name = true;
+                }
+                std::collections::hash_map::Entry::Vacant(empty) => {
+                    // No problem, name is unique
+                    empty.insert(self.factions.len());
+                    tracing::trace!("Loaded faction definition {}", faction.faction_reference);
+                    self.factions.push(faction);
+                }
+            };
+        }
+
+        if duplicated_name {
+            tracing::error!(
+                "Bundle could not be loaded due to duplicate names from self or previously loaded bundles"
+            );
+            Err(())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn get_faction_record(&self, faction_reference: &str) -> Option<&FactionRecord> {
+        self.faction_reference_names
+            .get(faction_reference)
+            .map(|index| &self.factions[*index])
+    }
+}
@@ -0,0 +1,74 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::HashMap;
+
+use crate::configuration_file_structures::{
+    mission_configuration_file::MissionRecord, reference_types::MissionReference,
+};
+
+pub struct MissionDefinitionCache {
+    missions: Vec<MissionRecord>,
+    mission_reference_names: HashMap<MissionReference, usize>,
+}
+
+impl MissionDefinitionCache {
+    pub fn new() -> MissionDefinitionCache {
+        MissionDefinitionCache {
+            missions: Vec::new(),
+            mission_reference_names: HashMap::new(),
+        }
+    }
+
+    pub fn add_mission_records(&mut self, records: impl Iterator<Item = MissionRecord>) -> Result<(), ()> {
+        let mut duplicated_name = false;
+
+        for mission in records {
+            match self.mission_reference_names.entry(mission.mission_reference.clone()) {
+                std::collections::hash_map::Entry::Occupied(_already_exists) => {
+                    tracing::error!("Duplicated mission record name {} found", mission.mission_reference);
+                    duplicated_name = true;
+                }
+                std::collections::hash_map::Entry::Vacant(empty) => {
+                    // No problem, name is unique
+                    empty.insert(self.missions.len());
+                    tracing::trace!("Loaded mission definition {}", mission.mission_reference);
+                    self.missions.push(mission);
+                }
+            };
+        }
+
+        if duplicated_name {
+            tracing::error!(
+                "Bundle could not be loaded due to duplicate names from self or previously loaded bundles"
+            );
+            Err(())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn get_all_mission_records(&self) -> &[MissionRecord] {
+        &self.missions
+    }
+
+    pub fn get_mission_record(&self, mission_reference: &str) -> Option<&MissionRecord> {
+        self.mission_reference_names
+            .get(mission_reference)
+            .map(|index| &self.missions[*index])
+    }
+}
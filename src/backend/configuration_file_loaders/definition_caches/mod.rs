@@ -15,5 +15,12 @@
     along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+pub mod commodity_definition_cache;
+pub mod faction_definition_cache;
 pub mod list_required_assets;
+pub mod mission_definition_cache;
+pub mod outfit_definition_cache;
 pub mod planetoid_definition_cache;
+pub mod ship_definition_cache;
+pub mod star_system_definition_cache;
+pub mod weapon_definition_cache;
@@ -0,0 +1,74 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::HashMap;
+
+use crate::configuration_file_structures::{
+    outfit_configuration_file::OutfitRecord, reference_types::OutfitReference,
+};
+
+pub struct OutfitDefinitionCache {
+    outfits: Vec<OutfitRecord>,
+    outfit_reference_names: HashMap<OutfitReference, usize>,
+}
+
+impl OutfitDefinitionCache {
+    pub fn new() -> OutfitDefinitionCache {
+        OutfitDefinitionCache {
+            outfits: Vec::new(),
+            outfit_reference_names: HashMap::new(),
+        }
+    }
+
+    pub fn add_outfit_records(&mut self, records: impl Iterator<Item = OutfitRecord>) -> Result<(), ()> {
+        let mut duplicated_name = false;
+
+        for outfit in records {
+            match self.outfit_reference_names.entry(outfit.outfit_reference.clone()) {
+                std::collections::hash_map::Entry::Occupied(_already_exists) => {
+                    tracing::error!("Duplicated outfit record name {} found", outfit.outfit_reference);
+                    duplicated_name = true;
+                }
+                std::collections::hash_map::Entry::Vacant(empty) => {
+                    // No problem, name is unique
+                    empty.insert(self.outfits.len());
+                    tracing::trace!("Loaded outfit definition {}", outfit.outfit_reference);
+                    self.outfits.push(outfit);
+                }
+            };
+        }
+
+        if duplicated_name {
+            tracing::error!(
+                "Bundle could not be loaded due to duplicate names from self or previously loaded bundles"
+            );
+            Err(())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn get_all_outfit_records(&self) -> &[OutfitRecord] {
+        &self.outfits
+    }
+
+    pub fn get_outfit_record(&self, outfit_reference: &str) -> Option<&OutfitRecord> {
+        self.outfit_reference_names
+            .get(outfit_reference)
+            .map(|index| &self.outfits[*index])
+    }
+}
@@ -83,6 +83,10 @@ impl PlanetoidDefinitionCache {
     pub fn get_all_planetoid_records(&self) -> &[PlanetoidRecord] {
         return &self.planetoids;
     }
+
+    pub fn has_planetoid_record(&self, planetoid_reference: &str) -> bool {
+        self.planetoid_reference_names.contains(planetoid_reference)
+    }
 }
 
 impl ListRequiredAssets for PlanetoidDefinitionCache {
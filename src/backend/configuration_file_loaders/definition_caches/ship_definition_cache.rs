@@ -0,0 +1,96 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::HashMap;
+
+use crate::configuration_file_structures::{
+    asset_definition_file::AssetType,
+    reference_types::{AssetReference, ShipReference},
+    ship_configuration_file::ShipRecord,
+};
+
+use super::list_required_assets::ListRequiredAssets;
+
+pub struct ShipDefinitionCache {
+    ships: Vec<ShipRecord>,
+    ship_reference_names: HashMap<ShipReference, usize>,
+}
+
+impl ShipDefinitionCache {
+    pub fn new() -> ShipDefinitionCache {
+        ShipDefinitionCache {
+            ships: Vec::new(),
+            ship_reference_names: HashMap::new(),
+        }
+    }
+
+    pub fn add_ship_records(&mut self, records: impl Iterator<Item = ShipRecord>) -> Result<(), ()> {
+        let mut duplicated_name = false;
+
+        for ship in records {
+            // Other verification steps may be done here with regards to required definitions for the ships
+
+            match self.ship_reference_names.entry(ship.ship_reference.clone()) {
+                std::collections::hash_map::Entry::Occupied(_already_exists) => {
+                    tracing::error!("Duplicated ship record name {} found", ship.ship_reference);
+                    duplicated_name = true;
+                }
+                std::collections::hash_map::Entry::Vacant(empty) => {
+                    // No problem, name is unique
+                    empty.insert(self.ships.len());
+                    tracing::trace!("Loaded ship definition {}", ship.ship_reference);
+                    self.ships.push(ship);
+                }
+            };
+        }
+
+        if duplicated_name {
+            tracing::error!(
+                "Bundle could not be loaded due to duplicate names from self or previously loaded bundles"
+            );
+            Err(())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn get_all_ship_records(&self) -> &[ShipRecord] {
+        &self.ships
+    }
+
+    pub fn get_ship_record(&self, ship_reference: &str) -> Option<&ShipRecord> {
+        self.ship_reference_names
+            .get(ship_reference)
+            .map(|index| &self.ships[*index])
+    }
+
+    pub fn get_player_starting_ship(&self) -> Option<&ShipRecord> {
+        self.ships
+            .iter()
+            .find(|ship| ship.player_starting_ship)
+            .or(self.ships.first())
+    }
+}
+
+impl ListRequiredAssets for ShipDefinitionCache {
+    fn get_required_asset_list(&self) -> Vec<(&AssetReference, AssetType)> {
+        self.ships
+            .iter()
+            .flat_map(|record| record.get_required_asset_list())
+            .collect()
+    }
+}
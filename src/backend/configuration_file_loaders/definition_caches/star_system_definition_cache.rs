@@ -0,0 +1,157 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::HashMap;
+
+use crate::configuration_file_structures::{
+    reference_types::{PlanetoidReference, StarSystemReference},
+    star_system_configuration_file::StarSystemRecord,
+};
+
+// Index of a star system within the definition cache, cheap enough to compare on every collision check
+pub type StarSystemId = usize;
+
+pub struct StarSystemDefinitionCache {
+    star_systems: Vec<StarSystemRecord>,
+    star_system_reference_names: HashMap<StarSystemReference, StarSystemId>,
+    planetoid_star_systems: HashMap<PlanetoidReference, StarSystemId>,
+}
+
+impl StarSystemDefinitionCache {
+    pub fn new() -> StarSystemDefinitionCache {
+        StarSystemDefinitionCache {
+            star_systems: Vec::new(),
+            star_system_reference_names: HashMap::new(),
+            planetoid_star_systems: HashMap::new(),
+        }
+    }
+
+    pub fn add_star_system_records(
+        &mut self,
+        records: impl Iterator<Item = StarSystemRecord>,
+    ) -> Result<(), ()> {
+        let mut duplicated_name = false;
+
+        for star_system in records {
+            let star_system_id = self.star_systems.len();
+
+            match self
+                .star_system_reference_names
+                .entry(star_system.star_system_reference.clone())
+            {
+                std::collections::hash_map::Entry::Occupied(_already_exists) => {
+                    tracing::error!(
+                        "Duplicated star system record name {} found",
+                        star_system.star_system_reference
+                    );
+                    duplicated_name = true;
+                    continue;
+                }
+                std::collections::hash_map::Entry::Vacant(empty) => {
+                    // No problem, name is unique
+                    empty.insert(star_system_id);
+                }
+            };
+
+            // A planetoid can only ever be within a single star system
+            for planetoid in &star_system.planetoids {
+                match self.planetoid_star_systems.entry(planetoid.clone()) {
+                    std::collections::hash_map::Entry::Occupied(_already_exists) => {
+                        tracing::error!(
+                            "Planetoid {} is placed in star system {} but already belongs to another star system",
+                            planetoid,
+                            star_system.star_system_reference
+                        );
+                        duplicated_name = true;
+                    }
+                    std::collections::hash_map::Entry::Vacant(empty) => {
+                        empty.insert(star_system_id);
+                    }
+                }
+            }
+
+            tracing::trace!(
+                "Loaded star system definition {}",
+                star_system.star_system_reference
+            );
+            self.star_systems.push(star_system);
+        }
+
+        if duplicated_name {
+            tracing::error!(
+                "Bundle could not be loaded due to duplicate names from self or previously loaded bundles"
+            );
+            Err(())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn get_all_star_system_records(&self) -> &[StarSystemRecord] {
+        &self.star_systems
+    }
+
+    pub fn get_star_system_id(&self, star_system_reference: &str) -> Option<StarSystemId> {
+        self.star_system_reference_names
+            .get(star_system_reference)
+            .copied()
+    }
+
+    pub fn get_star_system_record(
+        &self,
+        star_system_id: StarSystemId,
+    ) -> Option<&StarSystemRecord> {
+        self.star_systems.get(star_system_id)
+    }
+
+    pub fn get_star_system_of_planetoid(&self, planetoid_reference: &str) -> Option<StarSystemId> {
+        self.planetoid_star_systems
+            .get(planetoid_reference)
+            .copied()
+    }
+
+    pub fn get_player_starting_system(&self) -> Option<StarSystemId> {
+        self.star_systems
+            .iter()
+            .position(|star_system| star_system.player_starting_system)
+            .or(if self.star_systems.is_empty() {
+                None
+            } else {
+                Some(0)
+            })
+    }
+
+    pub fn are_linked(&self, from: StarSystemId, to: StarSystemId) -> bool {
+        let lists = |listing: StarSystemId, listed: StarSystemId| match (
+            self.star_systems.get(listing),
+            self.star_systems.get(listed),
+        ) {
+            (Some(listing), Some(listed)) => {
+                listing.hyperlanes.contains(&listed.star_system_reference)
+            }
+            _ => false,
+        };
+
+        from != to && (lists(from, to) || lists(to, from))
+    }
+
+    pub fn get_linked_star_systems(&self, star_system_id: StarSystemId) -> Vec<StarSystemId> {
+        (0..self.star_systems.len())
+            .filter(|other| self.are_linked(star_system_id, *other))
+            .collect()
+    }
+}
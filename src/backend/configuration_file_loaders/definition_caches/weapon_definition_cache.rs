@@ -0,0 +1,85 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::HashMap;
+
+use crate::configuration_file_structures::{
+    asset_definition_file::AssetType,
+    reference_types::{AssetReference, WeaponReference},
+    weapon_configuration_file::WeaponRecord,
+};
+
+use super::list_required_assets::ListRequiredAssets;
+
+pub struct WeaponDefinitionCache {
+    weapons: Vec<WeaponRecord>,
+    weapon_reference_names: HashMap<WeaponReference, usize>,
+}
+
+impl WeaponDefinitionCache {
+    pub fn new() -> WeaponDefinitionCache {
+        WeaponDefinitionCache {
+            weapons: Vec::new(),
+            weapon_reference_names: HashMap::new(),
+        }
+    }
+
+    pub fn add_weapon_records(&mut self, records: impl Iterator<Item = WeaponRecord>) -> Result<(), ()> {
+        let mut duplicated_name = false;
+
+        for weapon in records {
+            // Other verification steps may be done here with regards to required definitions for the weapons
+
+            match self.weapon_reference_names.entry(weapon.weapon_reference.clone()) {
+                std::collections::hash_map::Entry::Occupied(_already_exists) => {
+                    tracing::error!("Duplicated weapon record name {} found", weapon.weapon_reference);
+                    duplicated_name = true;
+                }
+                std::collections::hash_map::Entry::Vacant(empty) => {
+                    // No problem, name is unique
+                    empty.insert(self.weapons.len());
+                    tracing::trace!("Loaded weapon definition {}", weapon.weapon_reference);
+                    self.weapons.push(weapon);
+                }
+            };
+        }
+
+        if duplicated_name {
+            tracing::error!(
+                "Bundle could not be loaded due to duplicate names from self or previously loaded bundles"
+            );
+            Err(())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn get_weapon_record(&self, weapon_reference: &str) -> Option<&WeaponRecord> {
+        self.weapon_reference_names
+            .get(weapon_reference)
+            .map(|index| &self.weapons[*index])
+    }
+}
+
+impl ListRequiredAssets for WeaponDefinitionCache {
+    fn get_required_asset_list(&self) -> Vec<(&AssetReference, AssetType)> {
+        self.weapons
+            .iter()
+            .flat_map(|record| record.get_required_asset_list())
+            .collect()
+    }
+}
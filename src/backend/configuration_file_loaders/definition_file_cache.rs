@@ -17,37 +17,262 @@
 
 use std::{collections::HashMap, ffi::OsStr, path::PathBuf};
 
-use crate::configuration_file_structures::planetoid_configuration_file::{PlanetoidConfigurationFile, PlanetoidRecord};
+use serde::de::DeserializeOwned;
 
-use super::{archive_readers::{archive_reader::ArchiveReader, filesystem_reader::FilesystemReader, zip_reader::ZipReader}, asset_bundle_loader::AssetBundle, definition_caches::{list_required_assets::ListRequiredAssets, planetoid_definition_cache::PlanetoidDefinitionCache}};
+use crate::configuration_file_structures::{commodity_configuration_file::CommodityConfigurationFile, faction_configuration_file::FactionConfigurationFile, mission_configuration_file::MissionConfigurationFile, outfit_configuration_file::OutfitConfigurationFile, planetoid_configuration_file::{PlanetoidConfigurationFile, PlanetoidRecord}, ship_configuration_file::ShipConfigurationFile, star_system_configuration_file::StarSystemConfigurationFile, weapon_configuration_file::WeaponConfigurationFile};
+#[cfg(test)]
+use crate::configuration_file_structures::{commodity_configuration_file::CommodityRecord, faction_configuration_file::FactionRecord, mission_configuration_file::MissionRecord, outfit_configuration_file::OutfitRecord, ship_configuration_file::ShipRecord, star_system_configuration_file::StarSystemRecord, weapon_configuration_file::WeaponRecord};
+
+use super::{archive_readers::{archive_reader::ArchiveReader, filesystem_reader::FilesystemReader, zip_reader::ZipReader}, asset_bundle_loader::AssetBundle, definition_caches::{commodity_definition_cache::CommodityDefinitionCache, faction_definition_cache::FactionDefinitionCache, list_required_assets::ListRequiredAssets, mission_definition_cache::MissionDefinitionCache, outfit_definition_cache::OutfitDefinitionCache, planetoid_definition_cache::PlanetoidDefinitionCache, ship_definition_cache::ShipDefinitionCache, star_system_definition_cache::StarSystemDefinitionCache, weapon_definition_cache::WeaponDefinitionCache}};
 
 enum DefinitionFileNames {
-    Planetoids
+    Commodities,
+    Factions,
+    Missions,
+    Outfits,
+    Planetoids,
+    Ships,
+    StarSystems,
+    Weapons
 }
 
 impl DefinitionFileNames {
     pub fn path_to_definition_type(path: &str) -> Option<DefinitionFileNames> {
 
         match path {
+            "commodities.json" => Some(DefinitionFileNames::Commodities),
+            "factions.json" => Some(DefinitionFileNames::Factions),
+            "missions.json" => Some(DefinitionFileNames::Missions),
+            "outfits.json" => Some(DefinitionFileNames::Outfits),
             "planetoids.json" => Some(DefinitionFileNames::Planetoids),
+            "ships.json" => Some(DefinitionFileNames::Ships),
+            "systems.json" => Some(DefinitionFileNames::StarSystems),
+            "weapons.json" => Some(DefinitionFileNames::Weapons),
             _ => None
         }
     }
 }
 
+// Every kind of definition, as they would be found across the definition files of a bundle
+#[cfg(test)]
+pub struct DefinitionRecords {
+    pub commodities: Vec<CommodityRecord>,
+    pub factions: Vec<FactionRecord>,
+    pub missions: Vec<MissionRecord>,
+    pub outfits: Vec<OutfitRecord>,
+    pub planetoids: Vec<PlanetoidRecord>,
+    pub ships: Vec<ShipRecord>,
+    pub star_systems: Vec<StarSystemRecord>,
+    pub weapons: Vec<WeaponRecord>
+}
+
 pub struct DefinitionFileCache {
-    planetoids: PlanetoidDefinitionCache
+    commodities: CommodityDefinitionCache,
+    factions: FactionDefinitionCache,
+    missions: MissionDefinitionCache,
+    outfits: OutfitDefinitionCache,
+    planetoids: PlanetoidDefinitionCache,
+    ships: ShipDefinitionCache,
+    star_systems: StarSystemDefinitionCache,
+    weapons: WeaponDefinitionCache
 }
 
 impl DefinitionFileCache {
     pub fn new() -> DefinitionFileCache {
-        DefinitionFileCache{planetoids: PlanetoidDefinitionCache::new()}
+        DefinitionFileCache{commodities: CommodityDefinitionCache::new(), factions: FactionDefinitionCache::new(), missions: MissionDefinitionCache::new(), outfits: OutfitDefinitionCache::new(), planetoids: PlanetoidDefinitionCache::new(), ships: ShipDefinitionCache::new(), star_systems: StarSystemDefinitionCache::new(), weapons: WeaponDefinitionCache::new()}
+    }
+
+    // Builds definitions directly rather than from bundles on disk, so tests can describe exactly the world they need
+    #[cfg(test)]
+    pub fn from_records(records: DefinitionRecords) -> Result<DefinitionFileCache, ()> {
+        let mut cache = DefinitionFileCache::new();
+        cache.commodities.add_commodity_records(records.commodities.into_iter())?;
+        cache.factions.add_faction_records(records.factions.into_iter())?;
+        cache.missions.add_mission_records(records.missions.into_iter())?;
+        cache.outfits.add_outfit_records(records.outfits.into_iter())?;
+        cache.planetoids.add_planetoid_records(records.planetoids.into_iter())?;
+        cache.ships.add_ship_records(records.ships.into_iter())?;
+        cache.star_systems.add_star_system_records(records.star_systems.into_iter())?;
+        cache.weapons.add_weapon_records(records.weapons.into_iter())?;
+        cache.verify_references()?;
+        Ok(cache)
+    }
+
+    pub fn get_commodities(&self) -> &CommodityDefinitionCache {
+        &self.commodities
+    }
+
+    pub fn get_factions(&self) -> &FactionDefinitionCache {
+        &self.factions
+    }
+
+    pub fn get_missions(&self) -> &MissionDefinitionCache {
+        &self.missions
+    }
+
+    pub fn get_outfits(&self) -> &OutfitDefinitionCache {
+        &self.outfits
     }
 
     pub fn get_planetoids(&self) -> &[PlanetoidRecord] {
         self.planetoids.get_all_planetoid_records()
     }
 
+    pub fn get_ships(&self) -> &ShipDefinitionCache {
+        &self.ships
+    }
+
+    pub fn get_star_systems(&self) -> &StarSystemDefinitionCache {
+        &self.star_systems
+    }
+
+    pub fn get_weapons(&self) -> &WeaponDefinitionCache {
+        &self.weapons
+    }
+
+    // Checks references between definitions, which can only be done once every definition bundle has been loaded
+    // Values which are valid on their own but unusable in play are rejected here as well
+    pub fn verify_references(&self) -> Result<(), ()> {
+        let mut missing_reference = false;
+        let mut invalid_value = false;
+
+        for ship in self.ships.get_all_ship_records() {
+            for weapon in &ship.weapons {
+                if self.weapons.get_weapon_record(weapon).is_none() {
+                    tracing::error!("Ship {} references weapon {} which does not exist", ship.ship_reference, weapon);
+                    missing_reference = true;
+                }
+            }
+        }
+
+        for mission in self.missions.get_all_mission_records() {
+            for planetoid in [&mission.origin, &mission.destination] {
+                if !self.planetoids.has_planetoid_record(planetoid) {
+                    tracing::error!("Mission {} starts or ends at planetoid {} which does not exist", mission.mission_reference, planetoid);
+                    missing_reference = true;
+                }
+            }
+
+            let missing_commodity = mission.cargo.as_ref().filter(|cargo| self.commodities.get_commodity_record(&cargo.commodity).is_none());
+
+            if let Some(cargo) = missing_commodity {
+                tracing::error!("Mission {} carries commodity {} which does not exist", mission.mission_reference, cargo.commodity);
+                missing_reference = true;
+            }
+
+            let missing_faction = mission.faction.as_ref().filter(|faction| self.factions.get_faction_record(faction).is_none());
+
+            if let Some(faction) = missing_faction {
+                tracing::error!("Mission {} is flown for faction {} which does not exist", mission.mission_reference, faction);
+                missing_reference = true;
+            }
+
+            for required in &mission.offer_conditions.completed_missions {
+                if self.missions.get_mission_record(required).is_none() {
+                    tracing::error!("Mission {} requires completing mission {} which does not exist", mission.mission_reference, required);
+                    missing_reference = true;
+                }
+            }
+        }
+
+        for outfit in self.outfits.get_all_outfit_records() {
+            let missing_weapon = outfit.weapon.as_ref().filter(|weapon| self.weapons.get_weapon_record(weapon).is_none());
+
+            if let Some(weapon) = missing_weapon {
+                tracing::error!("Outfit {} mounts weapon {} which does not exist", outfit.outfit_reference, weapon);
+                missing_reference = true;
+            }
+        }
+
+        for planetoid in self.planetoids.get_all_planetoid_records() {
+            let missing_faction = planetoid.faction.as_ref().filter(|faction| self.factions.get_faction_record(faction).is_none());
+
+            if let Some(faction) = missing_faction {
+                tracing::error!("Planetoid {} belongs to faction {} which does not exist", planetoid.planetoid_reference, faction);
+                missing_reference = true;
+            }
+
+            let features = match planetoid.may_be_landed_on.as_ref().and_then(|x| x.features.as_ref()) {
+                Some(has) => has,
+                None => continue,
+            };
+
+            for ship in &features.shipyard {
+                if self.ships.get_ship_record(ship).is_none() {
+                    tracing::error!("Planetoid {} has a shipyard selling ship {} which does not exist", planetoid.planetoid_reference, ship);
+                    missing_reference = true;
+                }
+            }
+
+            for outfit in &features.outfitter {
+                if self.outfits.get_outfit_record(outfit).is_none() {
+                    tracing::error!("Planetoid {} has an outfitter selling outfit {} which does not exist", planetoid.planetoid_reference, outfit);
+                    missing_reference = true;
+                }
+            }
+
+            for listing in &features.market {
+                if self.commodities.get_commodity_record(&listing.commodity).is_none() {
+                    tracing::error!("Planetoid {} has a market for commodity {} which does not exist", planetoid.planetoid_reference, listing.commodity);
+                    missing_reference = true;
+                }
+
+                // Buying for less than it can be sold back for would be a way to print money
+                if listing.buy_price < listing.sell_price {
+                    tracing::error!(
+                        "Planetoid {} market has inverted prices for commodity {}: players buy it for {} credits per ton but are paid {} to sell it back",
+                        planetoid.planetoid_reference, listing.commodity, listing.buy_price, listing.sell_price
+                    );
+                    invalid_value = true;
+                }
+            }
+        }
+
+        for star_system in self.star_systems.get_all_star_system_records() {
+            for planetoid in &star_system.planetoids {
+                if !self.planetoids.has_planetoid_record(planetoid) {
+                    tracing::error!("Star system {} references planetoid {} which does not exist", star_system.star_system_reference, planetoid);
+                    missing_reference = true;
+                }
+            }
+
+            for hyperlane in &star_system.hyperlanes {
+                if self.star_systems.get_star_system_id(hyperlane).is_none() {
+                    tracing::error!("Star system {} has a hyperlane to star system {} which does not exist", star_system.star_system_reference, hyperlane);
+                    missing_reference = true;
+                }
+            }
+
+            for traffic in &star_system.traffic {
+                let missing_faction = traffic.faction.as_ref().filter(|faction| self.factions.get_faction_record(faction).is_none());
+
+                if let Some(faction) = missing_faction {
+                    tracing::error!("Star system {} has traffic of faction {} which does not exist", star_system.star_system_reference, faction);
+                    missing_reference = true;
+                }
+
+                for ship in &traffic.ships {
+                    if self.ships.get_ship_record(ship).is_none() {
+                        tracing::error!("Star system {} has traffic of ship {} which does not exist", star_system.star_system_reference, ship);
+                        missing_reference = true;
+                    }
+                }
+
+                // Spawning every tick forever would be a mistake in the definition rather than something to honor
+                if traffic.ships.is_empty() || traffic.spawn_interval_seconds <= 0.0 || traffic.lifetime_seconds <= 0.0 {
+                    tracing::error!("Star system {} has traffic without ships or with a spawn interval or lifetime which is not positive", star_system.star_system_reference);
+                    invalid_value = true;
+                }
+            }
+        }
+
+        if missing_reference || invalid_value {
+            Err(())
+        } else {
+            Ok(())
+        }
+    }
+
     pub async fn load_definition_bundle(&mut self, file: &AssetBundle) -> Result<(), ()> {
         match &file.bundle_type {
             super::asset_bundle_loader::AssetBundleType::Folder => {
@@ -101,40 +326,164 @@ impl DefinitionFileCache {
             match DefinitionFileNames::path_to_definition_type(&*file_name.0.to_string_lossy()) {
                 Some(known_type) => {
                     match known_type {
+                        DefinitionFileNames::Commodities => {
+                            let definition_files = match Self::load_definition_files::<CommodityConfigurationFile>(file, &asset_loader, &file_name.1).await {
+                                Ok(loaded) => loaded,
+                                Err(()) => {
+                                    return Err(());
+                                }
+                            };
+
+                            for definition_file in definition_files {
+                                match self.commodities.add_commodity_records(definition_file.definitions.into_iter()) {
+                                    Ok(()) => {
+                                        // No problem here
+                                    },
+                                    Err(()) => {
+                                        tracing::error!("Error loading commodity file from definition bundle {}", file.name);
+                                        return Err(());
+                                    },
+                                }
+                            }
+                        }
+                        DefinitionFileNames::Factions => {
+                            let definition_files = match Self::load_definition_files::<FactionConfigurationFile>(file, &asset_loader, &file_name.1).await {
+                                Ok(loaded) => loaded,
+                                Err(()) => {
+                                    return Err(());
+                                }
+                            };
+
+                            for definition_file in definition_files {
+                                match self.factions.add_faction_records(definition_file.definitions.into_iter()) {
+                                    Ok(()) => {
+                                        // No problem here
+                                    },
+                                    Err(()) => {
+                                        tracing::error!("Error loading faction file from definition bundle {}", file.name);
+                                        return Err(());
+                                    },
+                                }
+                            }
+                        }
+                        DefinitionFileNames::Missions => {
+                            let definition_files = match Self::load_definition_files::<MissionConfigurationFile>(file, &asset_loader, &file_name.1).await {
+                                Ok(loaded) => loaded,
+                                Err(()) => {
+                                    return Err(());
+                                }
+                            };
+
+                            for definition_file in definition_files {
+                                match self.missions.add_mission_records(definition_file.definitions.into_iter()) {
+                                    Ok(()) => {
+                                        // No problem here
+                                    },
+                                    Err(()) => {
+                                        tracing::error!("Error loading mission file from definition bundle {}", file.name);
+                                        return Err(());
+                                    },
+                                }
+                            }
+                        }
+                        DefinitionFileNames::Outfits => {
+                            let definition_files = match Self::load_definition_files::<OutfitConfigurationFile>(file, &asset_loader, &file_name.1).await {
+                                Ok(loaded) => loaded,
+                                Err(()) => {
+                                    return Err(());
+                                }
+                            };
+
+                            for definition_file in definition_files {
+                                match self.outfits.add_outfit_records(definition_file.definitions.into_iter()) {
+                                    Ok(()) => {
+                                        // No problem here
+                                    },
+                                    Err(()) => {
+                                        tracing::error!("Error loading outfit file from definition bundle {}", file.name);
+                                        return Err(());
+                                    },
+                                }
+                            }
+                        }
                         DefinitionFileNames::Planetoids => {
-                            for planetoid_record in file_name.1 {
-                                match asset_loader.try_get_file(&planetoid_record).await {
-                                    Ok(no_error) => {
-                                        match no_error {
-                                            Some(planetoid_file_data) => {
-                                                match serde_json::de::from_slice::<PlanetoidConfigurationFile>(&planetoid_file_data) {
-                                                    Ok(deserialized) => {
-                                                        match self.planetoids.add_planetoid_records(deserialized.definitions.into_iter()) {
-                                                            Ok(()) => {
-                                                                // No problem here
-                                                            },
-                                                            Err(()) => {
-                                                                tracing::error!("Error loading planetoid file {} from definition bundle {}", planetoid_record.to_string_lossy(), file.name);
-                                                                return Err(());
-                                                            },
-                                                        }
-                                                    },
-                                                    Err(error_deserializing) => {
-                                                        tracing::error!("Error deserializing planetoids.json from definition bundle {} with error {}", file.path.to_string_lossy(), error_deserializing);
-                                                        return Err(());
-                                                    },
-                                                }
-                                            },
-                                            None => {
-                                                tracing::warn!("File {} from definition bundle {} has suddenly gone missing between directory enumeration and file loading", planetoid_record.to_string_lossy(), file.name);
-                                            },
-                                        }
-                                    },
-                                    Err(_record_read_error) => {
-                                        tracing::error!("Error reading definition bundle {}", file.name);
+                            let definition_files = match Self::load_definition_files::<PlanetoidConfigurationFile>(file, &asset_loader, &file_name.1).await {
+                                Ok(loaded) => loaded,
+                                Err(()) => {
+                                    return Err(());
+                                }
+                            };
+
+                            for definition_file in definition_files {
+                                match self.planetoids.add_planetoid_records(definition_file.definitions.into_iter()) {
+                                    Ok(()) => {
+                                        // No problem here
+                                    },
+                                    Err(()) => {
+                                        tracing::error!("Error loading planetoid file from definition bundle {}", file.name);
+                                        return Err(());
+                                    },
+                                }
+                            }
+                        }
+                        DefinitionFileNames::Ships => {
+                            let definition_files = match Self::load_definition_files::<ShipConfigurationFile>(file, &asset_loader, &file_name.1).await {
+                                Ok(loaded) => loaded,
+                                Err(()) => {
+                                    return Err(());
+                                }
+                            };
+
+                            for definition_file in definition_files {
+                                match self.ships.add_ship_records(definition_file.definitions.into_iter()) {
+                                    Ok(()) => {
+                                        // No problem here
+                                    },
+                                    Err(()) => {
+                                        tracing::error!("Error loading ship file from definition bundle {}", file.name);
                                         return Err(());
                                     },
-                                };
+                                }
+                            }
+                        }
+                        DefinitionFileNames::StarSystems => {
+                            let definition_files = match Self::load_definition_files::<StarSystemConfigurationFile>(file, &asset_loader, &file_name.1).await {
+                                Ok(loaded) => loaded,
+                                Err(()) => {
+                                    return Err(());
+                                }
+                            };
+
+                            for definition_file in definition_files {
+                                match self.star_systems.add_star_system_records(definition_file.definitions.into_iter()) {
+                                    Ok(()) => {
+                                        // No problem here
+                                    },
+                                    Err(()) => {
+                                        tracing::error!("Error loading star system file from definition bundle {}", file.name);
+                                        return Err(());
+                                    },
+                                }
+                            }
+                        }
+                        DefinitionFileNames::Weapons => {
+                            let definition_files = match Self::load_definition_files::<WeaponConfigurationFile>(file, &asset_loader, &file_name.1).await {
+                                Ok(loaded) => loaded,
+                                Err(()) => {
+                                    return Err(());
+                                }
+                            };
+
+                            for definition_file in definition_files {
+                                match self.weapons.add_weapon_records(definition_file.definitions.into_iter()) {
+                                    Ok(()) => {
+                                        // No problem here
+                                    },
+                                    Err(()) => {
+                                        tracing::error!("Error loading weapon file from definition bundle {}", file.name);
+                                        return Err(());
+                                    },
+                                }
                             }
                         }
                     }
@@ -147,10 +496,50 @@ impl DefinitionFileCache {
 
         Ok(())
     }
+
+    async fn load_definition_files<T: DeserializeOwned>(
+        file: &AssetBundle,
+        asset_loader: &impl ArchiveReader,
+        definition_files: &[&PathBuf],
+    ) -> Result<Vec<T>, ()> {
+        let mut loaded_files = Vec::new();
+
+        for definition_file in definition_files {
+            match asset_loader.try_get_file(definition_file).await {
+                Ok(no_error) => {
+                    match no_error {
+                        Some(definition_file_data) => {
+                            match serde_json::de::from_slice::<T>(&definition_file_data) {
+                                Ok(deserialized) => {
+                                    loaded_files.push(deserialized);
+                                },
+                                Err(error_deserializing) => {
+                                    tracing::error!("Error deserializing {} from definition bundle {} with error {}", definition_file.to_string_lossy(), file.path.to_string_lossy(), error_deserializing);
+                                    return Err(());
+                                },
+                            }
+                        },
+                        None => {
+                            tracing::warn!("File {} from definition bundle {} has suddenly gone missing between directory enumeration and file loading", definition_file.to_string_lossy(), file.name);
+                        },
+                    }
+                },
+                Err(_record_read_error) => {
+                    tracing::error!("Error reading definition bundle {}", file.name);
+                    return Err(());
+                },
+            };
+        }
+
+        Ok(loaded_files)
+    }
 }
 
 impl ListRequiredAssets for DefinitionFileCache {
     fn get_required_asset_list(&self) -> Vec<(&crate::configuration_file_structures::reference_types::AssetReference, crate::configuration_file_structures::asset_definition_file::AssetType)> {
-        self.planetoids.get_required_asset_list()
+        let mut required_assets = self.planetoids.get_required_asset_list();
+        required_assets.extend(self.ships.get_required_asset_list());
+        required_assets.extend(self.weapons.get_required_asset_list());
+        required_assets
     }
 }
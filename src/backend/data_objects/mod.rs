@@ -16,3 +16,7 @@
 */
 
 pub mod input_status;
+pub mod outfitting_request;
+pub mod ship_loadout;
+pub mod target_request;
+pub mod trade_request;
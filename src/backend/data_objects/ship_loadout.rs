@@ -0,0 +1,121 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::{
+    backend::{
+        configuration_file_loaders::definition_file_cache::DefinitionFileCache,
+        world_objects::{components::weapons_component::WeaponsComponent, ship::ShipBundle},
+    },
+    configuration_file_structures::{
+        outfit_configuration_file::OutfitRecord, reference_types::WeaponReference,
+        ship_configuration_file::ShipRecord,
+    },
+    connectivity::{asset_index::AssetIndex, player_info::player_profile::PlayerHoldings},
+    shared_types::{AccelerationScalar, AngularVelocity, Coordinates, Speed},
+};
+
+// A player's ship along with the outfits fitted to it
+pub struct ShipLoadout<'a> {
+    pub ship: &'a ShipRecord,
+    pub outfits: Vec<(&'a OutfitRecord, u32)>,
+}
+
+impl<'a> ShipLoadout<'a> {
+    // Anything whose definition has since been removed is left out rather than refusing to build the ship at all
+    pub fn from_holdings(
+        definitions: &'a DefinitionFileCache,
+        holdings: &PlayerHoldings,
+    ) -> Option<Self> {
+        let ships = definitions.get_ships();
+        let ship = holdings
+            .ship
+            .as_ref()
+            .and_then(|ship| ships.get_ship_record(ship))
+            .or_else(|| ships.get_player_starting_ship())?;
+
+        let outfits = holdings
+            .outfits
+            .iter()
+            .filter_map(|(outfit, count)| {
+                definitions
+                    .get_outfits()
+                    .get_outfit_record(outfit)
+                    .map(|record| (record, *count))
+            })
+            .collect();
+
+        Some(Self { ship, outfits })
+    }
+
+    pub fn outfit_mass(&self) -> f32 {
+        self.outfits
+            .iter()
+            .map(|(outfit, count)| outfit.mass * *count as f32)
+            .sum()
+    }
+
+    fn outfit_weapons(&self) -> impl Iterator<Item = &WeaponReference> {
+        self.outfits.iter().flat_map(|(outfit, count)| {
+            outfit
+                .weapon
+                .iter()
+                .flat_map(move |weapon| std::iter::repeat_n(weapon, *count as usize))
+        })
+    }
+
+    pub fn weapon_slots_used(&self) -> u32 {
+        self.outfit_weapons().count() as u32
+    }
+
+    pub fn fits(&self) -> bool {
+        self.outfit_mass() <= self.ship.outfit_space
+            && self.weapon_slots_used() <= self.ship.weapon_slots
+    }
+
+    pub fn cargo_capacity(&self) -> u32 {
+        self.outfits
+            .iter()
+            .fold(self.ship.cargo_capacity, |capacity, (outfit, count)| {
+                capacity.saturating_add(outfit.cargo_capacity.saturating_mul(*count))
+            })
+    }
+
+    pub fn build(&self, position: Coordinates, asset_index: &AssetIndex) -> Result<ShipBundle, ()> {
+        let mut ship = ShipBundle::new(self.ship, position, None, None, None, asset_index)?;
+
+        for (outfit, count) in &self.outfits {
+            let count = *count as f32;
+            ship.maneuverability.thrust += AccelerationScalar::new(outfit.thrust * count);
+            ship.maneuverability.maximum_speed += Speed::new(outfit.maximum_speed * count);
+            ship.maneuverability.turn_rate += AngularVelocity::radians(outfit.turn_rate * count);
+            ship.health.maximum_shield += outfit.shield * count;
+            ship.health.shield += outfit.shield * count;
+            ship.health.shield_regeneration += outfit.shield_regeneration * count;
+        }
+
+        ship.cargo.capacity = self.cargo_capacity();
+        ship.weapons = WeaponsComponent::new(
+            self.ship
+                .weapons
+                .iter()
+                .chain(self.outfit_weapons())
+                .cloned(),
+        );
+
+        Ok(ship)
+    }
+}
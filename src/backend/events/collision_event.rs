@@ -0,0 +1,73 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+use bevy_ecs::{
+    entity::Entity,
+    event::{Event, EventReader, EventWriter, Events},
+    system::{Query, ResMut},
+};
+
+use crate::backend::{
+    events::collision_layer_events::{CollisionOngoing, CollisionStarted},
+    shape::Contact,
+    world_objects::components::{
+        collision_component::{CollidableComponent, CollisionMarker},
+        physical_component::Physical,
+    },
+};
+
+// An object overlapping something solid this tick, sent once for each object which can be pushed around
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CollisionEvent {
+    pub entity: Entity,
+    pub other: Entity,
+    pub contact: Contact, // From the point of view of entity, so the normal points away from other
+}
+
+pub fn clear_old_collision_events(mut events: ResMut<Events<CollisionEvent>>) {
+    events.update();
+}
+
+// Turns the physical collision layer's overlaps into events carrying where and how deeply objects overlap
+pub fn send_collision_events(
+    mut started: EventReader<CollisionStarted<Physical>>,
+    mut ongoing: EventReader<CollisionOngoing<Physical>>,
+    receivers: Query<&CollidableComponent<Physical>>,
+    senders: Query<&CollisionMarker<Physical>>,
+    mut events: EventWriter<CollisionEvent>,
+) {
+    // Objects keep being pushed apart for as long as they overlap, not just when they first touch
+    let overlaps = started
+        .read()
+        .map(|event| (event.receiver, event.sender))
+        .chain(ongoing.read().map(|event| (event.receiver, event.sender)));
+
+    for (entity, other) in overlaps {
+        let (collidable, other_shape) = match (receivers.get(entity), senders.get(other)) {
+            (Ok(collidable), Ok(marker)) => (collidable, marker.shape),
+            _ => continue,
+        };
+
+        // The collision layer only checks overlap, which this agrees with except for shapes exactly touching
+        if let Some(contact) = collidable.shape.contact(&other_shape) {
+            events.write(CollisionEvent {
+                entity,
+                other,
+                contact,
+            });
+        }
+    }
+}
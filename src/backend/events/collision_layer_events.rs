@@ -0,0 +1,173 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+use std::marker::PhantomData;
+
+use bevy_ecs::{
+    entity::Entity,
+    event::{Event, Events},
+    system::ResMut,
+};
+
+// A receiver on collision layer T began overlapping a sender this tick
+#[derive(Event)]
+pub struct CollisionStarted<T: Send + Sync + 'static> {
+    pub receiver: Entity,
+    pub sender: Entity,
+    _phantom: PhantomData<T>,
+}
+
+// A receiver on collision layer T is still overlapping a sender it already overlapped last tick
+#[derive(Event)]
+pub struct CollisionOngoing<T: Send + Sync + 'static> {
+    pub receiver: Entity,
+    pub sender: Entity,
+    _phantom: PhantomData<T>,
+}
+
+// A receiver on collision layer T stopped overlapping a sender, either may have been despawned since
+#[derive(Event)]
+pub struct CollisionEnded<T: Send + Sync + 'static> {
+    pub receiver: Entity,
+    pub sender: Entity,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: Send + Sync + 'static> CollisionStarted<T> {
+    pub fn new(receiver: Entity, sender: Entity) -> Self {
+        Self {
+            receiver,
+            sender,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: Send + Sync + 'static> CollisionOngoing<T> {
+    pub fn new(receiver: Entity, sender: Entity) -> Self {
+        Self {
+            receiver,
+            sender,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: Send + Sync + 'static> CollisionEnded<T> {
+    pub fn new(receiver: Entity, sender: Entity) -> Self {
+        Self {
+            receiver,
+            sender,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+// Events are kept for two ticks so readers running before the collision system in a tick still see the last tick's
+pub fn clear_old_collision_layer_events<T: Send + Sync + 'static>(
+    mut started: ResMut<Events<CollisionStarted<T>>>,
+    mut ongoing: ResMut<Events<CollisionOngoing<T>>>,
+    mut ended: ResMut<Events<CollisionEnded<T>>>,
+) {
+    started.update();
+    ongoing.update();
+    ended.update();
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::event::Events;
+
+    use super::{CollisionEnded, CollisionOngoing, CollisionStarted};
+    use crate::{
+        backend::{
+            shape::{CircleData, Shape},
+            test_harness::TestHarness,
+            world_objects::components::{
+                collision_component::CollisionMarker, position_component::PositionComponent,
+                projectile_component::Damaging,
+            },
+        },
+        shared_types::{Coordinates, Radius, Velocity},
+    };
+
+    #[derive(Debug, PartialEq)]
+    enum Seen {
+        Started,
+        Ongoing,
+        Ended,
+    }
+
+    #[test]
+    fn passing_through_starts_continues_then_ends_once() {
+        let mut harness = TestHarness::new();
+        let location = Coordinates::new(100.0, 0.0);
+        let marker = harness
+            .world
+            .spawn((
+                CollisionMarker::<Damaging>::new(Shape::Circle(CircleData {
+                    location,
+                    radius: Radius::new(10.0),
+                })),
+                PositionComponent { position: location },
+            ))
+            .id();
+        let ship = harness.spawn_ship(
+            "shuttle",
+            Coordinates::zero(),
+            Velocity::new(100.0, 0.0),
+            None,
+        );
+
+        let mut seen = Vec::new();
+
+        for _ in 0..150 {
+            harness.step();
+
+            let pair = |receiver, sender| receiver == ship && sender == marker;
+            let world = &harness.world;
+
+            if world
+                .resource::<Events<CollisionStarted<Damaging>>>()
+                .iter_current_update_events()
+                .any(|event| pair(event.receiver, event.sender))
+            {
+                seen.push(Seen::Started);
+            }
+
+            if world
+                .resource::<Events<CollisionOngoing<Damaging>>>()
+                .iter_current_update_events()
+                .any(|event| pair(event.receiver, event.sender))
+            {
+                seen.push(Seen::Ongoing);
+            }
+
+            if world
+                .resource::<Events<CollisionEnded<Damaging>>>()
+                .iter_current_update_events()
+                .any(|event| pair(event.receiver, event.sender))
+            {
+                seen.push(Seen::Ended);
+            }
+        }
+
+        assert_eq!(seen.first(), Some(&Seen::Started));
+        assert_eq!(seen.last(), Some(&Seen::Ended));
+        assert!(seen.len() > 10);
+        assert!(seen[1..seen.len() - 1].iter().all(|x| *x == Seen::Ongoing));
+    }
+}
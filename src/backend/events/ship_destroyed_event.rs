@@ -0,0 +1,35 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use bevy_ecs::{
+    entity::Entity,
+    event::{Event, Events},
+    system::ResMut,
+};
+
+use crate::configuration_file_structures::reference_types::FactionReference;
+
+// A ship destroyed by weapons fire, sent while the wreck's components can still be looked up
+#[derive(Event, Clone, Debug)]
+pub struct ShipDestroyedEvent {
+    pub destroyed_by: Entity, // Ship which fired the final shot
+    pub faction: Option<FactionReference>,
+}
+
+pub fn clear_old_ship_destroyed_events(mut events: ResMut<Events<ShipDestroyedEvent>>) {
+    events.update();
+}
@@ -18,9 +18,14 @@
 pub mod components;
 pub mod configuration_file_loaders;
 pub mod data_objects;
+pub mod events;
 pub mod resources;
 pub mod shape;
 pub mod shrink_storage;
 pub mod spatial_optimizer;
 pub mod systems;
+#[cfg(test)]
+pub mod test_harness;
+pub mod world_builder;
 pub mod world_objects;
+pub mod world_save_file;
@@ -19,30 +19,32 @@ use std::time::{self, Duration};
 
 use bevy_ecs::prelude::{ResMut, Resource};
 
-const MICROSECONDS_PER_SECOND: u64 = 1_000_000;
-const FRACTIONAL_MAX_TICK_TIME: u64 = 20;
-const FRACTIONAL_MIN_TICK_TIME: u64 = 60;
-const MAXIMUM_TICK_MICROSECONDS: u64 = MICROSECONDS_PER_SECOND / FRACTIONAL_MAX_TICK_TIME;
-const MINIMUM_TICK_MICROSECONDS: u64 = MICROSECONDS_PER_SECOND / FRACTIONAL_MIN_TICK_TIME;
-pub const MAXIMUM_TICK_DURATION: Duration = time::Duration::from_micros(MAXIMUM_TICK_MICROSECONDS);
-pub const MINIMUM_TICK_DURATION: Duration = time::Duration::from_micros(MINIMUM_TICK_MICROSECONDS);
-
 #[derive(Resource)]
 pub struct DeltaTResource {
+    tick: u64,
+    minimum_tick_duration: Duration,
+    maximum_tick_duration: Duration,
     total_time: Duration,
     last_tick: Duration,
     last_tick_reported_real_world_time: Duration,
 }
 
 impl DeltaTResource {
-    pub fn new() -> Self {
+    pub fn new(minimum_tick_duration: Duration, maximum_tick_duration: Duration) -> Self {
         Self {
-            total_time: MINIMUM_TICK_DURATION,
-            last_tick: MINIMUM_TICK_DURATION,
+            tick: 0,
+            minimum_tick_duration,
+            maximum_tick_duration,
+            total_time: minimum_tick_duration,
+            last_tick: minimum_tick_duration,
             last_tick_reported_real_world_time: time::Duration::ZERO,
         }
     }
 
+    pub fn get_tick(&self) -> u64 {
+        self.tick
+    }
+
     pub fn get_total_time(&self) -> Duration {
         self.total_time
     }
@@ -59,8 +61,9 @@ impl DeltaTResource {
 pub fn increment_time(mut time: ResMut<DeltaTResource>) {
     let corrected_duration = time
         .last_tick_reported_real_world_time
-        .clamp(MINIMUM_TICK_DURATION, MAXIMUM_TICK_DURATION);
+        .clamp(time.minimum_tick_duration, time.maximum_tick_duration);
 
+    time.tick += 1;
     time.total_time = time.total_time + corrected_duration;
     time.last_tick = corrected_duration;
 }
@@ -15,4 +15,9 @@
     along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+pub mod definitions_resource;
 pub mod delta_t_resource;
+pub mod profile_save_queue_resource;
+pub mod saved_player_ships_resource;
+pub mod traffic_spawner_resource;
+pub mod viewport_settings_resource;
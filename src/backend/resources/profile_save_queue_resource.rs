@@ -0,0 +1,57 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use async_channel::Sender;
+use bevy_ecs::resource::Resource;
+
+use crate::connectivity::player_info::{
+    player_profile::PlayerProfile, player_profile_store::StoredPlayerProfile,
+};
+
+// Hands profiles whose holdings were just committed to the task which writes them to the profile store
+// Worlds built without a queue, such as in tests, simply don't save
+#[derive(Resource, Default)]
+pub struct ProfileSaveQueueResource {
+    sender: Option<Sender<StoredPlayerProfile>>,
+}
+
+impl ProfileSaveQueueResource {
+    pub fn new(sender: Sender<StoredPlayerProfile>) -> Self {
+        Self {
+            sender: Some(sender),
+        }
+    }
+
+    pub fn save(&self, profile: &PlayerProfile) {
+        let sender = match &self.sender {
+            Some(has) => has,
+            None => return,
+        };
+
+        // Token players are never stored
+        if !profile.is_persistent() {
+            return;
+        }
+
+        if sender.try_send(profile.to_stored()).is_err() {
+            tracing::error!(
+                "Could not queue player profile {} for saving",
+                profile.get_credentials().get_identifier()
+            );
+        }
+    }
+}
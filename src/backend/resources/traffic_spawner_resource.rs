@@ -0,0 +1,27 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+use std::{collections::HashMap, time::Duration};
+
+use bevy_ecs::resource::Resource;
+
+use crate::backend::configuration_file_loaders::definition_caches::star_system_definition_cache::StarSystemId;
+
+// Simulation time since each star system's traffic last spawned a ship, keyed by star system and traffic index
+#[derive(Resource, Default)]
+pub struct TrafficSpawnerResource {
+    pub time_since_spawn: HashMap<(StarSystemId, usize), Duration>,
+}
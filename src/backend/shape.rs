@@ -41,6 +41,14 @@ pub struct PointData {
     pub point: Coordinates,
 }
 
+// Where two shapes overlap, from the point of view of the first shape
+#[derive(Copy, Clone, Debug)]
+pub struct Contact {
+    pub point: Coordinates, // Midway through the overlap
+    pub normal: Vector2D<GlobalCoordinateType, WorldCoordinates>, // Unit vector pointing from the other shape toward this one
+    pub depth: Distance, // How far the shapes would have to move apart along the normal to stop overlapping
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum Shape {
     Circle(CircleData),
@@ -117,6 +125,76 @@ impl Shape {
         return AABBIterator::new(self.aabb(), hash_cell_size);
     }
 
+    // What this shape covers moving from one point to another, tubes are swept as the circle around them
+    pub fn swept(&self, from: Coordinates, to: Coordinates) -> Shape {
+        let radius = match self {
+            Shape::Circle(circle) => circle.radius,
+            Shape::RoundedTube(tube) => tube.radius + tube.length / 2.0,
+            Shape::Point(_point) => Radius::new(0.0),
+        };
+        let path = to - from;
+
+        Shape::RoundedTube(RoundedTubeData {
+            center: from.lerp(to, 0.5),
+            rotation: path.angle_from_x_axis().cast::<LocalCoordinateType>(),
+            length: Distance::new(path.length()),
+            radius,
+        })
+    }
+
+    // Every shape is a line segment swept by a radius, circles and points just have segments of no length
+    fn segment_and_radius(&self) -> (Coordinates, Coordinates, Radius) {
+        match self {
+            Shape::Circle(circle) => (circle.location, circle.location, circle.radius),
+            Shape::RoundedTube(tube) => {
+                let (point_1, point_2) = rounded_tube_points(&tube.center, &tube.rotation, &tube.length);
+                (point_1, point_2, tube.radius)
+            }
+            Shape::Point(point) => (point.point, point.point, Radius::new(0.0)),
+        }
+    }
+
+    pub fn contact(&self, other: &Shape) -> Option<Contact> {
+        let (self_point_1, self_point_2, self_radius) = self.segment_and_radius();
+        let (other_point_1, other_point_2, other_radius) = other.segment_and_radius();
+        let (self_closest, other_closest) =
+            closest_points_on_segments(self_point_1, self_point_2, other_point_1, other_point_2);
+
+        let separation = self_closest - other_closest;
+        let distance = separation.length();
+        let depth = (self_radius + other_radius).get() - distance;
+
+        if depth < 0.0 {
+            return None;
+        }
+
+        let normal = if distance > f64::EPSILON {
+            separation / distance
+        } else {
+            // Segments cross exactly, so push apart along the line between centers, or any direction if those are the same too
+            let between_centers = self.center() - other.center();
+            if between_centers.length() > f64::EPSILON {
+                between_centers.normalize()
+            } else {
+                Vector2D::new(1.0, 0.0)
+            }
+        };
+
+        Some(Contact {
+            point: other_closest + normal * (other_radius.get() - depth / 2.0),
+            normal,
+            depth: Distance::new(depth),
+        })
+    }
+
+    // Gap between the edge of this shape and a point, zero when the point is inside
+    pub fn distance_to(&self, point: Coordinates) -> Distance {
+        let (point_1, point_2, radius) = self.segment_and_radius();
+        let (closest, _) = closest_points_on_segments(point_1, point_2, point, point);
+
+        Distance::new((closest.distance_to(point) - radius.get()).max(0.0))
+    }
+
     pub fn collides(&self, other: &Shape) -> bool {
         match self {
             Shape::Circle(circle1) => match other {
@@ -149,6 +227,55 @@ fn rounded_tube_points(center: &Coordinates, rotation: &Rotation, length: &Dista
     (point_1, point_2)
 }
 
+// Closest pair of points between segments 1 and 2, one on each
+fn closest_points_on_segments(
+    segment_1_start: Coordinates,
+    segment_1_end: Coordinates,
+    segment_2_start: Coordinates,
+    segment_2_end: Coordinates,
+) -> (Coordinates, Coordinates) {
+    let direction_1 = segment_1_end - segment_1_start;
+    let direction_2 = segment_2_end - segment_2_start;
+    let between_starts = segment_1_start - segment_2_start;
+    let length_1_squared = direction_1.square_length();
+    let length_2_squared = direction_2.square_length();
+    let along_2 = direction_2.dot(between_starts);
+
+    let (s, t) = if length_1_squared <= f64::EPSILON && length_2_squared <= f64::EPSILON {
+        (0.0, 0.0)
+    } else if length_1_squared <= f64::EPSILON {
+        (0.0, (along_2 / length_2_squared).clamp(0.0, 1.0))
+    } else {
+        let along_1 = direction_1.dot(between_starts);
+
+        if length_2_squared <= f64::EPSILON {
+            ((-along_1 / length_1_squared).clamp(0.0, 1.0), 0.0)
+        } else {
+            let between_directions = direction_1.dot(direction_2);
+            let denominator = length_1_squared * length_2_squared - between_directions * between_directions;
+
+            // Parallel segments have no single closest pair, so any point on the first will do
+            let s = if denominator > f64::EPSILON {
+                ((between_directions * along_2 - along_1 * length_2_squared) / denominator).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+
+            let t = (between_directions * s + along_2) / length_2_squared;
+
+            if t < 0.0 {
+                ((-along_1 / length_1_squared).clamp(0.0, 1.0), 0.0)
+            } else if t > 1.0 {
+                (((between_directions - along_1) / length_1_squared).clamp(0.0, 1.0), 1.0)
+            } else {
+                (s, t)
+            }
+        }
+    };
+
+    (segment_1_start + direction_1 * s, segment_2_start + direction_2 * t)
+}
+
 fn dist(point_1: Coordinates, point_2: Coordinates) -> Distance {
     Distance::new(point_1.distance_to(point_2))
 }
@@ -232,3 +359,63 @@ fn point_rounded_tube(point: &PointData, tube: &RoundedTubeData) -> bool {
 fn point_point(point1: &PointData, point2: &PointData) -> bool {
     point1.point == point2.point
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn circle(x: f64, y: f64, radius: f64) -> Shape {
+        Shape::Circle(CircleData {
+            location: Coordinates::new(x, y),
+            radius: Radius::new(radius),
+        })
+    }
+
+    #[test]
+    fn circle_contact_points_away_from_other() {
+        let contact = circle(15.0, 0.0, 10.0).contact(&circle(0.0, 0.0, 10.0)).unwrap();
+
+        assert!((contact.normal.x - 1.0).abs() < 1e-9 && contact.normal.y.abs() < 1e-9);
+        assert!((contact.depth.get() - 5.0).abs() < 1e-9);
+        assert!((contact.point.x - 7.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn separate_shapes_have_no_contact() {
+        assert!(circle(25.0, 0.0, 10.0).contact(&circle(0.0, 0.0, 10.0)).is_none());
+    }
+
+    #[test]
+    fn tube_contact_uses_closest_point_along_tube() {
+        let tube = Shape::RoundedTube(RoundedTubeData {
+            center: Coordinates::new(0.0, 0.0),
+            rotation: Rotation::zero(),
+            length: Distance::new(100.0),
+            radius: Radius::new(5.0),
+        });
+
+        // Beside the end of the tube rather than its center, which is far out of reach
+        let contact = circle(40.0, 12.0, 10.0).contact(&tube).unwrap();
+
+        // Tube ends come from single precision rotations, so are only approximately where expected
+        assert!(contact.normal.x.abs() < 1e-4 && (contact.normal.y - 1.0).abs() < 1e-4);
+        assert!((contact.depth.get() - 3.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn distance_to_is_measured_from_the_edge() {
+        assert!((circle(0.0, 0.0, 10.0).distance_to(Coordinates::new(30.0, 0.0)).get() - 20.0).abs() < 1e-9);
+        assert_eq!(circle(0.0, 0.0, 10.0).distance_to(Coordinates::new(5.0, 0.0)).get(), 0.0);
+    }
+
+    #[test]
+    fn swept_circle_covers_the_path_between() {
+        let swept = circle(0.0, 0.0, 2.0).swept(Coordinates::new(0.0, 0.0), Coordinates::new(100.0, 0.0));
+
+        // Small enough to slip between where the circle started and ended
+        assert!(swept.collides(&circle(50.0, 3.0, 1.5)));
+        assert!(!circle(0.0, 0.0, 2.0).collides(&circle(50.0, 3.0, 1.5)));
+        assert!(!circle(100.0, 0.0, 2.0).collides(&circle(50.0, 3.0, 1.5)));
+        assert!(!swept.collides(&circle(50.0, 10.0, 1.5)));
+    }
+}
@@ -15,11 +15,23 @@
     along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use crate::backend::world_objects::components::collision_component::{
-    CollidableComponent, CollisionMarker,
+use std::collections::HashSet;
+
+use crate::{
+    backend::{
+        configuration_file_loaders::definition_caches::star_system_definition_cache::StarSystemId,
+        events::collision_layer_events::{CollisionEnded, CollisionOngoing, CollisionStarted},
+        shape::{CircleData, Shape},
+        shrink_storage::MutableShrinkable,
+        world_objects::components::{
+            collision_component::{CollidableComponent, CollisionMarker},
+            star_system_component::StarSystemComponent,
+        },
+    },
+    shared_types::{AABB, Coordinates, Distance, Radius},
 };
 
-use super::{hash_coordinates::*, hash_sized::HashSized};
+use super::{aabb_iterator::AABBIterator, hash_coordinates::*, hash_sized::HashSized};
 use bevy_ecs::prelude::*;
 use rayon::prelude::*;
 
@@ -28,7 +40,15 @@ enum SenderReceiver<'a, T: Send + Sync + 'static> {
     Receiver(&'a CollidableComponent<T>),
 }
 
+struct IndexedReceiver {
+    pub star_system: Option<StarSystemId>,
+    pub cell: HashCoordinates,
+    pub entity: Entity,
+    pub shape: Shape,
+}
+
 struct ObjectWithinCell<'a, T: Send + Sync + 'static> {
+    pub star_system: Option<StarSystemId>,
     pub cell: HashCoordinates,
     pub entity: Entity,
     pub sender_receiver: SenderReceiver<'a, T>,
@@ -36,70 +56,123 @@ struct ObjectWithinCell<'a, T: Send + Sync + 'static> {
 
 pub fn collision_system<T: Send + Sync + HashSized>(
     mut optimizer: ResMut<CollisionOptimizer<T>>,
-    receivers: Query<(Entity, &CollidableComponent<T>)>,
-    senders: Query<(Entity, &CollisionMarker<T>)>,
+    receivers: Query<(
+        Entity,
+        &CollidableComponent<T>,
+        Option<&StarSystemComponent>,
+    )>,
+    senders: Query<(Entity, &CollisionMarker<T>, Option<&StarSystemComponent>)>,
+    mut started: EventWriter<CollisionStarted<T>>,
+    mut ongoing: EventWriter<CollisionOngoing<T>>,
+    mut ended: EventWriter<CollisionEnded<T>>,
 ) {
     let mut list = optimizer.cache.take().unwrap();
-    list.extend(receivers.iter().flat_map(|(entity, collision_receiver)| {
-        collision_receiver
-            .shape
-            .aabb_iter(T::HASH_CELL_SIZE)
-            .map(move |coordinates| ObjectWithinCell {
-                cell: coordinates,
-                entity: entity.clone(),
-                sender_receiver: SenderReceiver::Receiver(collision_receiver),
-            })
-    }));
-
-    list.extend(senders.iter().flat_map(|(entity, collision_sender)| {
-        collision_sender
-            .shape
-            .aabb_iter(T::HASH_CELL_SIZE)
-            .map(move |coordinates| ObjectWithinCell {
-                cell: coordinates,
-                entity: entity.clone(),
-                sender_receiver: SenderReceiver::Sender(collision_sender),
-            })
-    }));
-
-    list.par_sort_unstable_by(|x, y| x.cell.cmp(&y.cell));
+    list.extend(
+        receivers
+            .iter()
+            .flat_map(|(entity, collision_receiver, star_system)| {
+                let star_system = star_system.map(|x| x.star_system);
+                collision_receiver
+                    .shape
+                    .aabb_iter(T::HASH_CELL_SIZE)
+                    .map(move |coordinates| ObjectWithinCell {
+                        star_system,
+                        cell: coordinates,
+                        entity,
+                        sender_receiver: SenderReceiver::Receiver(collision_receiver),
+                    })
+            }),
+    );
 
-    list.par_iter().enumerate().for_each(|range| {
-        let outer_object = &range.1;
+    list.extend(
+        senders
+            .iter()
+            .flat_map(|(entity, collision_sender, star_system)| {
+                let star_system = star_system.map(|x| x.star_system);
+                collision_sender
+                    .shape
+                    .aabb_iter(T::HASH_CELL_SIZE)
+                    .map(move |coordinates| ObjectWithinCell {
+                        star_system,
+                        cell: coordinates,
+                        entity,
+                        sender_receiver: SenderReceiver::Sender(collision_sender),
+                    })
+            }),
+    );
 
-        let mut inner_index = range.0 + 1;
-
-        if inner_index >= list.len() {
-            return;
-        }
+    // Star systems are sorted first so only objects in the same cell of the same star system are ever compared
+    list.par_sort_unstable_by(|x, y| (x.star_system, x.cell).cmp(&(y.star_system, y.cell)));
 
-        while inner_index < list.len() && outer_object.cell == list[inner_index].cell {
-            let inner_object = &list[inner_index];
-            match outer_object.sender_receiver {
-                SenderReceiver::Sender(sender) => match inner_object.sender_receiver {
-                    SenderReceiver::Sender(_) => (),
-                    SenderReceiver::Receiver(receiver) => {
-                        if sender.shape.collides(&receiver.shape)
-                            && (inner_object.entity != outer_object.entity)
-                        {
-                            receiver.add_to_collision_list(outer_object.entity);
+    // Objects spanning several cells can meet in more than one of them, so pairs of (receiver, sender) are deduplicated
+    let current: HashSet<(Entity, Entity)> = list
+        .par_iter()
+        .enumerate()
+        .flat_map_iter(|(index, outer_object)| {
+            list[index + 1..]
+                .iter()
+                .take_while(move |inner_object| {
+                    outer_object.cell == inner_object.cell
+                        && outer_object.star_system == inner_object.star_system
+                })
+                .filter(move |inner_object| inner_object.entity != outer_object.entity)
+                .filter_map(move |inner_object| {
+                    match (&outer_object.sender_receiver, &inner_object.sender_receiver) {
+                        (SenderReceiver::Sender(sender), SenderReceiver::Receiver(receiver)) => {
+                            if sender.shape.collides(&receiver.shape) {
+                                Some((inner_object.entity, outer_object.entity))
+                            } else {
+                                None
+                            }
                         }
-                    }
-                },
-                SenderReceiver::Receiver(receiver) => match inner_object.sender_receiver {
-                    SenderReceiver::Sender(sender) => {
-                        if sender.shape.collides(&receiver.shape)
-                            && (inner_object.entity != outer_object.entity)
-                        {
-                            receiver.add_to_collision_list(inner_object.entity);
+                        (SenderReceiver::Receiver(receiver), SenderReceiver::Sender(sender)) => {
+                            if sender.shape.collides(&receiver.shape) {
+                                Some((outer_object.entity, inner_object.entity))
+                            } else {
+                                None
+                            }
                         }
+                        _ => None,
                     }
-                    SenderReceiver::Receiver(_) => (),
-                },
-            };
-            inner_index += 1;
+                })
+        })
+        .collect();
+
+    for (receiver, sender) in current.iter().copied() {
+        if optimizer.previous_collisions.contains(&(receiver, sender)) {
+            ongoing.write(CollisionOngoing::new(receiver, sender));
+        } else {
+            started.write(CollisionStarted::new(receiver, sender));
         }
-    });
+    }
+
+    for (receiver, sender) in optimizer.previous_collisions.difference(&current).copied() {
+        ended.write(CollisionEnded::new(receiver, sender));
+    }
+
+    optimizer.previous_collisions = current;
+
+    // Receivers are kept sorted the same way between ticks so spatial queries can look cells up by binary search
+    let index = &mut optimizer.receiver_index;
+    index.clear();
+    (&mut *index).shrink_storage(); // Shrinking is implemented for mutable references to vectors, not vectors themselves
+    index.extend(
+        receivers
+            .iter()
+            .flat_map(|(entity, collision_receiver, star_system)| {
+                let star_system = star_system.map(|x| x.star_system);
+                let shape = collision_receiver.shape;
+                shape
+                    .aabb_iter(T::HASH_CELL_SIZE)
+                    .map(move |cell| IndexedReceiver {
+                        star_system,
+                        cell,
+                        entity,
+                        shape,
+                    })
+            }),
+    );
+    index.par_sort_unstable_by_key(|x| (x.star_system, x.cell));
 
     list.clear();
     optimizer.cache = list.into_iter().map(|_| unreachable!()).collect();
@@ -108,12 +181,149 @@ pub fn collision_system<T: Send + Sync + HashSized>(
 #[derive(Resource)]
 pub struct CollisionOptimizer<T: Send + Sync + 'static> {
     cache: Option<Vec<ObjectWithinCell<'static, T>>>,
+    previous_collisions: HashSet<(Entity, Entity)>, // Pairs of (receiver, sender) overlapping last tick
+    receiver_index: Vec<IndexedReceiver>,
 }
 
 impl<T: Send + Sync + 'static> CollisionOptimizer<T> {
     pub fn new() -> Self {
         Self {
             cache: Some(Vec::new()),
+            previous_collisions: HashSet::new(),
+            receiver_index: Vec::new(),
         }
     }
 }
+
+// Queries against the receivers of this layer as they were at the last run of its collision system
+impl<T: Send + Sync + HashSized + 'static> CollisionOptimizer<T> {
+    // Every receiver in any cell the bounds touch, once each
+    fn candidates(&self, star_system: Option<StarSystemId>, bounds: AABB) -> Vec<&IndexedReceiver> {
+        let mut found: Vec<&IndexedReceiver> = AABBIterator::new(bounds, T::HASH_CELL_SIZE)
+            .flat_map(|cell| {
+                let start = self
+                    .receiver_index
+                    .partition_point(|x| (x.star_system, x.cell) < (star_system, cell));
+                self.receiver_index[start..]
+                    .iter()
+                    .take_while(move |x| x.star_system == star_system && x.cell == cell)
+            })
+            .collect();
+
+        // Shapes spanning several cells turn up once for each of them
+        found.sort_unstable_by_key(|x| x.entity);
+        found.dedup_by_key(|x| x.entity);
+        found
+    }
+
+    pub fn within_radius(
+        &self,
+        star_system: Option<StarSystemId>,
+        center: Coordinates,
+        radius: Radius,
+    ) -> Vec<Entity> {
+        let probe = Shape::Circle(CircleData {
+            location: center,
+            radius,
+        });
+
+        self.candidates(star_system, probe.aabb())
+            .into_iter()
+            .filter(|x| probe.collides(&x.shape))
+            .map(|x| x.entity)
+            .collect()
+    }
+
+    // Closest receivers accepted by the filter, nearest first, measured to the edge of their shapes
+    pub fn nearest(
+        &self,
+        star_system: Option<StarSystemId>,
+        center: Coordinates,
+        count: usize,
+        maximum_distance: Distance,
+        mut filter: impl FnMut(Entity) -> bool,
+    ) -> Vec<(Entity, Distance)> {
+        let bounds = Shape::Circle(CircleData {
+            location: center,
+            radius: maximum_distance,
+        })
+        .aabb();
+
+        let mut found: Vec<(Entity, Distance)> = self
+            .candidates(star_system, bounds)
+            .into_iter()
+            .filter(|x| filter(x.entity))
+            .map(|x| (x.entity, x.shape.distance_to(center)))
+            .filter(|(_, distance)| *distance <= maximum_distance)
+            .collect();
+
+        found.sort_unstable_by(|x, y| x.1.get().total_cmp(&y.1.get()));
+        found.truncate(count);
+        found
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CollisionOptimizer;
+    use crate::{
+        backend::{
+            resources::definitions_resource::DefinitionsResource, test_harness::TestHarness,
+            world_objects::components::physical_component::Physical,
+        },
+        shared_types::{Coordinates, Distance, Radius, Velocity},
+    };
+
+    #[test]
+    fn queries_find_receivers_in_the_same_star_system() {
+        let mut harness = TestHarness::new();
+        let near = harness.spawn_ship(
+            "shuttle",
+            Coordinates::new(100.0, 0.0),
+            Velocity::zero(),
+            Some("sol"),
+        );
+        harness.spawn_ship(
+            "shuttle",
+            Coordinates::new(3000.0, 0.0),
+            Velocity::zero(),
+            Some("sol"),
+        );
+        let behind = harness.spawn_ship(
+            "shuttle",
+            Coordinates::new(-200.0, 0.0),
+            Velocity::zero(),
+            Some("sol"),
+        );
+        harness.spawn_ship(
+            "shuttle",
+            Coordinates::new(100.0, 0.0),
+            Velocity::zero(),
+            Some("alpha"),
+        );
+        harness.step();
+
+        let sol = harness
+            .world
+            .resource::<DefinitionsResource>()
+            .definitions
+            .get_star_systems()
+            .get_star_system_id("sol");
+        let optimizer = harness.world.resource::<CollisionOptimizer<Physical>>();
+
+        let mut within = optimizer.within_radius(sol, Coordinates::zero(), Radius::new(500.0));
+        within.sort();
+        let mut expected = vec![near, behind];
+        expected.sort();
+        assert_eq!(within, expected);
+
+        let nearest: Vec<_> = optimizer
+            .nearest(sol, Coordinates::zero(), 2, Distance::new(5000.0), |_| true)
+            .into_iter()
+            .map(|(ship, _)| ship)
+            .collect();
+        assert_eq!(nearest, vec![near, behind]);
+
+    }
+}
@@ -0,0 +1,247 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+use bevy_ecs::{
+    entity::Entity,
+    event::EventReader,
+    system::{Commands, Query},
+};
+use euclid::Vector2D;
+
+use crate::{
+    backend::{
+        events::collision_event::CollisionEvent,
+        world_objects::components::{
+            health_component::HealthComponent, physical_component::MassComponent,
+            position_component::PositionComponent, velocity_component::VelocityComponent,
+        },
+    },
+    shared_types::{GlobalCoordinateType, Velocity, WorldCoordinates},
+};
+
+// Fraction of the speed objects hit each other at which they bounce back apart
+const RESTITUTION: f32 = 0.5;
+
+// Impacts slower than this only bounce, faster ones also damage whatever was hit
+const HARMLESS_IMPACT_SPEED: f32 = 50.0;
+
+// Damage dealt to both shields and hull for each unit of impact speed above the harmless speed
+const DAMAGE_PER_IMPACT_SPEED: f32 = 0.5;
+
+struct CollisionResponse {
+    entity: Entity,
+    push: Vector2D<GlobalCoordinateType, WorldCoordinates>,
+    velocity_change: Velocity,
+    damage: f32,
+}
+
+// Pushes overlapping objects apart and bounces them off each other, damaging them if they hit hard enough
+pub fn apply_collision_responses(
+    mut events: EventReader<CollisionEvent>,
+    mut bodies: Query<(
+        &mut PositionComponent,
+        &mut VelocityComponent,
+        &MassComponent,
+    )>,
+    mut healths: Query<&mut HealthComponent>,
+    mut commands: Commands,
+) {
+    // Every response is worked out before any are applied, so both sides of a collision see the same velocities
+    let mut responses = Vec::new();
+
+    for event in events.read() {
+        let (velocity, inverse_mass) = match bodies.get(event.entity) {
+            Ok((_, velocity, mass)) => (velocity.velocity, 1.0 / mass.mass.max(f32::EPSILON)),
+            Err(_) => continue,
+        };
+
+        // Anything without mass, like a solid planetoid, is immovable
+        let (other_velocity, other_inverse_mass) = match bodies.get(event.other) {
+            Ok((_, velocity, mass)) => (velocity.velocity, 1.0 / mass.mass.max(f32::EPSILON)),
+            Err(_) => (Velocity::zero(), 0.0),
+        };
+
+        let total_inverse_mass = inverse_mass + other_inverse_mass;
+        let share = inverse_mass / total_inverse_mass; // Lighter objects are moved more
+        let contact = event.contact;
+        let normal = Velocity::new(contact.normal.x as f32, contact.normal.y as f32);
+
+        // Each side only moves itself, so between them they cover the whole overlap
+        let push = contact.normal * (contact.depth.get() * share as f64);
+
+        let approach_speed = -(velocity - other_velocity).dot(normal);
+
+        let (velocity_change, damage) = if approach_speed > 0.0 {
+            let impulse = (1.0 + RESTITUTION) * approach_speed / total_inverse_mass;
+            let damage =
+                (approach_speed - HARMLESS_IMPACT_SPEED).max(0.0) * DAMAGE_PER_IMPACT_SPEED * share;
+
+            tracing::trace!(
+                "Entity {:?} hit {:?} at {:?} with speed {}",
+                event.entity,
+                event.other,
+                contact.point,
+                approach_speed
+            );
+
+            (normal * (impulse * inverse_mass), damage)
+        } else {
+            (Velocity::zero(), 0.0) // Already separating, so only the overlap needs fixing
+        };
+
+        responses.push(CollisionResponse {
+            entity: event.entity,
+            push,
+            velocity_change,
+            damage,
+        });
+    }
+
+    for response in responses {
+        if let Ok((mut position, mut velocity, _)) = bodies.get_mut(response.entity) {
+            position.position += response.push;
+            velocity.velocity += response.velocity_change;
+        }
+
+        if response.damage <= 0.0 {
+            continue;
+        }
+
+        if let Ok(mut health) = healths.get_mut(response.entity) {
+            health.apply_damage(response.damage, response.damage);
+
+            if health.is_destroyed() {
+                tracing::trace!("Entity {:?} was destroyed in a collision", response.entity);
+                commands.entity(response.entity).try_despawn();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backend::{
+            test_harness::{TestHarness, test_planetoid},
+            world_objects::components::{
+                health_component::HealthComponent, position_component::PositionComponent,
+                velocity_component::VelocityComponent,
+            },
+        },
+        shared_types::{Coordinates, Velocity},
+    };
+
+    #[test]
+    fn ships_bounce_off_each_other() {
+        let mut harness = TestHarness::new();
+        let left = harness.spawn_ship(
+            "shuttle",
+            Coordinates::new(-100.0, 0.0),
+            Velocity::new(40.0, 0.0),
+            Some("sol"),
+        );
+        let right = harness.spawn_ship(
+            "shuttle",
+            Coordinates::new(100.0, 0.0),
+            Velocity::new(-40.0, 0.0),
+            Some("sol"),
+        );
+
+        harness.step_for(200);
+
+        let left_velocity = harness
+            .world
+            .get::<VelocityComponent>(left)
+            .unwrap()
+            .velocity;
+        let right_velocity = harness
+            .world
+            .get::<VelocityComponent>(right)
+            .unwrap()
+            .velocity;
+        let left_position = harness
+            .world
+            .get::<PositionComponent>(left)
+            .unwrap()
+            .position;
+        let right_position = harness
+            .world
+            .get::<PositionComponent>(right)
+            .unwrap()
+            .position;
+
+        assert!(left_velocity.x < 0.0 && right_velocity.x > 0.0);
+        assert!(left_position.x < right_position.x);
+
+        // Gentle bumps do no damage
+        let health = harness.world.get::<HealthComponent>(left).unwrap();
+        assert_eq!(health.shield, health.maximum_shield);
+    }
+
+    #[test]
+    fn ships_bounce_off_solid_planetoids_and_take_damage() {
+        let mut harness = TestHarness::new();
+        let mut record = test_planetoid("rock", Coordinates::new(0.0, 0.0));
+        record.collision_radius = Some(100.0);
+        record.may_be_landed_on = None;
+        harness.spawn_planetoid(&record, Some("sol"));
+
+        let ship = harness.spawn_ship(
+            "shuttle",
+            Coordinates::new(-200.0, 0.0),
+            Velocity::new(200.0, 0.0),
+            Some("sol"),
+        );
+
+        harness.step_for(50);
+
+        let position = harness
+            .world
+            .get::<PositionComponent>(ship)
+            .unwrap()
+            .position;
+        let velocity = harness
+            .world
+            .get::<VelocityComponent>(ship)
+            .unwrap()
+            .velocity;
+        assert!(position.x < -100.0);
+        assert!(velocity.x < 0.0);
+
+        let health = harness.world.get::<HealthComponent>(ship).unwrap();
+        assert!(health.shield < health.maximum_shield);
+    }
+
+    #[test]
+    fn ships_fly_over_planetoids_which_are_not_solid() {
+        let mut harness = TestHarness::new();
+        let ship = harness.spawn_ship(
+            "shuttle",
+            Coordinates::new(200.0, 0.0),
+            Velocity::new(200.0, 0.0),
+            Some("sol"),
+        );
+
+        harness.step_for(50);
+
+        let position = harness
+            .world
+            .get::<PositionComponent>(ship)
+            .unwrap()
+            .position;
+        assert!(position.x > 350.0);
+    }
+}
@@ -0,0 +1,415 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+use bevy_ecs::{
+    entity::Entity,
+    query::Without,
+    system::{Commands, Query, Res},
+};
+use euclid::num::Zero;
+
+use crate::{
+    backend::{
+        resources::delta_t_resource::DeltaTResource,
+        systems::{
+            apply_player_control::PlayerControllablePhysics, player_landing::MAXIMUM_LANDING_SPEED,
+        },
+        world_objects::components::{
+            angular_velocity_component::AngularVelocityComponent,
+            hyperspace_jump_component::HyperspaceJumpComponent,
+            maneuverability_component::ManeuverabilityComponent,
+            npc_controller_component::{NpcBehavior, NpcControllerComponent},
+            planetoid_component::LandablePlanetoidComponent,
+            position_component::PositionComponent,
+            rotation_component::RotationComponent,
+            semi_newtonian_physics_component::SemiNewtonianPhysicsComponent,
+            star_system_component::{StarSystemComponent, in_same_star_system},
+            velocity_component::VelocityComponent,
+        },
+    },
+    shared_types::{AccelerationScalar, AngularVelocity, Coordinates, Speed, Velocity},
+};
+
+// How close a ship must get to a destination for it to count as reached
+const ARRIVAL_DISTANCE: f64 = 50.0;
+
+// How close a patrolling ship must get to a waypoint before heading for the next one
+const WAYPOINT_DISTANCE: f64 = 200.0;
+
+// Ships only fire their engines when pointed within this many radians of where they want to accelerate
+const FACING_TOLERANCE: f32 = 0.2;
+
+// Differences from the desired velocity smaller than this are not worth correcting
+const SPEED_TOLERANCE: f32 = 5.0;
+
+// Fraction of the ideal braking speed to approach at, leaving time to turn around before braking
+const BRAKING_MARGIN: f64 = 0.6;
+
+// The velocity which flies toward a point as fast as possible while still being able to stop short of it by stop_distance
+fn arrival_velocity(
+    position: Coordinates,
+    destination: Coordinates,
+    stop_distance: f64,
+    maximum_speed: Speed,
+    thrust: AccelerationScalar,
+) -> Velocity {
+    let offset = destination - position;
+    let distance = offset.length();
+    let remaining = distance - stop_distance;
+
+    if remaining <= 0.0 || distance <= 0.0 {
+        return Velocity::zero();
+    }
+
+    let braking_speed = (2.0 * thrust.get() as f64 * remaining).sqrt() * BRAKING_MARGIN;
+    let speed = braking_speed.min(maximum_speed.get() as f64);
+    let direction = offset / distance;
+
+    Velocity::new((direction.x * speed) as f32, (direction.y * speed) as f32)
+}
+
+fn flee_velocity(position: Coordinates, threat: Coordinates, maximum_speed: Speed) -> Velocity {
+    let offset = position - threat;
+    let distance = offset.length();
+
+    if distance <= 0.0 {
+        // Sitting right on top of the threat, so any direction is as good as another
+        return Velocity::new(maximum_speed.get(), 0.0);
+    }
+
+    let direction = offset / distance;
+    Velocity::new(direction.x as f32, direction.y as f32) * maximum_speed.get()
+}
+
+pub fn apply_npc_control(
+    mut npcs: Query<
+        (
+            Entity,
+            &mut NpcControllerComponent,
+            &ManeuverabilityComponent,
+            &RotationComponent,
+            &mut SemiNewtonianPhysicsComponent,
+        ),
+        Without<HyperspaceJumpComponent>,
+    >,
+    // Planetoids have no velocity but can still be steered toward
+    motion: Query<(
+        &PositionComponent,
+        Option<&VelocityComponent>,
+        Option<&StarSystemComponent>,
+    )>,
+    landable: Query<&LandablePlanetoidComponent>,
+    mut angular_velocity_components: Query<&mut AngularVelocityComponent>,
+    time: Res<DeltaTResource>,
+    mut commands: Commands,
+) {
+    let delta_t = time.get_last_tick_duration().as_secs_f32();
+
+    for (entity, mut npc, maneuverability, rotation, mut physics_component) in npcs.iter_mut() {
+        let (position, velocity, star_system) = match motion.get(entity) {
+            Ok((position, Some(velocity), star_system)) => {
+                (position.position, velocity.velocity, star_system)
+            }
+            _ => continue,
+        };
+        let maximum_speed = physics_component.maximum_speed;
+
+        // Anything in another star system may as well not exist
+        let locate = |target: Entity| match motion.get(target) {
+            Ok((target_position, target_velocity, target_star_system)) => {
+                if in_same_star_system(star_system, target_star_system) {
+                    Some((
+                        target_position.position,
+                        target_velocity.map_or(Velocity::zero(), |has| has.velocity),
+                    ))
+                } else {
+                    None
+                }
+            }
+            Err(_) => None,
+        };
+
+        let mut lost_target = false;
+
+        let desired_velocity = match &mut npc.behavior {
+            NpcBehavior::Idle => None,
+            NpcBehavior::Patrol {
+                waypoints,
+                next_waypoint,
+            } => match waypoints
+                .get(*next_waypoint)
+                .and_then(|waypoint| locate(*waypoint))
+            {
+                Some((waypoint_position, _)) => {
+                    if position.distance_to(waypoint_position) <= WAYPOINT_DISTANCE {
+                        *next_waypoint = (*next_waypoint + 1) % waypoints.len();
+                    }
+
+                    Some(arrival_velocity(
+                        position,
+                        waypoint_position,
+                        0.0,
+                        maximum_speed,
+                        maneuverability.thrust,
+                    ))
+                }
+                None => {
+                    lost_target = true;
+                    None
+                }
+            },
+            NpcBehavior::TravelTo { destination } => {
+                if position.distance_to(*destination) > ARRIVAL_DISTANCE {
+                    Some(arrival_velocity(
+                        position,
+                        *destination,
+                        0.0,
+                        maximum_speed,
+                        maneuverability.thrust,
+                    ))
+                } else if velocity.length() > SPEED_TOLERANCE {
+                    Some(Velocity::zero())
+                } else {
+                    lost_target = true; // Nothing left to do once stopped there
+                    None
+                }
+            }
+            NpcBehavior::Follow { target, distance } => match locate(*target) {
+                Some((target_position, target_velocity)) => Some(
+                    (arrival_velocity(
+                        position,
+                        target_position,
+                        distance.get(),
+                        maximum_speed,
+                        maneuverability.thrust,
+                    ) + target_velocity)
+                        .with_max_length(maximum_speed.get()),
+                ),
+                None => {
+                    lost_target = true;
+                    None
+                }
+            },
+            NpcBehavior::Flee { target, distance } => match locate(*target) {
+                Some((target_position, _)) => {
+                    if position.distance_to(target_position) < distance.get() {
+                        Some(flee_velocity(position, target_position, maximum_speed))
+                    } else {
+                        None // Far enough away to stop running, but still wary
+                    }
+                }
+                None => {
+                    lost_target = true;
+                    None
+                }
+            },
+            NpcBehavior::Land { planetoid } => {
+                match (locate(*planetoid), landable.get(*planetoid)) {
+                    (Some((planetoid_position, _)), Ok(landable)) => {
+                        let landing_radius = landable.landing_radius as f64;
+
+                        if position.distance_to(planetoid_position) > landing_radius {
+                            // Aim to stop well inside the landing radius so braking late still ends up in it
+                            Some(arrival_velocity(
+                                position,
+                                planetoid_position,
+                                landing_radius / 2.0,
+                                maximum_speed,
+                                maneuverability.thrust,
+                            ))
+                        } else if velocity.length() > MAXIMUM_LANDING_SPEED {
+                            Some(Velocity::zero())
+                        } else {
+                            // Landed ships leave the world, the same as players do
+                            commands.entity(entity).despawn();
+                            continue;
+                        }
+                    }
+                    _ => {
+                        lost_target = true;
+                        None
+                    }
+                }
+            }
+        };
+
+        if lost_target {
+            npc.behavior = NpcBehavior::Idle;
+        }
+
+        let correction = match desired_velocity {
+            Some(desired) => desired - velocity,
+            None => Velocity::zero(),
+        };
+
+        let (thrust, turn) = if correction.length() <= SPEED_TOLERANCE {
+            (AccelerationScalar::zero(), AngularVelocity::zero())
+        } else {
+            // Point the ship along the change in velocity wanted, which turns it around to brake when going too fast
+            let heading_error = (correction.angle_from_x_axis() - rotation.rotation).signed();
+
+            // Turn no further than needed this tick, so the ship settles on its heading instead of oscillating around it
+            let turn_rate = maneuverability.turn_rate.radians;
+            let turn = if delta_t > 0.0 {
+                (heading_error.radians / delta_t).clamp(-turn_rate, turn_rate)
+            } else {
+                0.0
+            };
+
+            let thrust = if heading_error.radians.abs() <= FACING_TOLERANCE {
+                maneuverability.thrust
+            } else {
+                AccelerationScalar::zero()
+            };
+
+            (thrust, AngularVelocity::radians(turn))
+        };
+
+        physics_component.set_acceleration(thrust);
+
+        if let Ok(mut angular_velocity) = angular_velocity_components.get_mut(entity) {
+            angular_velocity.angular_velocity = turn;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backend::{
+            test_harness::TestHarness,
+            world_objects::components::{
+                npc_controller_component::{NpcBehavior, NpcControllerComponent},
+                position_component::PositionComponent,
+            },
+        },
+        shared_types::{Coordinates, Distance, Velocity},
+    };
+
+    #[test]
+    fn travel_to_stops_at_destination_and_goes_idle() {
+        let mut harness = TestHarness::new();
+        let destination = Coordinates::new(-800.0, 600.0);
+        let ship = harness.spawn_ship(
+            "shuttle",
+            Coordinates::zero(),
+            Velocity::zero(),
+            Some("sol"),
+        );
+        harness
+            .world
+            .entity_mut(ship)
+            .insert(NpcControllerComponent::new(NpcBehavior::TravelTo {
+                destination,
+            }));
+
+        harness.step_for(3000);
+
+        let npc = harness.world.get::<NpcControllerComponent>(ship).unwrap();
+        assert!(matches!(npc.behavior, NpcBehavior::Idle));
+
+        let position = harness
+            .world
+            .get::<PositionComponent>(ship)
+            .unwrap()
+            .position;
+        assert!(position.distance_to(destination) <= super::ARRIVAL_DISTANCE);
+    }
+
+    #[test]
+    fn flee_moves_away_from_threat() {
+        let mut harness = TestHarness::new();
+        let threat = harness.spawn_ship(
+            "shuttle",
+            Coordinates::zero(),
+            Velocity::zero(),
+            Some("sol"),
+        );
+        let ship = harness.spawn_ship(
+            "shuttle",
+            Coordinates::new(-100.0, 0.0),
+            Velocity::zero(),
+            Some("sol"),
+        );
+        harness
+            .world
+            .entity_mut(ship)
+            .insert(NpcControllerComponent::new(NpcBehavior::Flee {
+                target: threat,
+                distance: Distance::new(1000.0),
+            }));
+
+        harness.step_for(400);
+
+        let position = harness
+            .world
+            .get::<PositionComponent>(ship)
+            .unwrap()
+            .position;
+        assert!(position.x < -300.0);
+    }
+
+    #[test]
+    fn lost_target_goes_idle() {
+        let mut harness = TestHarness::new();
+        let target = harness.spawn_ship(
+            "shuttle",
+            Coordinates::zero(),
+            Velocity::zero(),
+            Some("alpha"),
+        );
+        let ship = harness.spawn_ship(
+            "shuttle",
+            Coordinates::zero(),
+            Velocity::zero(),
+            Some("sol"),
+        );
+        harness
+            .world
+            .entity_mut(ship)
+            .insert(NpcControllerComponent::new(NpcBehavior::Follow {
+                target,
+                distance: Distance::new(100.0),
+            }));
+
+        harness.step();
+
+        let npc = harness.world.get::<NpcControllerComponent>(ship).unwrap();
+        assert!(matches!(npc.behavior, NpcBehavior::Idle));
+    }
+
+    #[test]
+    fn land_despawns_at_planetoid() {
+        let mut harness = TestHarness::new();
+        let earth = harness.find_planetoid("earth").unwrap();
+        let ship = harness.spawn_ship(
+            "shuttle",
+            Coordinates::new(-500.0, 0.0),
+            Velocity::new(0.0, 50.0),
+            Some("sol"),
+        );
+        harness
+            .world
+            .entity_mut(ship)
+            .insert(NpcControllerComponent::new(NpcBehavior::Land {
+                planetoid: earth,
+            }));
+
+        harness.step_for(3000);
+
+        assert!(harness.world.get_entity(ship).is_err());
+    }
+}
@@ -19,35 +19,79 @@ use bevy_ecs::{
     component::{Component, Mutable},
     entity::Entity,
     hierarchy::ChildOf,
-    system::Query,
+    query::Without,
+    system::{Query, Res},
 };
-use euclid::num::Zero;
+use euclid::{Angle, num::Zero};
 
 use crate::{
     backend::{
         components::session::player_session_component::PlayerSessionComponent,
+        resources::delta_t_resource::DeltaTResource,
         world_objects::components::{
-            angular_velocity_component::AngularVelocityComponent,
+            angular_velocity_component::AngularVelocityComponent, fuel_component::FuelComponent,
+            hyperspace_jump_component::HyperspaceJumpComponent,
+            maneuverability_component::ManeuverabilityComponent,
             player_controlled_component::PlayerControlledComponent,
+            rotation_component::RotationComponent, velocity_component::VelocityComponent,
+            weapons_component::WeaponsComponent,
         },
     },
-    shared_types::{AccelerationScalar, AngularVelocity},
+    configuration_file_structures::ship_configuration_file::ReverseThrustMode,
+    shared_types::{AccelerationScalar, AngularVelocity, Speed},
 };
 
+// Slowest a ship can be going and still have a direction of travel worth turning against
+const RETRO_BURN_MINIMUM_SPEED: f32 = 1.0;
+
 pub trait PlayerControllablePhysics {
     fn set_acceleration(&mut self, acceleration: AccelerationScalar);
+    fn set_maximum_speed(&mut self, maximum_speed: Speed);
+    fn set_braking(&mut self, deceleration: AccelerationScalar);
 }
 
-const PLAYER_ACCELERATION_PLACEHOLDER: f32 = 40.0;
-const PLAYER_ANGULAR_VELOCITY_PLACEHOLDER: f32 = std::f32::consts::PI / 2.0;
+// Turn rate which brings the ship round to face against its velocity, as fast as it can turn without overshooting this tick
+fn retro_burn_turn(
+    rotation: &RotationComponent,
+    velocity: &VelocityComponent,
+    turn_rate: AngularVelocity,
+    delta_t: f32,
+) -> AngularVelocity {
+    if velocity.velocity.length() < RETRO_BURN_MINIMUM_SPEED || delta_t <= 0.0 {
+        return AngularVelocity::zero();
+    }
+
+    let retrograde = velocity.velocity.angle_from_x_axis() + Angle::pi();
+    let heading_error = (retrograde - rotation.rotation).signed();
 
+    AngularVelocity::radians(
+        (heading_error.radians / delta_t).clamp(-turn_rate.radians, turn_rate.radians),
+    )
+}
+
+// Ships in hyperspace are left to the jump drive
 pub fn apply_player_control<T: PlayerControllablePhysics + Component<Mutability = Mutable>>(
-    mut controllable: Query<(Entity, &PlayerControlledComponent, &mut T, &ChildOf)>,
+    mut controllable: Query<
+        (
+            Entity,
+            &PlayerControlledComponent,
+            &ManeuverabilityComponent,
+            &mut T,
+            &ChildOf,
+        ),
+        Without<HyperspaceJumpComponent>,
+    >,
     mut angular_velocity_components: Query<&mut AngularVelocityComponent>,
+    mut weapons_components: Query<&mut WeaponsComponent>,
+    mut fuel_components: Query<&mut FuelComponent>,
+    motion: Query<(&RotationComponent, &VelocityComponent)>,
     sessions: Query<&PlayerSessionComponent>,
+    time: Res<DeltaTResource>,
 ) {
+    let delta_t = time.get_last_tick_duration().as_secs_f32();
+
     controllable.iter_mut().for_each(
-        |(entity, _player_controls, mut physics_component, parent_session)| {
+        |(entity, _player_controls, maneuverability, mut physics_component, parent_session)| {
             let session = match sessions.get(parent_session.parent()) {
                 Ok(session) => session,
                 Err(_) => return,
@@ -55,27 +99,202 @@ pub fn apply_player_control<T: PlayerControllablePhysics + Component<Mutability
 
             let input_status = session.input_status;
 
-            if input_status.forward {
-                physics_component
-                    .set_acceleration(AccelerationScalar::new(PLAYER_ACCELERATION_PLACEHOLDER));
-            } else {
-                physics_component.set_acceleration(AccelerationScalar::zero());
+            // The afterburner thrusts on its own, and only for as long as there is fuel to burn
+            let afterburner = match (input_status.afterburner, maneuverability.afterburner) {
+                (true, Some(afterburner)) => match fuel_components.get_mut(entity) {
+                    Ok(mut fuel) => fuel
+                        .burn(afterburner.fuel_per_second * delta_t)
+                        .then_some(afterburner),
+                    Err(_) => None,
+                },
+                _ => None,
+            };
+
+            match afterburner {
+                Some(afterburner) => {
+                    physics_component.set_acceleration(AccelerationScalar::new(afterburner.thrust));
+                    physics_component.set_maximum_speed(Speed::new(afterburner.maximum_speed));
+                }
+                None => {
+                    if input_status.forward {
+                        physics_component.set_acceleration(maneuverability.thrust);
+                    } else {
+                        physics_component.set_acceleration(AccelerationScalar::zero());
+                    }
+
+                    physics_component.set_maximum_speed(maneuverability.maximum_speed);
+                }
+            }
+
+            let thrusting = input_status.forward || afterburner.is_some();
+            let reversing = input_status.backward && !thrusting;
+
+            match (reversing, maneuverability.reverse_thrust) {
+                (true, ReverseThrustMode::Brake) => {
+                    physics_component.set_braking(maneuverability.thrust)
+                }
+                _ => physics_component.set_braking(AccelerationScalar::zero()),
             }
 
             match angular_velocity_components.get_mut(entity) {
                 Ok(mut angular_velocity) => {
                     if input_status.left && !input_status.right {
-                        angular_velocity.angular_velocity =
-                            -AngularVelocity::radians(PLAYER_ANGULAR_VELOCITY_PLACEHOLDER);
+                        angular_velocity.angular_velocity = -maneuverability.turn_rate;
                     } else if input_status.right && !input_status.left {
-                        angular_velocity.angular_velocity =
-                            AngularVelocity::radians(PLAYER_ANGULAR_VELOCITY_PLACEHOLDER);
+                        angular_velocity.angular_velocity = maneuverability.turn_rate;
+                    } else if reversing
+                        && matches!(maneuverability.reverse_thrust, ReverseThrustMode::RetroBurn)
+                    {
+                        angular_velocity.angular_velocity = match motion.get(entity) {
+                            Ok((rotation, velocity)) => retro_burn_turn(
+                                rotation,
+                                velocity,
+                                maneuverability.turn_rate,
+                                delta_t,
+                            ),
+                            Err(_) => AngularVelocity::zero(),
+                        };
                     } else {
                         angular_velocity.angular_velocity = -AngularVelocity::zero();
                     }
                 }
                 Err(_) => (), // We can't change angular velocity directly on this entity
             };
+
+            // Unarmed entities simply have nothing to fire
+            if let Ok(mut weapons) = weapons_components.get_mut(entity) {
+                weapons.trigger_pulled = input_status.fire;
+            }
         },
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::entity::Entity;
+
+    use crate::{
+        backend::{
+            test_harness::{TestHarness, TestSession},
+            world_objects::components::{
+                fuel_component::FuelComponent, maneuverability_component::ManeuverabilityComponent,
+                position_component::PositionComponent, rotation_component::RotationComponent,
+                velocity_component::VelocityComponent,
+            },
+        },
+        configuration_file_structures::ship_configuration_file::{
+            AfterburnerRecord, ReverseThrustMode,
+        },
+        connectivity::client_server_message::{ClientServerMessage, ControlInput},
+        shared_types::{Coordinates, Rotation, Velocity},
+    };
+
+    // A player ship coasting along the x axis, well away from anything it could run into
+    fn coasting_player_ship(harness: &mut TestHarness) -> (TestSession, Entity) {
+        let session = harness.connect_player();
+        harness.step_for(3);
+
+        let ship = harness.player_ship(&session);
+
+        let mut ship_entity = harness.world.entity_mut(ship);
+        ship_entity.insert((
+            PositionComponent {
+                position: Coordinates::new(-5000.0, 5000.0),
+            },
+            VelocityComponent {
+                velocity: Velocity::new(100.0, 0.0),
+            },
+            RotationComponent {
+                rotation: Rotation::zero(),
+            },
+        ));
+
+        (session, ship)
+    }
+
+    fn press(session: &TestSession, input: ControlInput) {
+        session.send(ClientServerMessage::ControlInput {
+            input,
+            pressed: true,
+        });
+    }
+
+    #[test]
+    fn retro_burn_turns_against_velocity() {
+        let mut harness = TestHarness::new();
+        let (session, ship) = coasting_player_ship(&mut harness);
+
+        press(&session, ControlInput::Backward);
+        harness.step_for(150);
+
+        let rotation = harness
+            .world
+            .get::<RotationComponent>(ship)
+            .unwrap()
+            .rotation;
+        assert!((rotation.signed().radians.abs() - std::f32::consts::PI).abs() < 0.05);
+    }
+
+    #[test]
+    fn braking_slows_without_turning() {
+        let mut harness = TestHarness::new();
+        let (session, ship) = coasting_player_ship(&mut harness);
+        harness
+            .world
+            .get_mut::<ManeuverabilityComponent>(ship)
+            .unwrap()
+            .reverse_thrust = ReverseThrustMode::Brake;
+
+        press(&session, ControlInput::Backward);
+        harness.step_for(150);
+
+        let velocity = harness
+            .world
+            .get::<VelocityComponent>(ship)
+            .unwrap()
+            .velocity;
+        let rotation = harness
+            .world
+            .get::<RotationComponent>(ship)
+            .unwrap()
+            .rotation;
+        assert!(velocity.length() < 1.0);
+        assert_eq!(rotation.radians, 0.0);
+    }
+
+    #[test]
+    fn afterburner_is_faster_until_the_fuel_runs_out() {
+        let mut harness = TestHarness::new();
+        let (session, ship) = coasting_player_ship(&mut harness);
+        harness
+            .world
+            .get_mut::<ManeuverabilityComponent>(ship)
+            .unwrap()
+            .afterburner = Some(AfterburnerRecord {
+            thrust: 200.0,
+            maximum_speed: 400.0,
+            fuel_per_second: 10.0,
+        });
+        harness.world.get_mut::<FuelComponent>(ship).unwrap().fuel = 10.0;
+
+        press(&session, ControlInput::Afterburner);
+        harness.step_for(75);
+
+        // A second of burning at five times the shuttle's thrust, then nothing once the tank is dry
+        let velocity = harness
+            .world
+            .get::<VelocityComponent>(ship)
+            .unwrap()
+            .velocity;
+        assert!(velocity.length() > 250.0);
+        assert_eq!(harness.world.get::<FuelComponent>(ship).unwrap().fuel, 0.0);
+
+        harness.step_for(25);
+        let coasting = harness
+            .world
+            .get::<VelocityComponent>(ship)
+            .unwrap()
+            .velocity;
+        assert!(coasting.length() <= velocity.length());
+    }
+}
@@ -0,0 +1,83 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::HashSet;
+
+use bevy_ecs::{
+    entity::Entity,
+    event::{EventReader, EventWriter},
+    system::{Commands, Query},
+};
+
+use crate::backend::{
+    events::{collision_layer_events::CollisionStarted, ship_destroyed_event::ShipDestroyedEvent},
+    world_objects::components::{
+        faction_component::FactionComponent,
+        health_component::HealthComponent,
+        projectile_component::{Damaging, ProjectileComponent},
+    },
+};
+
+pub fn apply_projectile_damage(
+    mut hits: EventReader<CollisionStarted<Damaging>>,
+    mut targets: Query<&mut HealthComponent>,
+    projectiles: Query<&ProjectileComponent>,
+    factions: Query<&FactionComponent>,
+    mut destroyed: EventWriter<ShipDestroyedEvent>,
+    mut commands: Commands,
+) {
+    // A projectile overlapping multiple targets in the same tick should only ever hit one of them
+    let mut spent_projectiles = HashSet::<Entity>::new();
+
+    for hit in hits.read() {
+        let projectile = match projectiles.get(hit.sender) {
+            Ok(has) => has,
+            Err(_) => continue, // Only projectiles deal damage for now
+        };
+
+        if projectile.owner == hit.receiver || spent_projectiles.contains(&hit.sender) {
+            continue;
+        }
+
+        let mut health = match targets.get_mut(hit.receiver) {
+            Ok(has) => has,
+            Err(_) => continue,
+        };
+
+        // Already on its way out, so leave the projectile to fly on through the wreck
+        if health.is_destroyed() {
+            continue;
+        }
+
+        spent_projectiles.insert(hit.sender);
+        health.apply_damage(projectile.shield_damage, projectile.hull_damage);
+        commands.entity(hit.sender).despawn();
+
+        if health.is_destroyed() {
+            tracing::trace!("Entity {:?} was destroyed by weapons fire", hit.receiver);
+            commands.entity(hit.receiver).despawn();
+
+            destroyed.write(ShipDestroyedEvent {
+                destroyed_by: projectile.owner,
+                faction: factions
+                    .get(hit.receiver)
+                    .ok()
+                    .map(|faction| faction.faction.clone()),
+            });
+        }
+    }
+}
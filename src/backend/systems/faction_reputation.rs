@@ -0,0 +1,144 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use bevy_ecs::{
+    event::EventReader,
+    hierarchy::ChildOf,
+    query::With,
+    system::{Query, Res},
+};
+
+use crate::backend::{
+    components::session::player_session_component::PlayerSessionComponent,
+    events::ship_destroyed_event::ShipDestroyedEvent,
+    resources::{
+        definitions_resource::DefinitionsResource,
+        profile_save_queue_resource::ProfileSaveQueueResource,
+    },
+    world_objects::components::player_controlled_component::PlayerControlledComponent,
+};
+
+// Players lose standing with a faction for every one of its ships they destroy
+pub fn apply_ship_destroyed_reputation(
+    mut destroyed: EventReader<ShipDestroyedEvent>,
+    player_ships: Query<&ChildOf, With<PlayerControlledComponent>>,
+    sessions: Query<&PlayerSessionComponent>,
+    definitions: Res<DefinitionsResource>,
+    profile_saves: Res<ProfileSaveQueueResource>,
+) {
+    for event in destroyed.read() {
+        let faction = match event.faction.as_ref().and_then(|faction| {
+            definitions
+                .definitions
+                .get_factions()
+                .get_faction_record(faction)
+        }) {
+            Some(has) => has,
+            None => continue,
+        };
+
+        // NPCs destroying each other leaves no one to hold it against
+        let session = match player_ships
+            .get(event.destroyed_by)
+            .and_then(|parent_session| sessions.get(parent_session.parent()))
+        {
+            Ok(has) => has,
+            Err(_) => continue,
+        };
+
+        let profile = match session.session.upgrade() {
+            Some(has) => has.player_profile.clone(),
+            None => continue,
+        };
+
+        profile.modify_holdings(|holdings| {
+            holdings.change_reputation(faction, faction.ship_destroyed_reputation)
+        });
+        profile_saves.save(&profile);
+
+        tracing::trace!(
+            "Player ship {:?} destroyed a ship of faction {}",
+            event.destroyed_by,
+            faction.faction_reference
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backend::{
+            test_harness::TestHarness,
+            world_objects::components::{
+                faction_component::FactionComponent, health_component::HealthComponent,
+                position_component::PositionComponent, rotation_component::RotationComponent,
+                weapons_component::WeaponsComponent,
+            },
+        },
+        connectivity::client_server_message::{ClientServerMessage, ControlInput},
+        shared_types::{Rotation, Velocity},
+    };
+
+    #[test]
+    fn destroying_a_faction_ship_lowers_the_players_reputation_with_it() {
+        let mut harness = TestHarness::new();
+        let session = harness.connect_player();
+        harness.step_for(3);
+
+        let ship = harness.player_ship(&session);
+        let position = harness
+            .world
+            .get::<PositionComponent>(ship)
+            .unwrap()
+            .position;
+        harness
+            .world
+            .get_mut::<RotationComponent>(ship)
+            .unwrap()
+            .rotation = Rotation::radians(0.0);
+        harness
+            .world
+            .entity_mut(ship)
+            .insert(WeaponsComponent::new(["railgun".to_string()].into_iter()));
+
+        let target = harness.spawn_ship(
+            "shuttle",
+            position + euclid::vec2(400.0, 0.0),
+            Velocity::zero(),
+            Some("sol"),
+        );
+        harness.world.entity_mut(target).insert(FactionComponent {
+            faction: "martians".to_string(),
+        });
+        let mut health = harness.world.get_mut::<HealthComponent>(target).unwrap();
+        health.shield = 0.0;
+        health.shield_regeneration = 0.0;
+        health.hull = 1.0;
+
+        session.send(ClientServerMessage::ControlInput {
+            input: ControlInput::Fire,
+            pressed: true,
+        });
+        harness.step_for(30);
+
+        assert!(harness.world.get_entity(target).is_err());
+        assert_eq!(
+            session.profile().get_holdings().reputation.get("martians"),
+            Some(&-10)
+        );
+    }
+}
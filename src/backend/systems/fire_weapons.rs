@@ -0,0 +1,202 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use bevy_ecs::{
+    entity::Entity,
+    system::{ParallelCommands, Query, Res},
+};
+
+use crate::{
+    AssetIndexResource,
+    backend::{
+        resources::{definitions_resource::DefinitionsResource, delta_t_resource::DeltaTResource},
+        world_objects::{
+            components::{
+                energy_component::EnergyComponent,
+                position_component::PositionComponent,
+                rotation_component::RotationComponent,
+                star_system_component::{StarSystemComponent, in_same_star_system},
+                target_component::TargetComponent,
+                velocity_component::VelocityComponent,
+                weapons_component::WeaponsComponent,
+            },
+            projectile::ProjectileBundle,
+        },
+    },
+};
+
+type Armed<'a> = (
+    Entity,
+    &'a mut WeaponsComponent,
+    &'a PositionComponent,
+    &'a RotationComponent,
+    &'a VelocityComponent,
+    Option<&'a StarSystemComponent>,
+    Option<&'a mut EnergyComponent>,
+    Option<&'a TargetComponent>,
+);
+
+pub fn fire_weapons(
+    mut armed: Query<Armed<'_>>,
+    targets: Query<(&PositionComponent, Option<&StarSystemComponent>)>,
+    definitions: Res<DefinitionsResource>,
+    asset_index: Res<AssetIndexResource>,
+    time: Res<DeltaTResource>,
+    commands: ParallelCommands,
+) {
+    let delta_t = time.get_last_tick_duration();
+    armed.par_iter_mut().for_each(
+        |(entity, mut weapons, position, rotation, velocity, star_system, mut energy, target)| {
+            let trigger_pulled = weapons.trigger_pulled;
+
+            for mount in weapons.mounts.iter_mut() {
+                mount.cooldown = mount.cooldown.saturating_sub(delta_t);
+
+                if !trigger_pulled || !mount.cooldown.is_zero() {
+                    continue;
+                }
+
+                let weapon = match definitions
+                    .definitions
+                    .get_weapons()
+                    .get_weapon_record(&mount.weapon)
+                {
+                    Some(has) => has,
+                    None => continue, // Weapon references are verified on load, so this should not happen
+                };
+
+                // Entities with no energy to draw on fire for free
+                if energy
+                    .as_mut()
+                    .is_some_and(|energy| !energy.spend(weapon.energy_cost))
+                {
+                    continue;
+                }
+
+                mount.cooldown = std::time::Duration::from_secs_f32(weapon.reload_time);
+
+                // Turrets swing round to face the target, everything else fires straight ahead
+                // A target left behind in another star system can't be aimed at, as its position means nothing here
+                let aim = match target
+                    .filter(|_| weapon.turret)
+                    .and_then(|target| targets.get(target.target).ok())
+                    .filter(|(_, target_star_system)| {
+                        in_same_star_system(star_system, *target_star_system)
+                    }) {
+                    Some((target_position, _)) => (target_position.position - position.position)
+                        .angle_from_x_axis()
+                        .cast::<f32>(),
+                    None => rotation.rotation,
+                };
+
+                if let Ok(projectile) = ProjectileBundle::new(
+                    weapon,
+                    entity,
+                    position.position,
+                    aim,
+                    velocity.velocity,
+                    &asset_index.asset_index,
+                ) {
+                    commands.command_scope(|mut commands| {
+                        let mut spawned = commands.spawn(projectile);
+
+                        // Projectiles stay within the star system they were fired in
+                        if let Some(star_system) = star_system {
+                            spawned.insert(*star_system);
+                        }
+                    });
+                }
+            }
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backend::{
+            test_harness::TestHarness,
+            world_objects::components::{
+                health_component::HealthComponent, target_component::TargetComponent,
+                weapons_component::WeaponsComponent,
+            },
+        },
+        shared_types::{Coordinates, Velocity},
+    };
+
+    #[test]
+    fn turrets_fire_at_the_target_instead_of_straight_ahead() {
+        let mut harness = TestHarness::new();
+        let shooter = harness.spawn_ship(
+            "shuttle",
+            Coordinates::zero(),
+            Velocity::zero(),
+            Some("sol"),
+        );
+        let target = harness.spawn_ship(
+            "shuttle",
+            Coordinates::new(0.0, 400.0),
+            Velocity::zero(),
+            Some("sol"),
+        );
+
+        let mut weapons = WeaponsComponent::new(["laser_turret".to_string()].into_iter());
+        weapons.trigger_pulled = true;
+        harness
+            .world
+            .entity_mut(shooter)
+            .insert((weapons, TargetComponent { target }));
+
+        harness.step_for(60);
+
+        assert!(harness.world.get::<HealthComponent>(target).unwrap().shield < 50.0);
+    }
+
+    #[test]
+    fn turrets_fire_straight_ahead_when_the_target_is_in_another_star_system() {
+        let mut harness = TestHarness::new();
+        let shooter = harness.spawn_ship(
+            "shuttle",
+            Coordinates::zero(),
+            Velocity::zero(),
+            Some("sol"),
+        );
+        let target = harness.spawn_ship(
+            "shuttle",
+            Coordinates::new(0.0, 400.0),
+            Velocity::zero(),
+            Some("alpha"),
+        );
+        let ahead = harness.spawn_ship(
+            "shuttle",
+            Coordinates::new(400.0, 0.0),
+            Velocity::zero(),
+            Some("sol"),
+        );
+
+        let mut weapons = WeaponsComponent::new(["laser_turret".to_string()].into_iter());
+        weapons.trigger_pulled = true;
+        harness
+            .world
+            .entity_mut(shooter)
+            .insert((weapons, TargetComponent { target }));
+
+        harness.step_for(60);
+
+        assert!(harness.world.get::<HealthComponent>(ahead).unwrap().shield < 50.0);
+    }
+}
@@ -0,0 +1,376 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Speed ships travel at while entering and leaving hyperspace
+const HYPERSPACE_SPEED: f32 = 3000.0;
+const HYPERSPACE_DEPARTURE_DURATION: Duration = Duration::from_secs(2);
+const HYPERSPACE_ARRIVAL_DURATION: Duration = Duration::from_secs(2);
+
+use std::time::Duration;
+
+use bevy_ecs::{
+    entity::Entity,
+    hierarchy::ChildOf,
+    query::{With, Without},
+    system::{Commands, Query, Res},
+};
+use euclid::num::Zero;
+
+use crate::{
+    backend::{
+        components::session::player_session_component::PlayerSessionComponent,
+        configuration_file_loaders::definition_caches::star_system_definition_cache::{
+            StarSystemDefinitionCache, StarSystemId,
+        },
+        resources::{definitions_resource::DefinitionsResource, delta_t_resource::DeltaTResource},
+        world_objects::components::{
+            angular_velocity_component::AngularVelocityComponent,
+            fuel_component::FuelComponent,
+            hyperspace_jump_component::{HyperspaceJumpComponent, HyperspaceJumpPhase},
+            maneuverability_component::ManeuverabilityComponent,
+            player_controlled_component::PlayerControlledComponent,
+            position_component::PositionComponent,
+            rotation_component::RotationComponent,
+            semi_newtonian_physics_component::SemiNewtonianPhysicsComponent,
+            star_system_component::StarSystemComponent,
+            velocity_component::VelocityComponent,
+            weapons_component::WeaponsComponent,
+        },
+    },
+    connectivity::{
+        hyperspace_message_data::{HyperlaneData, HyperspaceJumpData, StarSystemData},
+        server_client_message::ServerClientMessage,
+    },
+    shared_types::{
+        AccelerationScalar, AngularVelocity, Coordinates, GlobalCoordinateType, Rotation, Velocity,
+        WorldCoordinates,
+    },
+};
+
+pub fn star_system_entered_message(
+    star_systems: &StarSystemDefinitionCache,
+    star_system_id: StarSystemId,
+) -> Option<ServerClientMessage> {
+    let record = star_systems.get_star_system_record(star_system_id)?;
+
+    let hyperlanes = star_systems
+        .get_linked_star_systems(star_system_id)
+        .into_iter()
+        .filter_map(|linked| star_systems.get_star_system_record(linked))
+        .map(|linked| HyperlaneData {
+            star_system_reference: linked.star_system_reference.clone(),
+            star_system_name: linked.star_system_display_name.clone(),
+        })
+        .collect();
+
+    Some(ServerClientMessage::StarSystemEntered(StarSystemData {
+        star_system_reference: record.star_system_reference.clone(),
+        star_system_name: record.star_system_display_name.clone(),
+        hyperlanes,
+    }))
+}
+
+pub fn begin_hyperspace_jumps(
+    mut sessions: Query<&mut PlayerSessionComponent>,
+    ships: Query<
+        Option<&StarSystemComponent>,
+        (
+            With<PlayerControlledComponent>,
+            Without<HyperspaceJumpComponent>,
+        ),
+    >,
+    mut fuel_components: Query<&mut FuelComponent>,
+    definitions: Res<DefinitionsResource>,
+    mut commands: Commands,
+) {
+    let star_systems = definitions.definitions.get_star_systems();
+
+    for mut session in sessions.iter_mut() {
+        let destination_reference = match session.jump_requested.take() {
+            Some(has) => has,
+            None => continue,
+        };
+
+        let ship = match session.should_follow {
+            Some(has) => has,
+            None => continue, // Landed players have nothing to jump with
+        };
+
+        let origin = match ships.get(ship) {
+            Ok(Some(star_system)) => star_system.star_system,
+            Ok(None) => continue, // Ships outside of any star system have no hyperlanes to follow
+            Err(_) => continue,   // Ship is missing or already jumping
+        };
+
+        let destination = match star_systems.get_star_system_id(&destination_reference) {
+            Some(has) => has,
+            None => continue,
+        };
+
+        if !star_systems.are_linked(origin, destination) {
+            tracing::trace!(
+                "Ship {:?} attempted to jump to star system {} which is not linked to its current star system",
+                ship,
+                destination_reference
+            );
+            continue;
+        }
+
+        let (origin_record, destination_record) = match (
+            star_systems.get_star_system_record(origin),
+            star_systems.get_star_system_record(destination),
+        ) {
+            (Some(origin_record), Some(destination_record)) => (origin_record, destination_record),
+            _ => continue,
+        };
+
+        // Jumps are paid for up front, so a ship can never run dry partway through one
+        if let Ok(mut fuel) = fuel_components.get_mut(ship) {
+            if fuel.fuel < fuel.jump_fuel {
+                tracing::trace!(
+                    "Ship {:?} does not have enough fuel to jump to star system {}",
+                    ship,
+                    destination_reference
+                );
+                continue;
+            }
+
+            fuel.fuel -= fuel.jump_fuel;
+        }
+
+        // Ships leave in the direction of their destination on the galaxy map
+        let heading = Rotation::radians(
+            (destination_record.map_y - origin_record.map_y)
+                .atan2(destination_record.map_x - origin_record.map_x) as f32,
+        );
+
+        commands.entity(ship).insert(HyperspaceJumpComponent {
+            destination,
+            heading,
+            phase: HyperspaceJumpPhase::Departing,
+            remaining: HYPERSPACE_DEPARTURE_DURATION,
+        });
+
+        let _ = session.command_queue_outbound.send(
+            ServerClientMessage::HyperspaceJumpStarted(HyperspaceJumpData {
+                ship_id: ship.to_bits(),
+                star_system_name: destination_record.star_system_display_name.clone(),
+            }),
+        ); // Nothing we can do about send errors for users disconnected
+    }
+}
+
+pub fn progress_hyperspace_jumps(
+    mut jumping: Query<(
+        Entity,
+        &mut HyperspaceJumpComponent,
+        &mut PositionComponent,
+        &mut VelocityComponent,
+        &mut RotationComponent,
+        &mut AngularVelocityComponent,
+        &mut SemiNewtonianPhysicsComponent,
+        &ManeuverabilityComponent,
+    )>,
+    mut weapons_components: Query<&mut WeaponsComponent>,
+    parents: Query<&ChildOf>,
+    sessions: Query<&PlayerSessionComponent>,
+    definitions: Res<DefinitionsResource>,
+    time: Res<DeltaTResource>,
+    mut commands: Commands,
+) {
+    let delta_t = time.get_last_tick_duration();
+
+    for (
+        entity,
+        mut jump,
+        mut position,
+        mut velocity,
+        mut rotation,
+        mut angular_velocity,
+        mut physics,
+        maneuverability,
+    ) in jumping.iter_mut()
+    {
+        // The jump drive has full control of the ship until it arrives, whatever the pilot was doing when it engaged
+        physics.thrust = AccelerationScalar::zero();
+        physics.braking = AccelerationScalar::zero();
+        physics.maximum_speed = maneuverability.maximum_speed;
+        angular_velocity.angular_velocity = AngularVelocity::zero();
+        rotation.rotation = jump.heading;
+        if let Ok(mut weapons) = weapons_components.get_mut(entity) {
+            weapons.trigger_pulled = false;
+        }
+
+        let cruising_speed = physics.maximum_speed.get();
+        let phase_duration = match jump.phase {
+            HyperspaceJumpPhase::Departing => HYPERSPACE_DEPARTURE_DURATION,
+            HyperspaceJumpPhase::Arriving => HYPERSPACE_ARRIVAL_DURATION,
+        };
+
+        jump.remaining = jump.remaining.saturating_sub(delta_t);
+        let progress = 1.0 - (jump.remaining.as_secs_f32() / phase_duration.as_secs_f32());
+
+        match jump.phase {
+            HyperspaceJumpPhase::Departing => {
+                if jump.remaining.is_zero() {
+                    commands.entity(entity).insert(StarSystemComponent {
+                        star_system: jump.destination,
+                    });
+
+                    // Arrive far enough out that slowing down from hyperspace leaves the ship near the center of the star system
+                    let arrival_distance = ((HYPERSPACE_SPEED + cruising_speed) / 2.0
+                        * HYPERSPACE_ARRIVAL_DURATION.as_secs_f32())
+                        as GlobalCoordinateType;
+                    position.position = Coordinates::origin()
+                        - euclid::Vector2D::<GlobalCoordinateType, WorldCoordinates>::from_angle_and_length(
+                            euclid::Angle::radians(jump.heading.get() as GlobalCoordinateType),
+                            arrival_distance,
+                        );
+                    velocity.velocity =
+                        Velocity::from_angle_and_length(jump.heading, HYPERSPACE_SPEED);
+
+                    jump.phase = HyperspaceJumpPhase::Arriving;
+                    jump.remaining = HYPERSPACE_ARRIVAL_DURATION;
+                } else {
+                    velocity.velocity = Velocity::from_angle_and_length(
+                        jump.heading,
+                        cruising_speed + (HYPERSPACE_SPEED - cruising_speed) * progress,
+                    );
+                }
+            }
+            HyperspaceJumpPhase::Arriving => {
+                if jump.remaining.is_zero() {
+                    velocity.velocity =
+                        Velocity::from_angle_and_length(jump.heading, cruising_speed);
+                    commands.entity(entity).remove::<HyperspaceJumpComponent>();
+
+                    let session = match parents.get(entity).map(|x| sessions.get(x.parent())) {
+                        Ok(Ok(has)) => has,
+                        _ => continue, // Only players are told about their arrival
+                    };
+
+                    let star_system_name = match definitions
+                        .definitions
+                        .get_star_systems()
+                        .get_star_system_record(jump.destination)
+                    {
+                        Some(has) => has.star_system_display_name.clone(),
+                        None => continue,
+                    };
+
+                    let _ = session.command_queue_outbound.send(
+                        ServerClientMessage::HyperspaceJumpCompleted(HyperspaceJumpData {
+                            ship_id: entity.to_bits(),
+                            star_system_name,
+                        }),
+                    ); // Nothing we can do about send errors for users disconnected
+                } else {
+                    velocity.velocity = Velocity::from_angle_and_length(
+                        jump.heading,
+                        HYPERSPACE_SPEED + (cruising_speed - HYPERSPACE_SPEED) * progress,
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backend::{
+            test_harness::TestHarness,
+            world_objects::components::{
+                fuel_component::FuelComponent, hyperspace_jump_component::HyperspaceJumpComponent,
+                maneuverability_component::ManeuverabilityComponent,
+                semi_newtonian_physics_component::SemiNewtonianPhysicsComponent,
+            },
+        },
+        configuration_file_structures::ship_configuration_file::AfterburnerRecord,
+        connectivity::client_server_message::{ClientServerMessage, ControlInput},
+    };
+
+    #[test]
+    fn jumps_need_and_use_fuel() {
+        let mut harness = TestHarness::new();
+        let session = harness.connect_player();
+        harness.step_for(3);
+
+        let ship = harness.player_ship(&session);
+        harness.world.entity_mut(ship).insert(FuelComponent {
+            fuel: 50.0,
+            maximum_fuel: 200.0,
+            jump_fuel: 100.0,
+        });
+
+        let jump = || ClientServerMessage::Jump {
+            star_system: "alpha".to_string(),
+        };
+
+        session.send(jump());
+        harness.step_for(2);
+        assert!(harness.world.get::<HyperspaceJumpComponent>(ship).is_none());
+
+        harness.world.get_mut::<FuelComponent>(ship).unwrap().fuel = 150.0;
+        session.send(jump());
+        harness.step_for(2);
+        assert!(harness.world.get::<HyperspaceJumpComponent>(ship).is_some());
+        assert_eq!(harness.world.get::<FuelComponent>(ship).unwrap().fuel, 50.0);
+    }
+
+    #[test]
+    fn holding_the_afterburner_does_nothing_during_a_jump() {
+        let mut harness = TestHarness::new();
+        let session = harness.connect_player();
+        harness.step_for(3);
+
+        let ship = harness.player_ship(&session);
+        harness.world.entity_mut(ship).insert(FuelComponent {
+            fuel: 150.0,
+            maximum_fuel: 200.0,
+            jump_fuel: 100.0,
+        });
+        harness
+            .world
+            .get_mut::<ManeuverabilityComponent>(ship)
+            .unwrap()
+            .afterburner = Some(AfterburnerRecord {
+            thrust: 200.0,
+            maximum_speed: 400.0,
+            fuel_per_second: 10.0,
+        });
+
+        session.send(ClientServerMessage::Jump {
+            star_system: "alpha".to_string(),
+        });
+        harness.step_for(2);
+        session.send(ClientServerMessage::ControlInput {
+            input: ControlInput::Afterburner,
+            pressed: true,
+        });
+        harness.step_for(100);
+
+        // Still cruising at the shuttle's own top speed, without a drop of fuel burned past the jump itself
+        assert!(harness.world.get::<HyperspaceJumpComponent>(ship).is_some());
+        let physics = harness
+            .world
+            .get::<SemiNewtonianPhysicsComponent>(ship)
+            .unwrap();
+        assert_eq!(physics.maximum_speed.get(), 200.0);
+        assert_eq!(harness.world.get::<FuelComponent>(ship).unwrap().fuel, 50.0);
+    }
+}
@@ -15,12 +15,27 @@
     along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+pub mod apply_collision_responses;
+pub mod apply_npc_control;
 pub mod apply_player_control;
+pub mod apply_projectile_damage;
+pub mod faction_reputation;
+pub mod fire_weapons;
+pub mod hyperspace_jump;
+pub mod outfit_player_ships;
+pub mod player_landing;
+pub mod player_missions;
 pub mod player_session_cleanup;
 pub mod player_spawn_system;
+pub mod player_targeting;
+pub mod send_ship_status;
+pub mod spawn_traffic;
 pub mod submit_command;
+pub mod trade_commodities;
 pub mod update_collisions_with_position;
 pub mod update_collisions_with_rotation;
 pub mod update_positions_with_velocity;
 pub mod update_rotations_with_angular_velocity;
+pub mod update_swept_collisions;
 pub mod update_velocities_with_semi_newtonian_physics;
+pub mod world_persistence;
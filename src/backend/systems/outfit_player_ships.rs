@@ -0,0 +1,374 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use bevy_ecs::system::{Query, Res};
+
+use crate::{
+    backend::{
+        components::session::player_session_component::PlayerSessionComponent,
+        configuration_file_loaders::definition_file_cache::DefinitionFileCache,
+        data_objects::{outfitting_request::OutfittingRequest, ship_loadout::ShipLoadout},
+        resources::{
+            definitions_resource::DefinitionsResource,
+            profile_save_queue_resource::ProfileSaveQueueResource,
+        },
+        world_objects::components::shipyard_component::ShipyardComponent,
+    },
+    configuration_file_structures::ship_configuration_file::ShipRecord,
+    connectivity::{
+        player_info::player_profile::PlayerHoldings,
+        server_client_message::ServerClientMessage,
+        shipyard_message_data::{OutfitOfferData, ShipOfferData, ShipyardData},
+    },
+};
+
+// Fraction of a ship's price given back when it is traded in for another
+const TRADE_IN_FRACTION: f64 = 0.5;
+
+fn trade_in_value(ship: &ShipRecord) -> u32 {
+    (ship.price as f64 * TRADE_IN_FRACTION) as u32
+}
+
+// Works on a copy of the holdings, so a purchase which leaves the ship overloaded or the player in debt is never kept
+fn apply_outfitting_request(
+    definitions: &DefinitionFileCache,
+    shipyard: &ShipyardComponent,
+    holdings: &PlayerHoldings,
+    request: &OutfittingRequest,
+) -> Result<PlayerHoldings, ()> {
+    let mut changed = holdings.clone();
+
+    let (cost, refund) = match request {
+        OutfittingRequest::BuyShip { ship } => {
+            let record = match definitions.get_ships().get_ship_record(ship) {
+                Some(has) if shipyard.ships.contains(ship) => has,
+                _ => return Err(()),
+            };
+
+            let current = ShipLoadout::from_holdings(definitions, holdings);
+
+            // Trading a ship in for another of its own kind would charge half its price for nothing
+            if current
+                .as_ref()
+                .is_some_and(|current| current.ship.ship_reference == record.ship_reference)
+            {
+                return Err(());
+            }
+
+            let trade_in = match current {
+                Some(current) => trade_in_value(current.ship),
+                None => 0,
+            };
+
+            // Outfits and cargo move over to the new ship, so it has to have room for them
+            changed.ship = Some(record.ship_reference.clone());
+            (record.price, trade_in)
+        }
+        OutfittingRequest::BuyOutfit { outfit } => {
+            let record = match definitions.get_outfits().get_outfit_record(outfit) {
+                Some(has) if shipyard.outfits.contains(outfit) => has,
+                _ => return Err(()),
+            };
+
+            *changed.outfits.entry(outfit.clone()).or_insert(0) += 1;
+            (record.price, 0)
+        }
+        OutfittingRequest::SellOutfit { outfit } => {
+            // Outfitters only buy back what they sell themselves
+            let record = match definitions.get_outfits().get_outfit_record(outfit) {
+                Some(has) if shipyard.outfits.contains(outfit) => has,
+                _ => return Err(()),
+            };
+
+            let fitted = match changed.outfits.get_mut(outfit) {
+                Some(has) if *has > 0 => has,
+                _ => return Err(()),
+            };
+
+            *fitted -= 1;
+            if *fitted == 0 {
+                changed.outfits.remove(outfit);
+            }
+
+            (0, record.price)
+        }
+    };
+
+    changed.credits = match (changed.credits + refund as u64).checked_sub(cost as u64) {
+        Some(has) => has,
+        None => return Err(()),
+    };
+
+    let loadout = match ShipLoadout::from_holdings(definitions, &changed) {
+        Some(has) => has,
+        None => return Err(()),
+    };
+
+    if !loadout.fits() || changed.cargo_used() > loadout.cargo_capacity() {
+        return Err(());
+    }
+
+    Ok(changed)
+}
+
+pub fn outfit_player_ships(
+    mut sessions: Query<&mut PlayerSessionComponent>,
+    shipyards: Query<&ShipyardComponent>,
+    definitions: Res<DefinitionsResource>,
+    profile_saves: Res<ProfileSaveQueueResource>,
+) {
+    for mut session in sessions.iter_mut() {
+        let outfitting_requests = std::mem::take(&mut session.outfitting_requests);
+
+        if outfitting_requests.is_empty() && !session.shipyard_outdated {
+            continue;
+        }
+
+        session.shipyard_outdated = false;
+
+        // Ships can only be changed on the ground, anything requested in flight is dropped
+        let planetoid = match session.landed_on {
+            Some(has) => has,
+            None => continue,
+        };
+
+        let shipyard = match shipyards.get(planetoid) {
+            Ok(has) => has,
+            Err(_) => continue, // Not every planetoid has a shipyard or outfitter
+        };
+
+        let profile = match session.session.upgrade() {
+            Some(has) => has.player_profile.clone(),
+            None => continue,
+        };
+
+        let (holdings, outfitted_any) = profile.modify_holdings(|holdings| {
+            let mut outfitted_any = false;
+
+            for request in &outfitting_requests {
+                match apply_outfitting_request(
+                    &definitions.definitions,
+                    shipyard,
+                    holdings,
+                    request,
+                ) {
+                    Ok(changed) => {
+                        *holdings = changed;
+                        outfitted_any = true;
+                    }
+                    Err(()) => {
+                        tracing::debug!(
+                            "Rejected outfitting {:?} on planetoid {:?}",
+                            request,
+                            planetoid
+                        );
+                    }
+                }
+            }
+
+            (holdings.clone(), outfitted_any)
+        });
+
+        if outfitted_any {
+            profile_saves.save(&profile);
+        }
+
+        // Credits and cargo space shown by the market and missions may have changed along with the ship
+        if !outfitting_requests.is_empty() {
+            session.market_outdated = true;
+            session.missions_outdated = true;
+        }
+
+        let loadout = match ShipLoadout::from_holdings(&definitions.definitions, &holdings) {
+            Some(has) => has,
+            None => continue,
+        };
+
+        let ships = definitions.definitions.get_ships();
+        let outfits = definitions.definitions.get_outfits();
+
+        let _ = session
+            .command_queue_outbound
+            .send(ServerClientMessage::Shipyard(ShipyardData {
+                planetoid_id: planetoid.to_bits(),
+                credits: holdings.credits,
+                ship_reference: loadout.ship.ship_reference.clone(),
+                ship_name: loadout.ship.ship_display_name.clone(),
+                trade_in_value: trade_in_value(loadout.ship),
+                outfit_space: loadout.ship.outfit_space,
+                outfit_space_used: loadout.outfit_mass(),
+                weapon_slots: loadout.ship.weapon_slots,
+                weapon_slots_used: loadout.weapon_slots_used(),
+                ships: shipyard
+                    .ships
+                    .iter()
+                    .filter_map(|ship| ships.get_ship_record(ship))
+                    .map(|record| ShipOfferData {
+                        ship_reference: record.ship_reference.clone(),
+                        ship_name: record.ship_display_name.clone(),
+                        price: record.price,
+                    })
+                    .collect(),
+                outfits: shipyard
+                    .outfits
+                    .iter()
+                    .filter_map(|outfit| outfits.get_outfit_record(outfit))
+                    .map(|record| OutfitOfferData {
+                        outfit_reference: record.outfit_reference.clone(),
+                        outfit_name: record.outfit_display_name.clone(),
+                        price: record.price,
+                        mass: record.mass,
+                        fitted: holdings
+                            .outfits
+                            .get(&record.outfit_reference)
+                            .copied()
+                            .unwrap_or(0),
+                    })
+                    .collect(),
+            })); // Nothing we can do about send errors for users disconnected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backend::{
+            test_harness::{TestHarness, TestSession},
+            world_objects::components::{
+                cargo_component::CargoComponent, ship_type_component::ShipTypeComponent,
+                weapons_component::WeaponsComponent,
+            },
+        },
+        connectivity::{
+            client_server_message::ClientServerMessage,
+            player_info::player_profile::STARTING_CREDITS,
+            server_client_message::ServerClientMessage, shipyard_message_data::ShipyardData,
+        },
+    };
+
+    fn shipyards(messages: Vec<ServerClientMessage>) -> Vec<ShipyardData> {
+        messages
+            .into_iter()
+            .filter_map(|message| match message {
+                ServerClientMessage::Shipyard(shipyard) => Some(shipyard),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn buy_outfit(harness: &mut TestHarness, session: &TestSession, outfit: &str) {
+        session.send(ClientServerMessage::BuyOutfit {
+            outfit: outfit.to_string(),
+        });
+        harness.step();
+    }
+
+    #[test]
+    fn outfits_are_limited_by_outfit_space_and_weapon_slots() {
+        let mut harness = TestHarness::new();
+        let session = harness.connect_player();
+        harness.land_player(&session, "earth");
+
+        let sent = shipyards(session.received());
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].ship_reference, "shuttle");
+        assert_eq!(sent[0].ships.len(), 2);
+
+        // The shuttle has one weapon slot and 10 tons of outfit space
+        for outfit in ["railgun_turret", "railgun_turret", "cargo_pod", "cargo_pod"] {
+            buy_outfit(&mut harness, &session, outfit);
+        }
+
+        let last = shipyards(session.received()).pop().unwrap();
+        assert_eq!(last.weapon_slots_used, 1);
+        assert_eq!(last.outfit_space_used, 9.0);
+        assert_eq!(last.credits, STARTING_CREDITS - 2000 - 500);
+
+        session.send(ClientServerMessage::TakeOff);
+        harness.step_for(2);
+
+        let ship = harness.player_ship(&session);
+        assert_eq!(
+            harness.world.get::<CargoComponent>(ship).unwrap().capacity,
+            30
+        );
+        assert_eq!(
+            harness
+                .world
+                .get::<WeaponsComponent>(ship)
+                .unwrap()
+                .mounts
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn buying_a_ship_trades_in_the_old_one_and_keeps_its_outfits() {
+        let mut harness = TestHarness::new();
+        let session = harness.connect_player();
+        harness.land_player(&session, "earth");
+
+        buy_outfit(&mut harness, &session, "cargo_pod");
+        session.send(ClientServerMessage::BuyShip {
+            ship: "courier".to_string(),
+        });
+        harness.step();
+
+        let last = shipyards(session.received()).pop().unwrap();
+        assert_eq!(last.ship_reference, "courier");
+        assert_eq!(last.credits, STARTING_CREDITS - 500 - 8000 + 2000);
+
+        // Selling the pod back at full price while the hold is empty is always fine
+        session.send(ClientServerMessage::SellOutfit {
+            outfit: "cargo_pod".to_string(),
+        });
+        session.send(ClientServerMessage::TakeOff);
+        harness.step_for(2);
+
+        let ship = harness.player_ship(&session);
+        assert_eq!(
+            harness
+                .world
+                .get::<ShipTypeComponent>(ship)
+                .unwrap()
+                .ship_reference,
+            "courier"
+        );
+        assert_eq!(
+            harness.world.get::<CargoComponent>(ship).unwrap().capacity,
+            50
+        );
+    }
+
+    #[test]
+    fn buying_the_ship_already_owned_is_rejected() {
+        let mut harness = TestHarness::new();
+        let session = harness.connect_player();
+        harness.land_player(&session, "earth");
+        session.received();
+
+        session.send(ClientServerMessage::BuyShip {
+            ship: "shuttle".to_string(),
+        });
+        harness.step();
+
+        let last = shipyards(session.received()).pop().unwrap();
+        assert_eq!(last.ship_reference, "shuttle");
+        assert_eq!(last.credits, STARTING_CREDITS);
+    }
+}
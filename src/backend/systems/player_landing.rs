@@ -0,0 +1,303 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use bevy_ecs::{
+    entity::Entity,
+    query::With,
+    system::{Commands, Query, Res},
+};
+use rand::Rng;
+
+use crate::{
+    AssetIndexResource,
+    backend::{
+        components::session::player_session_component::PlayerSessionComponent,
+        data_objects::ship_loadout::ShipLoadout,
+        resources::definitions_resource::DefinitionsResource,
+        systems::player_spawn_system::spawn_player_ship,
+        world_objects::components::{
+            faction_component::FactionComponent,
+            planetoid_component::{LandablePlanetoidComponent, PlanetoidComponent},
+            player_controlled_component::PlayerControlledComponent,
+            position_component::PositionComponent,
+            star_system_component::{StarSystemComponent, in_same_star_system},
+            velocity_component::VelocityComponent,
+        },
+    },
+    connectivity::{
+        landing_message_data::{LandedData, LandingRefusedData},
+        server_client_message::ServerClientMessage,
+    },
+    shared_types::{GlobalCoordinateType, WorldCoordinates},
+};
+
+pub const MAXIMUM_LANDING_SPEED: f32 = 25.0;
+
+// Fraction of the landing radius away from the planetoid center that ships are placed on takeoff
+const TAKEOFF_DISTANCE_FRACTION: f64 = 0.5;
+
+pub fn land_player_ships(
+    mut sessions: Query<&mut PlayerSessionComponent>,
+    ships: Query<
+        (
+            &PositionComponent,
+            &VelocityComponent,
+            Option<&StarSystemComponent>,
+        ),
+        With<PlayerControlledComponent>,
+    >,
+    planetoids: Query<(
+        Entity,
+        &PositionComponent,
+        &PlanetoidComponent,
+        &LandablePlanetoidComponent,
+        Option<&StarSystemComponent>,
+    )>,
+    planetoid_factions: Query<&FactionComponent>,
+    mut commands: Commands,
+    definitions: Res<DefinitionsResource>,
+) {
+    for mut session in sessions.iter_mut() {
+        if !session.input_status.land {
+            session.landing_refused_by = None;
+            continue;
+        }
+
+        if session.landed_on.is_some() {
+            continue;
+        }
+
+        let ship = match session.should_follow {
+            Some(has) => has,
+            None => continue,
+        };
+
+        let (ship_position, ship_velocity, ship_star_system) = match ships.get(ship) {
+            Ok(has) => has,
+            Err(_) => continue,
+        };
+
+        if ship_velocity.velocity.length() > MAXIMUM_LANDING_SPEED {
+            continue;
+        }
+
+        let closest_planetoid = planetoids
+            .iter()
+            .filter(|(_, _, _, _, planetoid_star_system)| {
+                in_same_star_system(ship_star_system, *planetoid_star_system)
+            })
+            .map(|(entity, position, planetoid, landable, _)| {
+                (
+                    entity,
+                    position.position.distance_to(ship_position.position),
+                    planetoid,
+                    landable,
+                )
+            })
+            .filter(|(_, distance, _, landable)| *distance <= landable.landing_radius as f64)
+            .min_by(|x, y| x.1.total_cmp(&y.1));
+
+        let (planetoid_entity, _distance, planetoid, landable) = match closest_planetoid {
+            Some(has) => has,
+            None => continue,
+        };
+
+        let profile = match session.session.upgrade() {
+            Some(has) => has.player_profile.clone(),
+            None => continue,
+        };
+
+        let refusal = match planetoid_factions
+            .get(planetoid_entity)
+            .ok()
+            .and_then(|faction| {
+                definitions
+                    .definitions
+                    .get_factions()
+                    .get_faction_record(&faction.faction)
+            }) {
+            Some(faction) => profile.get_holdings().landing_refusal(faction),
+            None => None, // Planetoids without a faction welcome everyone
+        };
+
+        if let Some(reason) = refusal {
+            if session.landing_refused_by != Some(planetoid_entity) {
+                session.landing_refused_by = Some(planetoid_entity);
+
+                let _ = session.command_queue_outbound.send(
+                    ServerClientMessage::LandingRefused(LandingRefusedData {
+                        planetoid_id: planetoid_entity.to_bits(),
+                        planetoid_name: planetoid.planetoid_display_name.clone(),
+                        reason,
+                    }),
+                ); // Nothing we can do about send errors for users disconnected
+            }
+            continue;
+        }
+
+        // Landed ships are removed from the world entirely and rebuilt on takeoff
+        commands.entity(ship).despawn();
+        session.should_follow = None;
+        session.landed_on = Some(planetoid_entity);
+        session.landing_refused_by = None;
+        session.takeoff_requested = false;
+        session.market_outdated = true;
+        session.shipyard_outdated = true;
+        session.missions_outdated = true;
+
+        tracing::trace!(
+            "Player ship {:?} landed on {}",
+            ship,
+            planetoid.planetoid_reference
+        );
+
+        let _ = session
+            .command_queue_outbound
+            .send(landed_message(planetoid_entity, planetoid, landable)); // Nothing we can do about send errors for users disconnected
+    }
+}
+
+pub fn landed_message(
+    planetoid_entity: Entity,
+    planetoid: &PlanetoidComponent,
+    landable: &LandablePlanetoidComponent,
+) -> ServerClientMessage {
+    ServerClientMessage::Landed(LandedData {
+        planetoid_id: planetoid_entity.to_bits(),
+        planetoid_name: planetoid.planetoid_display_name.clone(),
+        backdrop_image_asset: landable.backdrop_image_asset,
+        text_description_asset: landable.text_description_asset,
+    })
+}
+
+pub fn take_off_player_ships(
+    mut sessions: Query<(Entity, &mut PlayerSessionComponent)>,
+    planetoids: Query<(
+        &PositionComponent,
+        &LandablePlanetoidComponent,
+        Option<&StarSystemComponent>,
+    )>,
+    mut commands: Commands,
+    asset_index: Res<AssetIndexResource>,
+    definitions: Res<DefinitionsResource>,
+) {
+    for (session_entity, mut session) in sessions.iter_mut() {
+        if !session.takeoff_requested {
+            continue;
+        }
+
+        session.takeoff_requested = false;
+
+        let landed_on = match session.landed_on {
+            Some(has) => has,
+            None => continue,
+        };
+
+        let (planetoid_position, landable, planetoid_star_system) = match planetoids.get(landed_on) {
+            Ok(has) => has,
+            Err(_) => continue, // Player spawning will pick up the pieces if the planetoid has vanished
+        };
+
+        let holdings = match session.session.upgrade() {
+            Some(has) => has.player_profile.get_holdings(),
+            None => continue,
+        };
+
+        // Ships are rebuilt from whatever the player has bought while landed
+        let loadout = match ShipLoadout::from_holdings(&definitions.definitions, &holdings) {
+            Some(has) => has,
+            None => continue,
+        };
+
+        let takeoff_angle = rand::rng().random_range(0.0..std::f64::consts::TAU);
+        let takeoff_position = planetoid_position.position
+            + euclid::Vector2D::<GlobalCoordinateType, WorldCoordinates>::from_angle_and_length(
+                euclid::Angle::radians(takeoff_angle),
+                landable.landing_radius as f64 * TAKEOFF_DISTANCE_FRACTION,
+            );
+
+        let new_ship = match spawn_player_ship(
+            &mut commands,
+            session_entity,
+            &loadout,
+            takeoff_position,
+            planetoid_star_system.copied(),
+            &asset_index.asset_index,
+        ) {
+            Ok(created) => created,
+            Err(()) => continue,
+        };
+
+        session.should_follow = Some(new_ship);
+        session.landed_on = None;
+
+        let _ = session
+            .command_queue_outbound
+            .send(ServerClientMessage::TookOff); // Nothing we can do about send errors for users disconnected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backend::{
+            components::session::player_session_component::PlayerSessionComponent,
+            test_harness::TestHarness,
+        },
+        connectivity::server_client_message::ServerClientMessage,
+    };
+
+    #[test]
+    fn players_below_a_factions_landing_reputation_are_refused_once() {
+        let mut harness = TestHarness::new();
+        let session = harness.connect_player();
+        session.profile().modify_holdings(|holdings| {
+            holdings.reputation.insert("martians".to_string(), -5);
+        });
+
+        harness.land_player(&session, "mars");
+
+        let landed_on = |harness: &TestHarness| {
+            harness
+                .world
+                .get::<PlayerSessionComponent>(session.entity)
+                .unwrap()
+                .landed_on
+        };
+        assert!(landed_on(&harness).is_none());
+
+        // Land was held for two ticks, but the refusal is only sent once
+        let refusals: Vec<String> = session
+            .received()
+            .into_iter()
+            .filter_map(|message| match message {
+                ServerClientMessage::LandingRefused(refused) => Some(refused.reason),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            refusals,
+            vec!["Your reputation with the Martian Republic is too low to land here".to_string()]
+        );
+
+        session.profile().modify_holdings(|holdings| {
+            holdings.reputation.insert("martians".to_string(), 0);
+        });
+        harness.land_player(&session, "mars");
+        assert!(landed_on(&harness).is_some());
+    }
+}
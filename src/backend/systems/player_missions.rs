@@ -0,0 +1,364 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy_ecs::system::{Query, Res};
+
+use crate::{
+    backend::{
+        components::session::player_session_component::PlayerSessionComponent,
+        data_objects::ship_loadout::ShipLoadout,
+        resources::{
+            definitions_resource::DefinitionsResource,
+            profile_save_queue_resource::ProfileSaveQueueResource,
+        },
+        world_objects::components::planetoid_component::PlanetoidComponent,
+    },
+    configuration_file_structures::mission_configuration_file::MissionRecord,
+    connectivity::{
+        mission_message_data::{ActiveMissionData, MissionOfferData, MissionsData},
+        player_info::player_profile::{ActiveMission, PlayerHoldings},
+        server_client_message::ServerClientMessage,
+    },
+};
+
+fn now_unix_seconds() -> u64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_secs(),
+        Err(_) => 0, // Clock is set before 1970, which is not worth failing missions over
+    }
+}
+
+// Missions which need more cargo space than the player has free are held back until they make room
+fn is_offered(
+    mission: &MissionRecord,
+    planetoid_reference: &str,
+    holdings: &PlayerHoldings,
+    cargo_capacity: u32,
+) -> bool {
+    let conditions = &mission.offer_conditions;
+    let cargo = mission.cargo.as_ref().map_or(0, |cargo| cargo.quantity);
+
+    mission.origin == planetoid_reference
+        && holdings.cargo_used().saturating_add(cargo) <= cargo_capacity
+        && !holdings
+            .active_missions
+            .iter()
+            .any(|active| active.mission == mission.mission_reference)
+        && (conditions.repeatable
+            || !holdings
+                .completed_missions
+                .contains(&mission.mission_reference))
+        && conditions
+            .completed_missions
+            .iter()
+            .all(|required| holdings.completed_missions.contains(required))
+}
+
+// Deadlines are only checked here, when the player lands, since landing is the only way to deliver a mission
+// A mission which runs out in flight is failed on the next landing, the client counts down seconds_remaining until then
+pub fn process_player_missions(
+    mut sessions: Query<&mut PlayerSessionComponent>,
+    planetoids: Query<&PlanetoidComponent>,
+    definitions: Res<DefinitionsResource>,
+    profile_saves: Res<ProfileSaveQueueResource>,
+) {
+    let missions = definitions.definitions.get_missions();
+    let factions = definitions.definitions.get_factions();
+
+    let mission_name = |mission: &str| match missions.get_mission_record(mission) {
+        Some(has) => has.mission_display_name.clone(),
+        None => mission.to_string(), // Definitions no longer have a mission accepted before a restart
+    };
+
+    let planetoid_name = |planetoid: &str| match definitions
+        .definitions
+        .get_planetoids()
+        .iter()
+        .find(|record| record.planetoid_reference == planetoid)
+    {
+        Some(has) => has.planetoid_display_name.clone(),
+        None => planetoid.to_string(),
+    };
+
+    for mut session in sessions.iter_mut() {
+        let mission_requests = std::mem::take(&mut session.mission_requests);
+
+        if mission_requests.is_empty() && !session.missions_outdated {
+            continue;
+        }
+
+        session.missions_outdated = false;
+
+        // Missions are only taken on and handed in on the ground
+        let planetoid_entity = match session.landed_on {
+            Some(has) => has,
+            None => continue,
+        };
+
+        let planetoid = match planetoids.get(planetoid_entity) {
+            Ok(has) => &has.planetoid_reference,
+            Err(_) => continue,
+        };
+
+        let profile = match session.session.upgrade() {
+            Some(has) => has.player_profile.clone(),
+            None => continue,
+        };
+
+        let now = now_unix_seconds();
+
+        let (holdings, cargo_capacity, completed, failed, changed) =
+            profile.modify_holdings(|holdings| {
+                let mut completed = Vec::new();
+                let mut failed = Vec::new();
+
+                // Deadlines are checked before deliveries, so landing late never pays out
+                holdings.active_missions.retain(|active| {
+                    let record = match missions.get_mission_record(&active.mission) {
+                        Some(has) => has,
+                        None => {
+                            failed.push(active.mission.clone());
+                            return false;
+                        }
+                    };
+
+                    if active.deadline.is_some_and(|deadline| deadline <= now) {
+                        failed.push(active.mission.clone());
+                        false
+                    } else if record.destination == *planetoid {
+                        completed.push(record);
+                        false
+                    } else {
+                        true
+                    }
+                });
+
+                for record in &completed {
+                    holdings.credits = holdings.credits.saturating_add(record.reward as u64);
+                    holdings
+                        .completed_missions
+                        .insert(record.mission_reference.clone());
+
+                    if let Some(faction) = record
+                        .faction
+                        .as_ref()
+                        .and_then(|faction| factions.get_faction_record(faction))
+                    {
+                        holdings.change_reputation(faction, faction.mission_completed_reputation);
+                    }
+                }
+
+                let cargo_capacity =
+                    match ShipLoadout::from_holdings(&definitions.definitions, holdings) {
+                        Some(has) => has.cargo_capacity(),
+                        None => 0,
+                    };
+
+                let mut changed = !completed.is_empty() || !failed.is_empty();
+
+                for request in &mission_requests {
+                    let record = match missions.get_mission_record(request) {
+                        Some(has) if is_offered(has, planetoid, holdings, cargo_capacity) => has,
+                        _ => {
+                            tracing::debug!("Rejected mission {} which is not on offer", request);
+                            continue;
+                        }
+                    };
+
+                    let cargo = match &record.cargo {
+                        Some(has) => has.quantity,
+                        None => 0,
+                    };
+
+                    holdings.active_missions.push(ActiveMission {
+                        mission: record.mission_reference.clone(),
+                        cargo,
+                        deadline: record.deadline_seconds.map(|seconds| now + seconds),
+                    });
+                    changed = true;
+                }
+
+                let completed: Vec<String> = completed
+                    .iter()
+                    .map(|record| record.mission_display_name.clone())
+                    .collect();
+
+                (holdings.clone(), cargo_capacity, completed, failed, changed)
+            });
+
+        // Credits and cargo space shown elsewhere change along with missions
+        if changed {
+            profile_saves.save(&profile);
+            session.market_outdated = true;
+            session.shipyard_outdated = true;
+        }
+
+        let _ = session
+            .command_queue_outbound
+            .send(ServerClientMessage::Missions(MissionsData {
+                planetoid_id: planetoid_entity.to_bits(),
+                offered: missions
+                    .get_all_mission_records()
+                    .iter()
+                    .filter(|record| is_offered(record, planetoid, &holdings, cargo_capacity))
+                    .map(|record| MissionOfferData {
+                        mission_reference: record.mission_reference.clone(),
+                        mission_name: record.mission_display_name.clone(),
+                        description: record.description.clone(),
+                        destination_name: planetoid_name(&record.destination),
+                        reward: record.reward,
+                        cargo_quantity: record.cargo.as_ref().map_or(0, |cargo| cargo.quantity),
+                        deadline_seconds: record.deadline_seconds,
+                    })
+                    .collect(),
+                active: holdings
+                    .active_missions
+                    .iter()
+                    .filter_map(|active| {
+                        missions.get_mission_record(&active.mission).map(|record| {
+                            ActiveMissionData {
+                                mission_reference: record.mission_reference.clone(),
+                                mission_name: record.mission_display_name.clone(),
+                                destination_name: planetoid_name(&record.destination),
+                                reward: record.reward,
+                                cargo_quantity: active.cargo,
+                                seconds_remaining: active
+                                    .deadline
+                                    .map(|deadline| deadline.saturating_sub(now)),
+                            }
+                        })
+                    })
+                    .collect(),
+                completed,
+                failed: failed.iter().map(|mission| mission_name(mission)).collect(),
+            })); // Nothing we can do about send errors for users disconnected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backend::test_harness::{TestHarness, TestSession},
+        connectivity::{
+            client_server_message::ClientServerMessage, mission_message_data::MissionsData,
+            player_info::player_profile::STARTING_CREDITS,
+            server_client_message::ServerClientMessage,
+        },
+    };
+
+    fn last_missions(session: &TestSession) -> MissionsData {
+        session
+            .received()
+            .into_iter()
+            .filter_map(|message| match message {
+                ServerClientMessage::Missions(missions) => Some(missions),
+                _ => None,
+            })
+            .next_back()
+            .unwrap()
+    }
+
+    fn accept_first_mission_at_earth(harness: &mut TestHarness, session: &TestSession) {
+        harness.land_player(session, "earth");
+
+        let offered = last_missions(session).offered;
+        assert_eq!(offered.len(), 1);
+        assert_eq!(offered[0].mission_reference, "earth_to_mars");
+        assert_eq!(offered[0].destination_name, "mars");
+
+        session.send(ClientServerMessage::AcceptMission {
+            mission: "earth_to_mars".to_string(),
+        });
+        harness.step();
+
+        let missions = last_missions(session);
+        assert!(missions.offered.is_empty());
+        assert_eq!(missions.active[0].cargo_quantity, 10);
+        assert_eq!(session.profile().get_holdings().cargo_used(), 10);
+    }
+
+    fn take_off_and_land_on_mars(harness: &mut TestHarness, session: &TestSession) -> MissionsData {
+        session.send(ClientServerMessage::TakeOff);
+        harness.land_player(session, "mars");
+        last_missions(session)
+    }
+
+    #[test]
+    fn delivering_a_mission_pays_out_and_unlocks_missions_which_require_it() {
+        let mut harness = TestHarness::new();
+        let session = harness.connect_player();
+
+        accept_first_mission_at_earth(&mut harness, &session);
+        let missions = take_off_and_land_on_mars(&mut harness, &session);
+        assert_eq!(missions.completed, vec!["Food for Mars".to_string()]);
+        assert!(missions.active.is_empty());
+
+        let holdings = session.profile().get_holdings();
+        assert_eq!(holdings.credits, STARTING_CREDITS + 3000);
+        assert_eq!(holdings.cargo_used(), 0);
+        assert_eq!(holdings.reputation.get("martians"), Some(&5));
+
+        session.send(ClientServerMessage::TakeOff);
+        harness.land_player(&session, "earth");
+        let offered = last_missions(&session).offered;
+        assert_eq!(offered.len(), 1);
+        assert_eq!(offered[0].mission_reference, "mars_follow_up");
+    }
+
+    #[test]
+    fn missions_past_their_deadline_fail_without_paying_out() {
+        let mut harness = TestHarness::new();
+        let session = harness.connect_player();
+
+        accept_first_mission_at_earth(&mut harness, &session);
+        session
+            .profile()
+            .modify_holdings(|holdings| holdings.active_missions[0].deadline = Some(0));
+        let missions = take_off_and_land_on_mars(&mut harness, &session);
+        assert!(missions.completed.is_empty());
+        assert_eq!(missions.failed, vec!["Food for Mars".to_string()]);
+
+        let holdings = session.profile().get_holdings();
+        assert_eq!(holdings.credits, STARTING_CREDITS);
+        assert!(holdings.active_missions.is_empty());
+        assert!(holdings.completed_missions.is_empty());
+    }
+
+    #[test]
+    fn missions_are_not_offered_without_the_cargo_space_for_them() {
+        let mut harness = TestHarness::new();
+        let session = harness.connect_player();
+        harness.land_player(&session, "earth");
+        assert_eq!(last_missions(&session).offered.len(), 1);
+
+        // The shuttle carries 20 tons and the mission needs 10 of them
+        session.send(ClientServerMessage::BuyCommodity {
+            commodity: "food".to_string(),
+            quantity: 11,
+        });
+        harness.step_for(2);
+        assert!(last_missions(&session).offered.is_empty());
+
+        session.send(ClientServerMessage::AcceptMission {
+            mission: "earth_to_mars".to_string(),
+        });
+        harness.step();
+        assert!(session.profile().get_holdings().active_missions.is_empty());
+    }
+}
@@ -18,68 +18,319 @@
 use bevy_ecs::{
     entity::Entity,
     hierarchy::ChildOf,
-    prelude::{Commands, Query, Res},
+    prelude::{Commands, Query, Res, ResMut},
+    system::SystemParam,
 };
 
 use crate::{
     AssetIndexResource,
     backend::{
         components::session::player_session_component::PlayerSessionComponent,
+        data_objects::ship_loadout::ShipLoadout,
+        resources::{
+            definitions_resource::DefinitionsResource,
+            saved_player_ships_resource::SavedPlayerShipsResource,
+            viewport_settings_resource::ViewportSettingsResource,
+        },
         shape::{CircleData, Shape},
+        systems::{
+            hyperspace_jump::star_system_entered_message, player_landing::landed_message,
+        },
         world_objects::{
             components::{
+                angular_velocity_component::AngularVelocityComponent,
                 collision_component::CollidableComponent,
+                planetoid_component::{LandablePlanetoidComponent, PlanetoidComponent},
                 player_controlled_component::PlayerControlledComponent,
+                position_component::PositionComponent,
+                rotation_component::RotationComponent,
                 semi_newtonian_physics_component::SemiNewtonianPhysicsComponent,
+                star_system_component::StarSystemComponent,
+                velocity_component::VelocityComponent,
             },
             server_viewport::{ServerViewport, ViewportBundle, ViewportTrackingMode},
-            ship::ShipBundle,
         },
+        world_save_file::SavedShipRecord,
     },
-    shared_types::{Coordinates, Radius, Speed},
+    connectivity::asset_index::AssetIndex,
+    shared_types::{AngularVelocity, Coordinates, Rotation, Velocity},
 };
 
+pub fn spawn_player_ship(
+    commands: &mut Commands,
+    session_entity: Entity,
+    loadout: &ShipLoadout,
+    position: Coordinates,
+    star_system: Option<StarSystemComponent>,
+    asset_index: &AssetIndex,
+) -> Result<Entity, ()> {
+    let new_ship = loadout.build(position, asset_index)?;
+    let maximum_speed = new_ship.maneuverability.maximum_speed;
+
+    let mut spawned = commands.spawn((
+        new_ship,
+        SemiNewtonianPhysicsComponent::new(maximum_speed),
+        PlayerControlledComponent {},
+        ChildOf(session_entity),
+    ));
+
+    if let Some(star_system) = star_system {
+        spawned.insert(star_system);
+    }
+
+    Ok(spawned.id())
+}
+
+// Rebuilds a ship from the save file, falling back to a fresh ship if it is no longer the ship the player owns
+fn spawn_saved_player_ship(
+    commands: &mut Commands,
+    session_entity: Entity,
+    saved_ship: &SavedShipRecord,
+    loadout: &ShipLoadout,
+    definitions: &DefinitionsResource,
+    asset_index: &AssetIndex,
+) -> Result<(Entity, Option<StarSystemComponent>), ()> {
+    if saved_ship.ship_reference != loadout.ship.ship_reference {
+        tracing::warn!(
+            "Saved player ship {} is no longer the {} the player owns, so a new ship will be spawned",
+            saved_ship.ship_reference,
+            loadout.ship.ship_reference
+        );
+        return Err(());
+    }
+
+    let star_system = saved_ship
+        .star_system
+        .as_ref()
+        .and_then(|x| definitions.definitions.get_star_systems().get_star_system_id(x))
+        .map(|star_system| StarSystemComponent { star_system });
+
+    let new_ship_id = spawn_player_ship(
+        commands,
+        session_entity,
+        loadout,
+        Coordinates::new(saved_ship.position_x, saved_ship.position_y),
+        star_system,
+        asset_index,
+    )?;
+
+    commands.entity(new_ship_id).insert((
+        VelocityComponent {
+            velocity: Velocity::new(saved_ship.velocity_x, saved_ship.velocity_y),
+        },
+        RotationComponent {
+            rotation: Rotation::radians(saved_ship.rotation),
+        },
+        AngularVelocityComponent {
+            angular_velocity: AngularVelocity::radians(saved_ship.angular_velocity),
+        },
+    ));
+
+    Ok((new_ship_id, star_system))
+}
+
+fn send_star_system_entered(
+    session: &PlayerSessionComponent,
+    definitions: &DefinitionsResource,
+    star_system: StarSystemComponent,
+) {
+    if let Some(message) = star_system_entered_message(
+        definitions.definitions.get_star_systems(),
+        star_system.star_system,
+    ) {
+        let _ = session.command_queue_outbound.send(message); // Nothing we can do about send errors for users disconnected
+    }
+}
+
+type LandablePlanetoids<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static PlanetoidComponent,
+        &'static LandablePlanetoidComponent,
+    ),
+>;
+
+// Puts players saved while landed back on their planetoid, leaving anyone else to be spawned in flight
+fn restore_landed_player(
+    session: &mut PlayerSessionComponent,
+    saved_player_ships: &mut SavedPlayerShipsResource,
+    planetoids: &LandablePlanetoids,
+) {
+    let profile = match session.session.upgrade() {
+        Some(has) => has.player_profile.clone(),
+        None => return,
+    };
+
+    if !profile.is_persistent() {
+        return;
+    }
+
+    let identifier = profile.get_credentials().get_identifier().to_string();
+
+    let landed_on = match saved_player_ships
+        .ships
+        .get(&identifier)
+        .and_then(|saved_ship| saved_ship.landed_on.as_ref())
+    {
+        Some(has) => has,
+        None => return,
+    };
+
+    // A planetoid which is gone leaves the saved ship to be spawned where it used to be
+    let (planetoid_entity, planetoid, landable) = match planetoids
+        .iter()
+        .find(|(_, planetoid, _)| planetoid.planetoid_reference == *landed_on)
+    {
+        Some(has) => has,
+        None => {
+            tracing::warn!(
+                "Saved player {} was landed on planetoid {} which no longer exists",
+                identifier,
+                landed_on
+            );
+            return;
+        }
+    };
+
+    saved_player_ships.ships.remove(&identifier);
+    session.landed_on = Some(planetoid_entity);
+    session.market_outdated = true;
+    session.shipyard_outdated = true;
+    session.missions_outdated = true;
+
+    let _ = session
+        .command_queue_outbound
+        .send(landed_message(planetoid_entity, planetoid, landable)); // Nothing we can do about send errors for users disconnected
+}
+
+// Everything player ships and viewports are built from
+#[derive(SystemParam)]
+pub struct PlayerSpawnResources<'w, 's> {
+    asset_index: Res<'w, AssetIndexResource>,
+    definitions: Res<'w, DefinitionsResource>,
+    viewport_settings: Res<'w, ViewportSettingsResource>,
+    saved_player_ships: ResMut<'w, SavedPlayerShipsResource>,
+    planetoids: LandablePlanetoids<'w, 's>,
+}
+
 pub fn spawn_player_ship_and_viewports(
     entities: Query<Entity>,
+    positions: Query<&PositionComponent>,
+    star_systems: Query<&StarSystemComponent>,
     mut sessions: Query<(Entity, &mut PlayerSessionComponent)>,
     mut viewports: Query<&mut ServerViewport>,
     mut commands: Commands,
-    asset_index: Res<AssetIndexResource>,
+    resources: PlayerSpawnResources,
 ) {
+    let PlayerSpawnResources {
+        asset_index,
+        definitions,
+        viewport_settings,
+        mut saved_player_ships,
+        planetoids,
+    } = resources;
+
     sessions
         .iter_mut()
         .for_each(|(session_entity, mut session)| {
-            let following_id = if let Some(following) = session.should_follow {
-                if entities.contains(following) {
-                    Some(following)
-                } else {
-                    None
-                }
-            } else {
-                None
+            // Only players who have just connected have neither a ship nor a planetoid
+            if session.landed_on.is_none() && session.should_follow.is_none() {
+                restore_landed_player(&mut session, &mut saved_player_ships, &planetoids);
+            }
+
+            // Landed players have no ship in the world, so the viewport stays on the planetoid they are landed on
+            let landed_position = match session.landed_on {
+                Some(landed_on) => match positions.get(landed_on) {
+                    Ok(position) => Some(position.position),
+                    Err(_) => {
+                        // Planetoid has somehow been destroyed, so there is nothing to stay landed on
+                        session.landed_on = None;
+                        None
+                    }
+                },
+                None => None,
             };
 
-            let following_id = match following_id {
-                Some(following) => following,
+            let (tracking_mode, star_system) = match landed_position {
+                Some(position) => (
+                    ViewportTrackingMode::Static(position),
+                    session
+                        .landed_on
+                        .and_then(|landed_on| star_systems.get(landed_on).ok().copied()),
+                ),
                 None => {
-                    let new_ship = ShipBundle::new(
-                        Coordinates::new(0.0, 0.0),
-                        None,
-                        None,
-                        None,
-                        &asset_index.asset_index,
-                    )
-                    .unwrap();
-                    let new_ship_id = commands
-                        .spawn((
-                            new_ship,
-                            SemiNewtonianPhysicsComponent::new(Speed::new(200.0)),
-                            PlayerControlledComponent {},
-                            ChildOf(session_entity),
-                        ))
-                        .id();
-                    session.should_follow = Some(new_ship_id);
-                    new_ship_id
+                    let following_id = session
+                        .should_follow
+                        .filter(|following| entities.contains(*following));
+
+                    match following_id {
+                        Some(following) => (
+                            ViewportTrackingMode::Entity(following),
+                            star_systems.get(following).ok().copied(),
+                        ),
+                        None => {
+                            let profile = match session.session.upgrade() {
+                                Some(has) => has.player_profile.clone(),
+                                None => return, // Session cleanup will remove the rest
+                            };
+
+                            let holdings = profile.get_holdings();
+                            let loadout = match ShipLoadout::from_holdings(&definitions.definitions, &holdings) {
+                                Some(has) => has,
+                                None => {
+                                    tracing::warn!("No ship definitions are loaded to spawn player ships with");
+                                    return;
+                                }
+                            };
+
+                            // Returning players pick up the ship they were flying when the server last stopped
+                            let saved_ship = match profile.is_persistent() {
+                                true => saved_player_ships
+                                    .ships
+                                    .remove(profile.get_credentials().get_identifier()),
+                                false => None,
+                            };
+
+                            let restored_ship = saved_ship.and_then(|saved_ship| {
+                                spawn_saved_player_ship(
+                                    &mut commands,
+                                    session_entity,
+                                    &saved_ship,
+                                    &loadout,
+                                    &definitions,
+                                    &asset_index.asset_index,
+                                )
+                                .ok()
+                            });
+
+                            let (new_ship_id, new_star_system) = match restored_ship {
+                                Some(restored) => restored,
+                                None => {
+                                    let starting_star_system = definitions
+                                        .definitions
+                                        .get_star_systems()
+                                        .get_player_starting_system()
+                                        .map(|star_system| StarSystemComponent { star_system });
+
+                                    match spawn_player_ship(
+                                        &mut commands,
+                                        session_entity,
+                                        &loadout,
+                                        Coordinates::new(0.0, 0.0),
+                                        starting_star_system,
+                                        &asset_index.asset_index,
+                                    ) {
+                                        Ok(created) => (created, starting_star_system),
+                                        Err(()) => return,
+                                    }
+                                }
+                            };
+                            session.should_follow = Some(new_ship_id);
+                            (ViewportTrackingMode::Entity(new_ship_id), new_star_system)
+                        }
+                    }
                 }
             };
 
@@ -88,7 +339,20 @@ pub fn spawn_player_ship_and_viewports(
                     match viewports.get_mut(viewport_exists) {
                         // Viewport exists already
                         Ok(has) => {
-                            has.set_tracking_mode(ViewportTrackingMode::Entity(following_id));
+                            has.set_tracking_mode(tracking_mode);
+
+                            // Viewports only see the star system of whatever they are watching
+                            if star_systems.get(viewport_exists).ok().copied() != star_system {
+                                match star_system {
+                                    Some(star_system) => {
+                                        commands.entity(viewport_exists).insert(star_system);
+                                        send_star_system_entered(&session, &definitions, star_system);
+                                    }
+                                    None => {
+                                        commands.entity(viewport_exists).remove::<StarSystemComponent>();
+                                    }
+                                }
+                            }
                         }
                         // Viewport has somehow been destroyed, forget reference
                         Err(_destroyed) => {
@@ -97,17 +361,21 @@ pub fn spawn_player_ship_and_viewports(
                     }
                 }
                 None => {
-                    let new_viewport = commands
-                        .spawn(ViewportBundle {
-                            viewport: ServerViewport::new(),
-                            collidable: CollidableComponent::new(Shape::Circle(CircleData {
-                                location: Coordinates::new(0.0, 0.0),
-                                radius: Radius::new(6000.0),
-                            })),
-                            parent_session: ChildOf(session_entity),
-                        })
-                        .id();
-                    session.primary_viewport = Some(new_viewport);
+                    let mut new_viewport = commands.spawn(ViewportBundle {
+                        viewport: ServerViewport::new(),
+                        collidable: CollidableComponent::new(Shape::Circle(CircleData {
+                            location: Coordinates::new(0.0, 0.0),
+                            radius: viewport_settings.viewport_radius,
+                        })),
+                        parent_session: ChildOf(session_entity),
+                    });
+
+                    if let Some(star_system) = star_system {
+                        new_viewport.insert(star_system);
+                        send_star_system_entered(&session, &definitions, star_system);
+                    }
+
+                    session.primary_viewport = Some(new_viewport.id());
                 }
             }
         });
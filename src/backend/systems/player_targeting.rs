@@ -0,0 +1,361 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::time::Duration;
+
+use bevy_ecs::{
+    entity::Entity,
+    query::{Or, With},
+    system::{Commands, Query, Res},
+};
+
+use crate::{
+    backend::{
+        components::session::player_session_component::PlayerSessionComponent,
+        data_objects::target_request::TargetRequest,
+        resources::{definitions_resource::DefinitionsResource, delta_t_resource::DeltaTResource},
+        world_objects::{
+            components::{
+                health_component::HealthComponent, planetoid_component::PlanetoidComponent,
+                position_component::PositionComponent, ship_type_component::ShipTypeComponent,
+                target_component::TargetComponent, velocity_component::VelocityComponent,
+            },
+            server_viewport::ServerViewport,
+        },
+    },
+    connectivity::{
+        server_client_message::ServerClientMessage,
+        target_message_data::{TargetHealthData, TargetInfoData},
+    },
+};
+
+// Distances and health change every tick in a fight, so each player is told about their target at most this often
+const TARGET_INFO_INTERVAL: Duration = Duration::from_millis(250);
+
+// Ships and planetoids can be targeted, projectiles and the like can't
+type Targetable = Or<(With<ShipTypeComponent>, With<PlanetoidComponent>)>;
+
+type TargetDetails<'a> = (
+    &'a PositionComponent,
+    Option<&'a VelocityComponent>,
+    Option<&'a HealthComponent>,
+    Option<&'a ShipTypeComponent>,
+    Option<&'a PlanetoidComponent>,
+);
+
+fn clear_target(session: &mut PlayerSessionComponent, ship: Entity, commands: &mut Commands) {
+    commands.entity(ship).try_remove::<TargetComponent>();
+    let _ = session
+        .command_queue_outbound
+        .send(ServerClientMessage::TargetCleared); // Nothing we can do about send errors for users disconnected
+}
+
+pub fn select_player_targets(
+    mut sessions: Query<&mut PlayerSessionComponent>,
+    viewports: Query<&ServerViewport>,
+    targetables: Query<(Entity, &PositionComponent), Targetable>,
+    current_targets: Query<&TargetComponent>,
+    mut commands: Commands,
+) {
+    for mut session in sessions.iter_mut() {
+        let request = match session.target_request.take() {
+            Some(has) => has,
+            None => continue,
+        };
+
+        let ship = match session.should_follow {
+            Some(has) => has,
+            None => continue, // Landed players have nothing to target with
+        };
+
+        let ship_position = match targetables.get(ship) {
+            Ok((_, position)) => position.position,
+            Err(_) => continue,
+        };
+
+        // Players can only pick out what their own client has been shown
+        let viewport = match session
+            .primary_viewport
+            .and_then(|viewport| viewports.get(viewport).ok())
+        {
+            Some(has) => has,
+            None => continue,
+        };
+
+        let target = match request {
+            TargetRequest::Select(None) => None,
+            TargetRequest::Select(Some(target)) => {
+                if target == ship || !targetables.contains(target) || !viewport.can_see(target) {
+                    tracing::debug!("Rejected target {:?} which the player can't see", target);
+                    continue;
+                }
+
+                Some(target)
+            }
+            TargetRequest::CycleNearest => {
+                let mut candidates: Vec<(Entity, f64)> = targetables
+                    .iter()
+                    .filter(|(entity, _)| *entity != ship && viewport.can_see(*entity))
+                    .map(|(entity, position)| {
+                        (entity, position.position.distance_to(ship_position))
+                    })
+                    .collect();
+                candidates.sort_by(|x, y| x.1.total_cmp(&y.1));
+
+                let current = current_targets.get(ship).ok().and_then(|current| {
+                    candidates
+                        .iter()
+                        .position(|(entity, _)| *entity == current.target)
+                });
+
+                let next = match current {
+                    Some(index) => (index + 1) % candidates.len(),
+                    None => 0,
+                };
+
+                candidates.get(next).map(|(entity, _)| *entity)
+            }
+        };
+
+        match target {
+            Some(target) => {
+                commands.entity(ship).try_insert(TargetComponent { target });
+                session.target_info_cooldown = Duration::ZERO; // Tell the player about their new target straight away
+            }
+            None => clear_target(&mut session, ship, &mut commands),
+        }
+    }
+}
+
+pub fn send_target_info(
+    mut sessions: Query<&mut PlayerSessionComponent>,
+    ships: Query<(&PositionComponent, &VelocityComponent, &TargetComponent)>,
+    targets: Query<TargetDetails<'_>>,
+    viewports: Query<&ServerViewport>,
+    definitions: Res<DefinitionsResource>,
+    time: Res<DeltaTResource>,
+    mut commands: Commands,
+) {
+    let delta_t = time.get_last_tick_duration();
+
+    for mut session in sessions.iter_mut() {
+        session.target_info_cooldown = session.target_info_cooldown.saturating_sub(delta_t);
+
+        let ship = match session.should_follow {
+            Some(has) => has,
+            None => continue,
+        };
+
+        let (ship_position, ship_velocity, target) = match ships.get(ship) {
+            Ok(has) => has,
+            Err(_) => continue, // Nothing targeted
+        };
+
+        let visible = session
+            .primary_viewport
+            .and_then(|viewport| viewports.get(viewport).ok())
+            .is_some_and(|viewport| viewport.can_see(target.target));
+
+        // Targets which are destroyed, land or fly out of sight are lost
+        let (position, velocity, health, ship_type, planetoid) = match targets.get(target.target) {
+            Ok(has) if visible => has,
+            _ => {
+                clear_target(&mut session, ship, &mut commands);
+                continue;
+            }
+        };
+
+        // Lost targets are cleared straight away, but updates on the rest wait their turn
+        if !session.target_info_cooldown.is_zero() {
+            continue;
+        }
+
+        let name = match (ship_type, planetoid) {
+            (Some(ship_type), _) => match definitions
+                .definitions
+                .get_ships()
+                .get_ship_record(&ship_type.ship_reference)
+            {
+                Some(has) => has.ship_display_name.clone(),
+                None => ship_type.ship_reference.clone(),
+            },
+            (None, Some(planetoid)) => planetoid.planetoid_display_name.clone(),
+            (None, None) => String::new(),
+        };
+
+        let relative_velocity = match velocity {
+            Some(has) => has.velocity - ship_velocity.velocity,
+            None => -ship_velocity.velocity,
+        };
+
+        let info = TargetInfoData {
+            target_id: target.target.to_bits(),
+            name,
+            distance: position.position.distance_to(ship_position.position),
+            relative_vx: relative_velocity.x,
+            relative_vy: relative_velocity.y,
+            health: health.map(|health| TargetHealthData {
+                hull: health.hull,
+                maximum_hull: health.maximum_hull,
+                shield: health.shield,
+                maximum_shield: health.maximum_shield,
+            }),
+        };
+
+        let _ = session
+            .command_queue_outbound
+            .send(ServerClientMessage::TargetInfo(info)); // Nothing we can do about send errors for users disconnected
+        session.target_info_cooldown = TARGET_INFO_INTERVAL;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::entity::Entity;
+
+    use crate::{
+        backend::{
+            test_harness::{TestHarness, TestSession},
+            world_objects::components::{
+                position_component::PositionComponent, target_component::TargetComponent,
+            },
+        },
+        connectivity::{
+            client_server_message::{ClientServerMessage, ControlInput},
+            server_client_message::ServerClientMessage,
+            target_message_data::TargetInfoData,
+        },
+        shared_types::{Coordinates, Velocity},
+    };
+
+    // Moves the player's ship out into empty space, with ships at the given distances straight ahead of it
+    fn player_with_ships_at(
+        harness: &mut TestHarness,
+        session: &TestSession,
+        distances: &[f64],
+    ) -> (Entity, Vec<Entity>) {
+        harness.step_for(3);
+
+        let ship = harness.player_ship(session);
+        let position = Coordinates::new(20000.0, 20000.0);
+        harness
+            .world
+            .get_mut::<PositionComponent>(ship)
+            .unwrap()
+            .position = position;
+
+        let others = distances
+            .iter()
+            .map(|distance| {
+                harness.spawn_ship(
+                    "shuttle",
+                    position + euclid::vec2(*distance, 0.0),
+                    Velocity::zero(),
+                    Some("sol"),
+                )
+            })
+            .collect();
+
+        harness.step_for(3);
+        session.received();
+
+        (ship, others)
+    }
+
+    fn target_of(harness: &TestHarness, ship: Entity) -> Option<Entity> {
+        harness
+            .world
+            .get::<TargetComponent>(ship)
+            .map(|target| target.target)
+    }
+
+    fn target_infos(messages: Vec<ServerClientMessage>) -> Vec<TargetInfoData> {
+        messages
+            .into_iter()
+            .filter_map(|message| match message {
+                ServerClientMessage::TargetInfo(info) => Some(info),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn cycle_target(harness: &mut TestHarness, session: &TestSession) {
+        session.send(ClientServerMessage::ControlInput {
+            input: ControlInput::CycleTarget,
+            pressed: true,
+        });
+        harness.step();
+        session.send(ClientServerMessage::ControlInput {
+            input: ControlInput::CycleTarget,
+            pressed: false,
+        });
+        harness.step();
+    }
+
+    #[test]
+    fn cycling_picks_visible_ships_nearest_first_and_wraps_around() {
+        let mut harness = TestHarness::new();
+        let session = harness.connect_player();
+        let (ship, others) = player_with_ships_at(&mut harness, &session, &[600.0, 300.0]);
+
+        cycle_target(&mut harness, &session);
+        assert_eq!(target_of(&harness, ship), Some(others[1]));
+
+        let infos = target_infos(session.received());
+        assert_eq!(infos.len(), 1);
+        assert_eq!(infos[0].target_id, others[1].to_bits());
+        assert_eq!(infos[0].name, "Shuttle");
+        assert!((infos[0].distance - 300.0).abs() < 1.0);
+        assert_eq!(infos[0].health.as_ref().unwrap().hull, 100.0);
+
+        cycle_target(&mut harness, &session);
+        assert_eq!(target_of(&harness, ship), Some(others[0]));
+
+        cycle_target(&mut harness, &session);
+        assert_eq!(target_of(&harness, ship), Some(others[1]));
+    }
+
+    #[test]
+    fn only_visible_targets_can_be_selected_and_lost_targets_are_cleared() {
+        let mut harness = TestHarness::new();
+        let session = harness.connect_player();
+        let (ship, others) = player_with_ships_at(&mut harness, &session, &[300.0, 100000.0]);
+
+        session.send(ClientServerMessage::SelectTarget {
+            id: Some(others[1].to_bits()),
+        });
+        harness.step();
+        assert_eq!(target_of(&harness, ship), None);
+
+        session.send(ClientServerMessage::SelectTarget {
+            id: Some(others[0].to_bits()),
+        });
+        harness.step();
+        assert_eq!(target_of(&harness, ship), Some(others[0]));
+
+        harness.world.despawn(others[0]);
+        session.received();
+        harness.step_for(2);
+        assert_eq!(target_of(&harness, ship), None);
+        assert!(
+            session
+                .received()
+                .iter()
+                .any(|message| matches!(message, ServerClientMessage::TargetCleared))
+        );
+    }
+}
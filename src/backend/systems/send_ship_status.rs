@@ -0,0 +1,138 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::time::Duration;
+
+use bevy_ecs::system::{Query, Res};
+
+use crate::{
+    backend::{
+        components::session::player_session_component::PlayerSessionComponent,
+        resources::delta_t_resource::DeltaTResource,
+        world_objects::components::{
+            cargo_component::CargoComponent, energy_component::EnergyComponent,
+            fuel_component::FuelComponent, health_component::HealthComponent,
+        },
+    },
+    connectivity::{
+        server_client_message::ServerClientMessage, ship_status_message_data::ShipStatusData,
+    },
+};
+
+// Statuses change almost every tick while anything is regenerating, so each player gets at most one this often
+const SHIP_STATUS_INTERVAL: Duration = Duration::from_millis(250);
+
+pub fn send_ship_status(
+    mut sessions: Query<&mut PlayerSessionComponent>,
+    ships: Query<(
+        &HealthComponent,
+        &FuelComponent,
+        &EnergyComponent,
+        &CargoComponent,
+    )>,
+    time: Res<DeltaTResource>,
+) {
+    let delta_t = time.get_last_tick_duration();
+
+    for mut session in sessions.iter_mut() {
+        session.ship_status_cooldown = session.ship_status_cooldown.saturating_sub(delta_t);
+
+        if !session.ship_status_cooldown.is_zero() {
+            continue;
+        }
+
+        let ship = match session.should_follow {
+            Some(has) => has,
+            None => continue, // Landed players have no ship to report on
+        };
+
+        let (health, fuel, energy, cargo) = match ships.get(ship) {
+            Ok(has) => has,
+            Err(_) => continue,
+        };
+
+        let status = ShipStatusData {
+            ship_id: ship.to_bits(),
+            hull: health.hull,
+            maximum_hull: health.maximum_hull,
+            shield: health.shield,
+            maximum_shield: health.maximum_shield,
+            fuel: fuel.fuel,
+            maximum_fuel: fuel.maximum_fuel,
+            energy: energy.energy,
+            maximum_energy: energy.maximum_energy,
+            cargo_capacity: cargo.capacity,
+        };
+
+        if session.last_ship_status.as_ref() == Some(&status) {
+            continue;
+        }
+
+        let _ = session
+            .command_queue_outbound
+            .send(ServerClientMessage::ShipStatus(status.clone())); // Nothing we can do about send errors for users disconnected
+        session.last_ship_status = Some(status);
+        session.ship_status_cooldown = SHIP_STATUS_INTERVAL;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backend::{
+            test_harness::TestHarness,
+            world_objects::components::health_component::HealthComponent,
+        },
+        connectivity::{
+            server_client_message::ServerClientMessage, ship_status_message_data::ShipStatusData,
+        },
+    };
+
+    fn statuses(messages: Vec<ServerClientMessage>) -> Vec<ShipStatusData> {
+        messages
+            .into_iter()
+            .filter_map(|message| match message {
+                ServerClientMessage::ShipStatus(status) => Some(status),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn status_is_sent_for_the_players_ship_only_when_it_changes() {
+        let mut harness = TestHarness::new();
+        let session = harness.connect_player();
+        harness.step_for(3);
+
+        let ship = harness.player_ship(&session);
+
+        let sent = statuses(session.received());
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].ship_id, ship.to_bits());
+        assert_eq!(sent[0].shield, sent[0].maximum_shield);
+
+        // Nothing changes on a ship sitting at full health
+        harness.step_for(50);
+        assert!(statuses(session.received()).is_empty());
+
+        harness.world.get_mut::<HealthComponent>(ship).unwrap().hull = 40.0;
+        harness.step_for(20);
+        let sent = statuses(session.received());
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].hull, 40.0);
+    }
+}
@@ -0,0 +1,445 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+use std::time::Duration;
+
+use bevy_ecs::{
+    entity::Entity,
+    query::{Has, With, Without},
+    system::{Commands, Query, Res, ResMut},
+};
+use euclid::Angle;
+use rand::{Rng, seq::IndexedRandom};
+
+use crate::{
+    AssetIndexResource,
+    backend::{
+        resources::{
+            definitions_resource::DefinitionsResource, delta_t_resource::DeltaTResource,
+            traffic_spawner_resource::TrafficSpawnerResource,
+        },
+        spatial_optimizer::collision_optimizer::CollisionOptimizer,
+        world_objects::{
+            components::{
+                cloak_component::CloakComponent,
+                faction_component::FactionComponent,
+                npc_controller_component::{NpcBehavior, NpcControllerComponent},
+                physical_component::Physical,
+                planetoid_component::{LandablePlanetoidComponent, PlanetoidComponent},
+                player_controlled_component::PlayerControlledComponent,
+                position_component::PositionComponent,
+                semi_newtonian_physics_component::SemiNewtonianPhysicsComponent,
+                ship_type_component::ShipTypeComponent,
+                star_system_component::StarSystemComponent,
+                timeout_component::TimeoutComponent,
+                traffic_component::TrafficComponent,
+            },
+            ship::ShipBundle,
+        },
+    },
+    configuration_file_structures::star_system_configuration_file::TrafficSpawnLocation,
+    shared_types::{Coordinates, Distance, Radius, Speed, Velocity},
+};
+
+// How far from the system center ships are sent when they have nowhere better to go
+const RANDOM_DESTINATION_RADIUS: f64 = 2000.0;
+
+// How closely ships escorting another ship stay with it
+const ESCORT_DISTANCE: f64 = 150.0;
+
+// How far ships spooked by a player ship try to get from it
+const FLEE_DISTANCE: f64 = 1500.0;
+
+// How far newly spawned ships notice other ships from
+const SENSOR_RANGE: f64 = 3000.0;
+
+// How many of the closest ships of the same faction a new ship chooses between escorting
+const ESCORT_CANDIDATES: usize = 3;
+
+// How far from a planetoid's center ships taking off from it appear
+const TAKE_OFF_SCATTER: f64 = 100.0;
+
+// Fraction of maximum speed ships arriving at the system edge are already moving at
+const ARRIVAL_SPEED_FRACTION: f32 = 0.5;
+
+fn plus_or_minus_random(radius: f64) -> f64 {
+    let value = rand::rng().random::<f64>();
+    let range = radius * 2.0;
+    (range * value) - radius
+}
+
+// Pick something for a newly spawned ship to do with the planetoids and ships in its star system
+fn choose_random_behavior(
+    waypoints: Vec<Entity>,
+    landing_sites: &[Entity],
+    escorted: &[Entity],
+    players: &[Entity],
+) -> NpcBehavior {
+    let mut rng = rand::rng();
+
+    match rng.random_range(0..5) {
+        0 if waypoints.len() >= 2 => NpcBehavior::Patrol {
+            waypoints,
+            next_waypoint: 0,
+        },
+        1 if !landing_sites.is_empty() => NpcBehavior::Land {
+            planetoid: landing_sites[rng.random_range(0..landing_sites.len())],
+        },
+        2 if !escorted.is_empty() => NpcBehavior::Follow {
+            target: escorted[rng.random_range(0..escorted.len())],
+            distance: Distance::new(ESCORT_DISTANCE),
+        },
+        3 if !players.is_empty() => NpcBehavior::Flee {
+            target: players[rng.random_range(0..players.len())],
+            distance: Distance::new(FLEE_DISTANCE),
+        },
+        _ => NpcBehavior::TravelTo {
+            destination: Coordinates::new(
+                plus_or_minus_random(RANDOM_DESTINATION_RADIUS),
+                plus_or_minus_random(RANDOM_DESTINATION_RADIUS),
+            ),
+        },
+    }
+}
+
+// Counts down each star system's traffic on simulation time, leaving a placeholder behind for each ship due to spawn
+pub fn tick_traffic_spawners(
+    traffic_ships: Query<&TrafficComponent>,
+    definitions: Res<DefinitionsResource>,
+    mut spawner: ResMut<TrafficSpawnerResource>,
+    time: Res<DeltaTResource>,
+    mut commands: Commands,
+) {
+    let delta_t = time.get_last_tick_duration();
+    let star_systems = definitions
+        .definitions
+        .get_star_systems()
+        .get_all_star_system_records();
+
+    for (star_system, star_system_record) in star_systems.iter().enumerate() {
+        for (traffic_index, traffic) in star_system_record.traffic.iter().enumerate() {
+            let time_since_spawn = spawner
+                .time_since_spawn
+                .entry((star_system, traffic_index))
+                .or_default();
+            *time_since_spawn += delta_t;
+
+            if time_since_spawn.as_secs_f64() < traffic.spawn_interval_seconds {
+                continue;
+            }
+
+            // A full population holds off until the next interval, rather than spawning a burst of ships the moment there is room
+            *time_since_spawn = Duration::ZERO;
+
+            // Placeholders count toward the population too, so a ship which has not been built yet is not doubled up
+            let population = traffic_ships
+                .iter()
+                .filter(|ship| {
+                    ship.star_system == star_system && ship.traffic_index == traffic_index
+                })
+                .count();
+
+            if population >= traffic.maximum_population {
+                continue;
+            }
+
+            commands.spawn(TrafficComponent {
+                star_system,
+                traffic_index,
+            });
+        }
+    }
+}
+
+// Builds the ships for traffic placeholders left by tick_traffic_spawners
+pub fn spawn_traffic_ships(
+    placeholders: Query<(Entity, &TrafficComponent), Without<ShipTypeComponent>>,
+    planetoids: Query<
+        (
+            Entity,
+            &PositionComponent,
+            &StarSystemComponent,
+            Has<LandablePlanetoidComponent>,
+        ),
+        With<PlanetoidComponent>,
+    >,
+    ships: Query<(
+        Option<&FactionComponent>,
+        Has<PlayerControlledComponent>,
+        Has<NpcControllerComponent>,
+    )>,
+    physical: Res<CollisionOptimizer<Physical>>,
+    asset_index: Res<AssetIndexResource>,
+    definitions: Res<DefinitionsResource>,
+    mut commands: Commands,
+) {
+    for (placeholder, traffic_ship) in placeholders.iter() {
+        let traffic = match definitions
+            .definitions
+            .get_star_systems()
+            .get_star_system_record(traffic_ship.star_system)
+            .and_then(|star_system| star_system.traffic.get(traffic_ship.traffic_index))
+        {
+            Some(has) => has,
+            None => {
+                commands.entity(placeholder).despawn();
+                continue;
+            }
+        };
+
+        let ship_record = match traffic
+            .ships
+            .choose(&mut rand::rng())
+            .and_then(|ship| definitions.definitions.get_ships().get_ship_record(ship))
+        {
+            Some(has) => has,
+            None => {
+                commands.entity(placeholder).despawn();
+                continue;
+            }
+        };
+
+        let star_system = StarSystemComponent {
+            star_system: traffic_ship.star_system,
+        };
+
+        let mut waypoints = Vec::new();
+        let mut landing_sites = Vec::new();
+        let mut take_off_sites = Vec::new();
+
+        for (planetoid, position, planetoid_star_system, landable) in planetoids.iter() {
+            if *planetoid_star_system != star_system {
+                continue;
+            }
+
+            waypoints.push(planetoid);
+            take_off_sites.push((planetoid, position.position));
+
+            if landable {
+                landing_sites.push(planetoid);
+            }
+        }
+
+        let (position, velocity, rotation) = match &traffic.spawn_location {
+            TrafficSpawnLocation::Planetoids => match take_off_sites.choose(&mut rand::rng()) {
+                Some((take_off_planetoid, planetoid_position)) => {
+                    // Landing straight back where it took off from would make the ship vanish on the spot
+                    landing_sites.retain(|planetoid| planetoid != take_off_planetoid);
+
+                    (
+                        Coordinates::new(
+                            planetoid_position.x + plus_or_minus_random(TAKE_OFF_SCATTER),
+                            planetoid_position.y + plus_or_minus_random(TAKE_OFF_SCATTER),
+                        ),
+                        Velocity::zero(),
+                        Angle::radians(plus_or_minus_random(std::f64::consts::PI) as f32),
+                    )
+                }
+                None => {
+                    tracing::warn!(
+                        "Traffic in star system {} spawns at planetoids but the system has none",
+                        traffic_ship.star_system
+                    );
+                    commands.entity(placeholder).despawn();
+                    continue;
+                }
+            },
+            TrafficSpawnLocation::SystemEdge { distance } => {
+                let bearing = Angle::radians(plus_or_minus_random(std::f64::consts::PI) as f32);
+                let inward = bearing + Angle::pi();
+
+                (
+                    Coordinates::new(
+                        bearing.radians.cos() as f64 * distance,
+                        bearing.radians.sin() as f64 * distance,
+                    ),
+                    Velocity::from_angle_and_length(
+                        inward,
+                        ship_record.maximum_speed * ARRIVAL_SPEED_FRACTION,
+                    ),
+                    inward.signed(),
+                )
+            }
+        };
+
+        // Ships only react to what they can sense from where they turn up
+        let players: Vec<Entity> = physical
+            .within_radius(
+                Some(traffic_ship.star_system),
+                position,
+                Radius::new(SENSOR_RANGE),
+            )
+            .into_iter()
+            .filter(|ship| matches!(ships.get(*ship), Ok((_, true, _))))
+            .collect();
+
+        // Traffic only escorts ships of its own faction
+        let escorted: Vec<Entity> = physical
+            .nearest(
+                Some(traffic_ship.star_system),
+                position,
+                ESCORT_CANDIDATES,
+                Distance::new(SENSOR_RANGE),
+                |ship| match ships.get(ship) {
+                    Ok((faction, _, true)) => {
+                        faction.map(|has| &has.faction) == traffic.faction.as_ref()
+                    }
+                    _ => false,
+                },
+            )
+            .into_iter()
+            .map(|(ship, _)| ship)
+            .collect();
+
+        let new_ship = match ShipBundle::new(
+            ship_record,
+            position,
+            Some(velocity),
+            Some(rotation),
+            None,
+            &asset_index.asset_index,
+        ) {
+            Ok(created) => created,
+            Err(()) => {
+                commands.entity(placeholder).despawn();
+                continue;
+            }
+        };
+
+        let behavior = choose_random_behavior(waypoints, &landing_sites, &escorted, &players);
+
+        let mut spawned = commands.entity(placeholder);
+        spawned.insert((
+            new_ship,
+            SemiNewtonianPhysicsComponent::new(Speed::new(ship_record.maximum_speed)),
+            NpcControllerComponent::new(behavior),
+            TimeoutComponent::new(Duration::from_secs_f64(traffic.lifetime_seconds)),
+            star_system,
+        ));
+
+        if let Some(faction) = &traffic.faction {
+            spawned.insert(FactionComponent {
+                faction: faction.clone(),
+            });
+        }
+
+        if traffic.cloaked {
+            spawned.insert(CloakComponent {});
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::query::With;
+    use serde_json::json;
+
+    use crate::{
+        backend::{
+            test_harness::{TestHarness, test_definitions_with_traffic},
+            world_objects::components::{
+                faction_component::FactionComponent, position_component::PositionComponent,
+                traffic_component::TrafficComponent,
+            },
+        },
+        shared_types::Coordinates,
+    };
+
+    fn traffic_positions(harness: &mut TestHarness) -> Vec<Coordinates> {
+        harness
+            .world
+            .query::<(&TrafficComponent, &PositionComponent)>()
+            .iter(&harness.world)
+            .map(|(_, position)| position.position)
+            .collect()
+    }
+
+    #[test]
+    fn traffic_spawns_on_interval_up_to_population_cap() {
+        let mut harness = TestHarness::with_definitions(test_definitions_with_traffic(json!([{
+            "ships": ["shuttle"],
+            "faction": "merchants",
+            "spawn_interval_seconds": 1.0,
+            "maximum_population": 3,
+            "spawn_location": {"SystemEdge": {"distance": 3000.0}},
+            "lifetime_seconds": 600.0
+        }])));
+
+        harness.step_for(40);
+        assert!(traffic_positions(&mut harness).is_empty());
+
+        harness.step_for(20);
+        assert_eq!(traffic_positions(&mut harness).len(), 1);
+
+        harness.step_for(500);
+        let positions = traffic_positions(&mut harness);
+        assert_eq!(positions.len(), 3);
+
+        let factions: Vec<String> = harness
+            .world
+            .query_filtered::<&FactionComponent, With<TrafficComponent>>()
+            .iter(&harness.world)
+            .map(|faction| faction.faction.clone())
+            .collect();
+        assert_eq!(factions, vec!["merchants"; 3]);
+    }
+
+    #[test]
+    fn traffic_takes_off_from_planetoids() {
+        let mut harness = TestHarness::with_definitions(test_definitions_with_traffic(json!([{
+            "ships": ["shuttle"],
+            "spawn_interval_seconds": 0.5,
+            "maximum_population": 1,
+            "spawn_location": "Planetoids",
+            "lifetime_seconds": 600.0
+        }])));
+
+        harness.step_for(26);
+
+        let positions = traffic_positions(&mut harness);
+        assert_eq!(positions.len(), 1);
+        assert!(positions[0].distance_to(Coordinates::new(500.0, 0.0)) < 200.0);
+    }
+
+    #[test]
+    fn traffic_is_replaced_after_lifetime() {
+        let mut harness = TestHarness::with_definitions(test_definitions_with_traffic(json!([{
+            "ships": ["shuttle"],
+            "spawn_interval_seconds": 0.1,
+            "maximum_population": 1,
+            "spawn_location": {"SystemEdge": {"distance": 3000.0}},
+            "lifetime_seconds": 1.0
+        }])));
+
+        harness.step_for(10);
+        let first = harness
+            .world
+            .query::<(bevy_ecs::entity::Entity, &TrafficComponent)>()
+            .single(&harness.world)
+            .unwrap()
+            .0;
+
+        harness.step_for(100);
+        let second = harness
+            .world
+            .query::<(bevy_ecs::entity::Entity, &TrafficComponent)>()
+            .single(&harness.world)
+            .unwrap()
+            .0;
+
+        assert_ne!(first, second);
+    }
+}
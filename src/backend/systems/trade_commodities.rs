@@ -0,0 +1,246 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use bevy_ecs::system::{Query, Res};
+
+use crate::{
+    backend::{
+        components::session::player_session_component::PlayerSessionComponent,
+        data_objects::{ship_loadout::ShipLoadout, trade_request::TradeRequest},
+        resources::{
+            definitions_resource::DefinitionsResource,
+            profile_save_queue_resource::ProfileSaveQueueResource,
+        },
+        world_objects::components::market_component::MarketComponent,
+    },
+    connectivity::{
+        market_message_data::{CargoData, MarketData, MarketListingData},
+        server_client_message::ServerClientMessage,
+    },
+};
+
+pub fn trade_commodities(
+    mut sessions: Query<&mut PlayerSessionComponent>,
+    mut markets: Query<&mut MarketComponent>,
+    definitions: Res<DefinitionsResource>,
+    profile_saves: Res<ProfileSaveQueueResource>,
+) {
+    let commodities = definitions.definitions.get_commodities();
+
+    for mut session in sessions.iter_mut() {
+        let trade_requests = std::mem::take(&mut session.trade_requests);
+
+        if trade_requests.is_empty() && !session.market_outdated {
+            continue;
+        }
+
+        session.market_outdated = false;
+
+        // Trading only happens on the ground, anything requested in flight is dropped
+        let planetoid = match session.landed_on {
+            Some(has) => has,
+            None => continue,
+        };
+
+        let mut market = match markets.get_mut(planetoid) {
+            Ok(has) => has,
+            Err(_) => continue, // Not every planetoid has a market
+        };
+
+        let profile = match session.session.upgrade() {
+            Some(has) => has.player_profile.clone(),
+            None => continue,
+        };
+
+        let (holdings, cargo_capacity, traded_any) = profile.modify_holdings(|holdings| {
+            // Landed players have no ship, so the ship they will take off in decides how much they can carry
+            let cargo_capacity =
+                match ShipLoadout::from_holdings(&definitions.definitions, holdings) {
+                    Some(has) => has.cargo_capacity(),
+                    None => 0,
+                };
+
+            let mut traded_any = false;
+
+            for request in &trade_requests {
+                let traded = match request {
+                    TradeRequest::Buy {
+                        commodity,
+                        quantity,
+                    } => market.buy(holdings, commodity, *quantity, cargo_capacity),
+                    TradeRequest::Sell {
+                        commodity,
+                        quantity,
+                    } => market.sell(holdings, commodity, *quantity),
+                };
+
+                match traded {
+                    Ok(()) => traded_any = true,
+                    Err(()) => {
+                        tracing::debug!("Rejected trade {:?} on planetoid {:?}", request, planetoid)
+                    }
+                }
+            }
+
+            (holdings.clone(), cargo_capacity, traded_any)
+        });
+
+        // Cargo space decides which missions are offered
+        if traded_any {
+            profile_saves.save(&profile);
+            session.missions_outdated = true;
+        }
+
+        let commodity_name = |commodity: &str| match commodities.get_commodity_record(commodity) {
+            Some(has) => has.commodity_display_name.clone(),
+            None => commodity.to_string(), // Definitions no longer have cargo loaded from an old profile
+        };
+
+        let _ = session
+            .command_queue_outbound
+            .send(ServerClientMessage::Market(MarketData {
+                planetoid_id: planetoid.to_bits(),
+                credits: holdings.credits,
+                cargo_capacity,
+                listings: market
+                    .listings
+                    .iter()
+                    .map(|listing| MarketListingData {
+                        commodity_reference: listing.commodity.clone(),
+                        commodity_name: commodity_name(&listing.commodity),
+                        buy_price: listing.buy_price,
+                        sell_price: listing.sell_price,
+                        stock: listing.stock,
+                    })
+                    .collect(),
+                cargo: holdings
+                    .cargo
+                    .iter()
+                    .map(|(commodity, quantity)| CargoData {
+                        commodity_reference: commodity.clone(),
+                        commodity_name: commodity_name(commodity),
+                        quantity: *quantity,
+                    })
+                    .collect(),
+            })); // Nothing we can do about send errors for users disconnected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backend::test_harness::TestHarness,
+        connectivity::{
+            client_server_message::ClientServerMessage, market_message_data::MarketData,
+            player_info::player_profile::STARTING_CREDITS,
+            server_client_message::ServerClientMessage,
+        },
+    };
+
+    fn markets(messages: Vec<ServerClientMessage>) -> Vec<MarketData> {
+        messages
+            .into_iter()
+            .filter_map(|message| match message {
+                ServerClientMessage::Market(market) => Some(market),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn landing_sends_the_market_and_trades_move_credits_cargo_and_stock() {
+        let mut harness = TestHarness::new();
+        let session = harness.connect_player();
+        harness.land_player(&session, "earth");
+
+        let sent = markets(session.received());
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].credits, STARTING_CREDITS);
+        assert_eq!(sent[0].listings[0].stock, 50);
+        assert!(sent[0].cargo.is_empty());
+
+        session.send(ClientServerMessage::BuyCommodity {
+            commodity: "food".to_string(),
+            quantity: 15,
+        });
+        harness.step();
+        session.send(ClientServerMessage::SellCommodity {
+            commodity: "food".to_string(),
+            quantity: 5,
+        });
+        harness.step();
+
+        let sent = markets(session.received());
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[1].credits, STARTING_CREDITS - 15 * 100 + 5 * 80);
+        assert_eq!(sent[1].listings[0].stock, 40);
+        assert_eq!(sent[1].cargo[0].commodity_name, "Food");
+        assert_eq!(sent[1].cargo[0].quantity, 10);
+    }
+
+    #[test]
+    fn trades_beyond_stock_cargo_space_or_holdings_are_rejected() {
+        let mut harness = TestHarness::new();
+        let session = harness.connect_player();
+        harness.land_player(&session, "earth");
+        session.received();
+
+        // Only 5 tons of metal are in stock, and the shuttle only carries 20 tons
+        for (commodity, quantity) in [("metal", 6), ("food", 21)] {
+            session.send(ClientServerMessage::BuyCommodity {
+                commodity: commodity.to_string(),
+                quantity,
+            });
+            harness.step();
+        }
+
+        session.send(ClientServerMessage::SellCommodity {
+            commodity: "food".to_string(),
+            quantity: 1,
+        });
+        harness.step();
+
+        let sent = markets(session.received());
+        assert_eq!(sent.len(), 3);
+        assert!(sent.iter().all(|market| market.credits == STARTING_CREDITS));
+        assert!(sent.iter().all(|market| market.cargo.is_empty()));
+    }
+
+    #[test]
+    fn completed_trades_save_the_profile_and_rejected_ones_do_not() {
+        let mut harness = TestHarness::new();
+        let session = harness.connect_registered_player("trader");
+        harness.land_player(&session, "earth");
+
+        session.send(ClientServerMessage::BuyCommodity {
+            commodity: "metal".to_string(),
+            quantity: 6,
+        });
+        harness.step();
+        assert!(harness.saved_profiles().is_empty());
+
+        session.send(ClientServerMessage::BuyCommodity {
+            commodity: "food".to_string(),
+            quantity: 5,
+        });
+        harness.step();
+
+        let saved = harness.saved_profiles();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].holdings.credits, STARTING_CREDITS - 5 * 100);
+    }
+}
@@ -0,0 +1,142 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use bevy_ecs::system::{Query, Res};
+
+use crate::{
+    backend::{
+        resources::delta_t_resource::DeltaTResource,
+        world_objects::components::{
+            collision_component::{CollidableComponent, CollisionMarker},
+            position_component::PositionComponent,
+            swept_collision_component::SweptCollisionComponent,
+            velocity_component::VelocityComponent,
+        },
+    },
+    shared_types::{GlobalCoordinateType, WorldCoordinates},
+};
+
+// Replaces the collision shapes of swept entities with the path they moved along this tick, worked back from their velocity
+// rather than their last position so jumps and other teleports don't sweep across everything in between
+pub fn update_swept_collisions<T: Send + Sync>(
+    mut receivers: Query<(
+        &mut CollidableComponent<T>,
+        &SweptCollisionComponent<T>,
+        &PositionComponent,
+        &VelocityComponent,
+    )>,
+    mut senders: Query<(
+        &mut CollisionMarker<T>,
+        &SweptCollisionComponent<T>,
+        &PositionComponent,
+        &VelocityComponent,
+    )>,
+    time: Res<DeltaTResource>,
+) {
+    let delta_t = time.get_last_tick_duration().as_secs_f32();
+    let moved = |velocity: &VelocityComponent| {
+        (velocity.velocity * delta_t)
+            .cast::<GlobalCoordinateType>()
+            .cast_unit::<WorldCoordinates>()
+    };
+
+    receivers
+        .par_iter_mut()
+        .for_each(|(mut collider, swept, position, velocity)| {
+            collider.shape = swept
+                .shape
+                .swept(position.position - moved(velocity), position.position);
+        });
+    senders
+        .par_iter_mut()
+        .for_each(|(mut collider, swept, position, velocity)| {
+            collider.shape = swept
+                .shape
+                .swept(position.position - moved(velocity), position.position);
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::{entity::Entity, event::Events};
+
+    use crate::{
+        backend::{
+            events::collision_layer_events::CollisionStarted,
+            shape::{CircleData, Shape},
+            test_harness::TestHarness,
+            world_objects::components::{
+                collision_component::CollisionMarker, position_component::PositionComponent,
+                projectile_component::Damaging, swept_collision_component::SweptCollisionComponent,
+                velocity_component::VelocityComponent,
+            },
+        },
+        shared_types::{Coordinates, Radius, Velocity},
+    };
+
+    fn spawn_bullet(harness: &mut TestHarness, position: Coordinates, swept: bool) -> Entity {
+        let shape = Shape::Circle(CircleData {
+            location: position,
+            radius: Radius::new(1.0),
+        });
+        let mut spawned = harness.world.spawn((
+            CollisionMarker::<Damaging>::new(shape),
+            PositionComponent { position },
+            VelocityComponent {
+                velocity: Velocity::new(20000.0, 0.0),
+            },
+        ));
+
+        if swept {
+            spawned.insert(SweptCollisionComponent::<Damaging>::new(shape));
+        }
+
+        spawned.id()
+    }
+
+    #[test]
+    fn only_swept_objects_hit_what_they_pass_between_ticks() {
+        let mut harness = TestHarness::new();
+        let swept_target = harness.spawn_ship(
+            "shuttle",
+            Coordinates::new(100.0, 0.0),
+            Velocity::zero(),
+            None,
+        );
+        let plain_target = harness.spawn_ship(
+            "shuttle",
+            Coordinates::new(100.0, 500.0),
+            Velocity::zero(),
+            None,
+        );
+        let swept_bullet = spawn_bullet(&mut harness, Coordinates::new(-200.0, 0.0), true);
+        let plain_bullet = spawn_bullet(&mut harness, Coordinates::new(-200.0, 500.0), false);
+
+        // Each bullet ends the tick well beyond its target
+        harness.step();
+
+        let hits: Vec<(Entity, Entity)> = harness
+            .world
+            .resource::<Events<CollisionStarted<Damaging>>>()
+            .iter_current_update_events()
+            .map(|hit| (hit.receiver, hit.sender))
+            .collect();
+
+        assert!(hits.contains(&(swept_target, swept_bullet)));
+        assert!(!hits.contains(&(plain_target, plain_bullet)));
+    }
+}
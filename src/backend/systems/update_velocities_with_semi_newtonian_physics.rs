@@ -140,6 +140,16 @@ pub fn update_velocities_with_semi_newtonian_physics(
                 new_velocity
             };
 
+            // Braking takes speed off directly, stopping short of reversing the ship
+            let new_velocity = if semi_newtonian_physics.braking.get() > 0.0 {
+                new_velocity.with_max_length(
+                    (new_velocity.length() - semi_newtonian_physics.braking.get() * delta_t)
+                        .max(0.0),
+                )
+            } else {
+                new_velocity
+            };
+
             // Apply general drag to velocity if we are below the minumum stopping speed
             let new_velocity = if new_velocity.length()
                 <= (semi_newtonian_physics.maximum_speed.get() / MIN_DRAG_SPEED_PERCENT)
@@ -0,0 +1,398 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+use bevy_ecs::{
+    entity::Entity,
+    hierarchy::ChildOf,
+    query::Without,
+    system::{Query, Res},
+    world::World,
+};
+
+use crate::{
+    AssetIndexResource,
+    backend::{
+        components::session::player_session_component::PlayerSessionComponent,
+        data_objects::ship_loadout::ShipLoadout,
+        resources::{
+            definitions_resource::DefinitionsResource,
+            saved_player_ships_resource::SavedPlayerShipsResource,
+        },
+        world_objects::{
+            components::{
+                angular_velocity_component::AngularVelocityComponent,
+                hyperspace_jump_component::HyperspaceJumpComponent,
+                planetoid_component::PlanetoidComponent,
+                position_component::PositionComponent, rotation_component::RotationComponent,
+                semi_newtonian_physics_component::SemiNewtonianPhysicsComponent,
+                ship_type_component::ShipTypeComponent, star_system_component::StarSystemComponent,
+                timeout_component::TimeoutComponent, traffic_component::TrafficComponent,
+                velocity_component::VelocityComponent,
+            },
+            ship::ShipBundle,
+        },
+        world_save_file::{SavedShipRecord, WorldSaveFile},
+    },
+    connectivity::server_client_message::ServerClientMessage,
+    shared_types::{AngularVelocity, Coordinates, Rotation, Speed, Velocity},
+};
+
+// Spawns saved ships nobody owns straight into the world, and holds on to player ships until their owner returns
+pub fn restore_world_save(world: &mut World, save: WorldSaveFile) {
+    let mut saved_player_ships = SavedPlayerShipsResource::default();
+    let mut restored_count: usize = 0;
+
+    for saved_ship in save.ships {
+        match saved_ship.owner.clone() {
+            Some(owner) => {
+                saved_player_ships.ships.insert(owner, saved_ship);
+            }
+            None => {
+                let definitions = world.resource::<DefinitionsResource>().definitions.clone();
+                let asset_index = world.resource::<AssetIndexResource>().asset_index.clone();
+
+                let ship_record = match definitions
+                    .get_ships()
+                    .get_ship_record(&saved_ship.ship_reference)
+                {
+                    Some(has) => has,
+                    None => {
+                        tracing::warn!(
+                            "Saved ship {} no longer has a definition and was not restored",
+                            saved_ship.ship_reference
+                        );
+                        continue;
+                    }
+                };
+
+                let new_ship = match ShipBundle::new(
+                    ship_record,
+                    Coordinates::new(saved_ship.position_x, saved_ship.position_y),
+                    Some(Velocity::new(saved_ship.velocity_x, saved_ship.velocity_y)),
+                    Some(Rotation::radians(saved_ship.rotation)),
+                    Some(AngularVelocity::radians(saved_ship.angular_velocity)),
+                    &asset_index,
+                ) {
+                    Ok(created) => created,
+                    Err(()) => continue,
+                };
+
+                let mut spawned = world.spawn((
+                    new_ship,
+                    SemiNewtonianPhysicsComponent::new(Speed::new(ship_record.maximum_speed)),
+                ));
+
+                if let Some(star_system) = saved_ship
+                    .star_system
+                    .as_ref()
+                    .and_then(|x| definitions.get_star_systems().get_star_system_id(x))
+                {
+                    spawned.insert(StarSystemComponent { star_system });
+                }
+
+                restored_count += 1;
+            }
+        }
+    }
+
+    tracing::info!(
+        "Restored {} saved ships and {} saved player ships",
+        restored_count,
+        saved_player_ships.ships.len()
+    );
+
+    world.insert_resource(saved_player_ships);
+}
+
+// Temporary ships, such as ones which time out on their own, are not worth saving
+// Traffic is repopulated by its spawners on startup, so saving it as well would pile ships up with every restart
+type Unsaved = (Without<TimeoutComponent>, Without<TrafficComponent>);
+
+pub fn collect_world_save(
+    ships: Query<
+        (
+            Entity,
+            &ShipTypeComponent,
+            &PositionComponent,
+            Option<&ChildOf>,
+        ),
+        Unsaved,
+    >,
+    motion: Query<(
+        &VelocityComponent,
+        &RotationComponent,
+        &AngularVelocityComponent,
+    )>,
+    locations: Query<(Option<&StarSystemComponent>, Option<&HyperspaceJumpComponent>)>,
+    planetoids: Query<(&PlanetoidComponent, &PositionComponent)>,
+    sessions: Query<&PlayerSessionComponent>,
+    definitions: Res<DefinitionsResource>,
+    saved_player_ships: Res<SavedPlayerShipsResource>,
+) -> WorldSaveFile {
+    let star_system_cache = definitions.definitions.get_star_systems();
+    let mut save = WorldSaveFile::default();
+
+    for (entity, ship_type, position, parent) in ships.iter() {
+        let owner = match parent {
+            Some(parent) => {
+                let profile = match sessions
+                    .get(parent.parent())
+                    .ok()
+                    .and_then(|session| session.session.upgrade())
+                {
+                    Some(has) => has.player_profile.clone(),
+                    None => continue,
+                };
+
+                // Players without a stored profile can never log back in to claim their ship
+                if !profile.is_persistent() {
+                    continue;
+                }
+
+                Some(profile.get_credentials().get_identifier().to_string())
+            }
+            None => None,
+        };
+
+        let (velocity, rotation, angular_velocity) = match motion.get(entity) {
+            Ok((velocity, rotation, angular_velocity)) => (
+                velocity.velocity,
+                rotation.rotation,
+                angular_velocity.angular_velocity,
+            ),
+            Err(_) => continue,
+        };
+
+        // Ships mid-jump are saved as having arrived, at rest, rather than flying off at hyperspace speed
+        let (star_system, velocity) = match locations.get(entity) {
+            Ok((_, Some(jump))) => (Some(jump.destination), Velocity::zero()),
+            Ok((star_system, None)) => (star_system.map(|x| x.star_system), velocity),
+            Err(_) => continue,
+        };
+
+        save.ships.push(SavedShipRecord {
+            ship_reference: ship_type.ship_reference.clone(),
+            owner,
+            star_system: star_system
+                .and_then(|x| star_system_cache.get_star_system_record(x))
+                .map(|x| x.star_system_reference.clone()),
+            position_x: position.position.x,
+            position_y: position.position.y,
+            velocity_x: velocity.x,
+            velocity_y: velocity.y,
+            rotation: rotation.radians,
+            angular_velocity: angular_velocity.radians,
+            landed_on: None,
+        });
+    }
+
+    // Landed players have no ship in the world, so the ship they will take off in is saved resting on the planetoid
+    for session in sessions.iter() {
+        let (planetoid, position) = match session
+            .landed_on
+            .and_then(|landed_on| planetoids.get(landed_on).ok())
+        {
+            Some(has) => has,
+            None => continue,
+        };
+
+        let profile = match session.session.upgrade() {
+            Some(has) => has.player_profile.clone(),
+            None => continue,
+        };
+
+        if !profile.is_persistent() {
+            continue;
+        }
+
+        let holdings = profile.get_holdings();
+        let loadout = match ShipLoadout::from_holdings(&definitions.definitions, &holdings) {
+            Some(has) => has,
+            None => continue,
+        };
+
+        let star_system = match session.landed_on.map(|landed_on| locations.get(landed_on)) {
+            Some(Ok((star_system, _))) => star_system.map(|x| x.star_system),
+            _ => None,
+        };
+
+        save.ships.push(SavedShipRecord {
+            ship_reference: loadout.ship.ship_reference.clone(),
+            owner: Some(profile.get_credentials().get_identifier().to_string()),
+            star_system: star_system
+                .and_then(|x| star_system_cache.get_star_system_record(x))
+                .map(|x| x.star_system_reference.clone()),
+            position_x: position.position.x,
+            position_y: position.position.y,
+            velocity_x: 0.0,
+            velocity_y: 0.0,
+            rotation: 0.0,
+            angular_velocity: 0.0,
+            landed_on: Some(planetoid.planetoid_reference.clone()),
+        });
+    }
+
+    // Player ships whose owner never came back this run are kept for the next one
+    save.ships
+        .extend(saved_player_ships.ships.values().cloned());
+
+    save
+}
+
+pub fn notify_server_shutdown(sessions: Query<&PlayerSessionComponent>) {
+    for session in sessions.iter() {
+        let _ = session
+            .command_queue_outbound
+            .send(ServerClientMessage::ServerShutdown); // Nothing we can do about send errors for users disconnected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::system::RunSystemOnce;
+
+    use crate::{
+        backend::{
+            components::session::player_session_component::PlayerSessionComponent,
+            configuration_file_loaders::definition_caches::star_system_definition_cache::StarSystemId,
+            resources::definitions_resource::DefinitionsResource,
+            test_harness::TestHarness,
+            world_objects::components::{
+                position_component::PositionComponent, ship_type_component::ShipTypeComponent,
+                star_system_component::StarSystemComponent, traffic_component::TrafficComponent,
+                velocity_component::VelocityComponent,
+            },
+            world_save_file::WorldSaveFile,
+        },
+        connectivity::server_client_message::ServerClientMessage,
+        shared_types::{Coordinates, Velocity},
+    };
+
+    use super::{collect_world_save, restore_world_save};
+
+    fn sol(harness: &TestHarness) -> StarSystemId {
+        harness
+            .world
+            .resource::<DefinitionsResource>()
+            .definitions
+            .get_star_systems()
+            .get_star_system_id("sol")
+            .unwrap()
+    }
+
+    #[test]
+    fn saved_ships_are_restored_where_they_were() {
+        let mut harness = TestHarness::new();
+        harness.spawn_ship(
+            "shuttle",
+            Coordinates::new(100.0, -50.0),
+            Velocity::new(3.0, 4.0),
+            Some("sol"),
+        );
+
+        let save = harness.world.run_system_once(collect_world_save).unwrap();
+        let save_path = std::env::temp_dir().join(format!("{}.json", uuid::Uuid::new_v4()));
+        save.save(&save_path).unwrap();
+        let loaded = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(WorldSaveFile::load(&save_path))
+            .unwrap()
+            .unwrap();
+        let _ = std::fs::remove_file(&save_path);
+
+        let mut restarted = TestHarness::new();
+        restore_world_save(&mut restarted.world, loaded);
+
+        let sol = sol(&harness);
+        let restored: Vec<_> = restarted
+            .world
+            .query::<(
+                &ShipTypeComponent,
+                &PositionComponent,
+                &VelocityComponent,
+                &StarSystemComponent,
+            )>()
+            .iter(&restarted.world)
+            .map(|(ship_type, position, velocity, star_system)| {
+                (
+                    ship_type.ship_reference.clone(),
+                    position.position,
+                    velocity.velocity,
+                    star_system.star_system,
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            restored,
+            vec![(
+                "shuttle".to_string(),
+                Coordinates::new(100.0, -50.0),
+                Velocity::new(3.0, 4.0),
+                sol
+            )]
+        );
+    }
+
+    #[test]
+    fn traffic_is_left_for_its_spawners_to_repopulate() {
+        let mut harness = TestHarness::new();
+        let sol = sol(&harness);
+        let traffic = harness.spawn_ship(
+            "shuttle",
+            Coordinates::zero(),
+            Velocity::zero(),
+            Some("sol"),
+        );
+        harness.world.entity_mut(traffic).insert(TrafficComponent {
+            star_system: sol,
+            traffic_index: 0,
+        });
+
+        let save = harness.world.run_system_once(collect_world_save).unwrap();
+        assert!(save.ships.is_empty());
+    }
+
+    #[test]
+    fn players_saved_while_landed_come_back_landed() {
+        let mut harness = TestHarness::new();
+        let session = harness.connect_registered_player("lander");
+        harness.land_player(&session, "earth");
+
+        let save = harness.world.run_system_once(collect_world_save).unwrap();
+        assert_eq!(save.ships.len(), 1);
+        assert_eq!(save.ships[0].landed_on.as_deref(), Some("earth"));
+
+        let mut restarted = TestHarness::new();
+        restore_world_save(&mut restarted.world, save);
+        let session = restarted.connect_registered_player("lander");
+        restarted.step();
+
+        let earth = restarted.find_planetoid("earth").unwrap();
+        let player = restarted
+            .world
+            .get::<PlayerSessionComponent>(session.entity)
+            .unwrap();
+        assert_eq!(player.landed_on, Some(earth));
+        assert!(player.should_follow.is_none());
+        assert!(
+            session
+                .received()
+                .iter()
+                .any(|message| matches!(message, ServerClientMessage::Landed(_)))
+        );
+    }
+}
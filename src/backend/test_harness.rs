@@ -0,0 +1,574 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+use std::{sync::Arc, time::Duration};
+
+use bevy_ecs::{entity::Entity, schedule::Schedule, world::World};
+use serde_json::json;
+
+use crate::{
+    backend::{
+        components::session::player_session_component::PlayerSessionComponent,
+        configuration_file_loaders::{
+            definition_caches::list_required_assets::ListRequiredAssets,
+            definition_file_cache::{DefinitionFileCache, DefinitionRecords},
+        },
+        resources::{
+            definitions_resource::DefinitionsResource,
+            profile_save_queue_resource::ProfileSaveQueueResource,
+        },
+        world_builder::WorldBuilder,
+        world_objects::{
+            components::{
+                planetoid_component::PlanetoidComponent, position_component::PositionComponent,
+                semi_newtonian_physics_component::SemiNewtonianPhysicsComponent,
+                star_system_component::StarSystemComponent, velocity_component::VelocityComponent,
+            },
+            planetoid::PlanetoidBundle,
+            ship::ShipBundle,
+        },
+    },
+    configuration_file_structures::{
+        planetoid_configuration_file::PlanetoidRecord,
+        server_configuration_file::ServerConfigurationFile,
+    },
+    connectivity::{
+        asset_index::AssetIndex,
+        client_server_message::{ClientServerMessage, ControlInput},
+        outbound_queue::{OutboundAttachment, outbound_queue},
+        player_info::{
+            player_profile::{PlayerCredentials, PlayerProfile},
+            player_profile_store::StoredPlayerProfile,
+            player_session::PlayerSession,
+        },
+        server_client_message::ServerClientMessage,
+        services::ecs_communication_service::EcsCommunicationService,
+    },
+    shared_types::{Coordinates, Speed, Velocity},
+};
+
+// Every tick advances the simulation by exactly this much, no matter how long the tick took to run
+pub const TEST_TICK_DURATION: Duration = Duration::from_millis(20);
+
+// A small universe with one landable planetoid with a market, shipyard and outfitter in the starting star system, and a second star system to jump to with a planetoid to deliver missions to
+pub fn test_definitions() -> DefinitionFileCache {
+    test_definitions_with_traffic(json!([]))
+}
+
+// The same universe as test_definitions, with the given traffic records flowing through the starting star system
+pub fn test_definitions_with_traffic(traffic: serde_json::Value) -> DefinitionFileCache {
+    let weapons = vec![
+        serde_json::from_value(json!({
+            "weapon_reference": "laser",
+            "projectile_asset": "bolt_image",
+            "projectile_display_radius": 5,
+            "projectile_collision_radius": 3,
+            "projectile_speed": 600,
+            "projectile_lifetime": 1.5,
+            "reload_time": 0.25,
+            "shield_damage": 10,
+            "hull_damage": 10
+        }))
+        .unwrap(),
+        serde_json::from_value(json!({
+            "weapon_reference": "railgun",
+            "projectile_asset": "bolt_image",
+            "projectile_display_radius": 5,
+            "projectile_collision_radius": 1,
+            "projectile_speed": 1000,
+            "projectile_lifetime": 1,
+            "reload_time": 1,
+            "shield_damage": 30,
+            "hull_damage": 30
+        }))
+        .unwrap(),
+        serde_json::from_value(json!({
+            "weapon_reference": "laser_turret",
+            "projectile_asset": "bolt_image",
+            "projectile_display_radius": 5,
+            "projectile_collision_radius": 3,
+            "projectile_speed": 600,
+            "projectile_lifetime": 1.5,
+            "reload_time": 0.25,
+            "shield_damage": 10,
+            "hull_damage": 10,
+            "turret": true
+        }))
+        .unwrap(),
+    ];
+
+    let ships = vec![
+        serde_json::from_value(json!({
+            "ship_reference": "shuttle",
+            "ship_display_name": "Shuttle",
+            "display_asset": "ship_image",
+            "display_radius": 25,
+            "maximum_speed": 200,
+            "thrust": 40,
+            "turn_rate": 1.57,
+            "collision_shape": {"Circle": {"radius": 12}},
+            "hull": 100,
+            "shield": 50,
+            "shield_regeneration": 5,
+            "weapons": ["laser"],
+            "cargo_capacity": 20,
+            "price": 4000,
+            "outfit_space": 10,
+            "weapon_slots": 1,
+            "player_starting_ship": true
+        }))
+        .unwrap(),
+        serde_json::from_value(json!({
+            "ship_reference": "courier",
+            "ship_display_name": "Courier",
+            "display_asset": "ship_image",
+            "display_radius": 30,
+            "maximum_speed": 180,
+            "thrust": 30,
+            "turn_rate": 1.2,
+            "collision_shape": {"Circle": {"radius": 15}},
+            "hull": 150,
+            "shield": 50,
+            "weapons": ["laser"],
+            "cargo_capacity": 50,
+            "price": 8000,
+            "outfit_space": 20,
+            "weapon_slots": 1
+        }))
+        .unwrap(),
+    ];
+
+    let outfits = vec![
+        serde_json::from_value(json!({
+            "outfit_reference": "cargo_pod",
+            "outfit_display_name": "Cargo Pod",
+            "price": 500,
+            "mass": 5,
+            "cargo_capacity": 10
+        }))
+        .unwrap(),
+        serde_json::from_value(json!({
+            "outfit_reference": "railgun_turret",
+            "outfit_display_name": "Railgun Turret",
+            "price": 2000,
+            "mass": 4,
+            "weapon": "railgun"
+        }))
+        .unwrap(),
+    ];
+
+    let commodities = vec![
+        serde_json::from_value(json!({
+            "commodity_reference": "food",
+            "commodity_display_name": "Food"
+        }))
+        .unwrap(),
+        serde_json::from_value(json!({
+            "commodity_reference": "metal",
+            "commodity_display_name": "Metal"
+        }))
+        .unwrap(),
+    ];
+
+    let mut earth = test_planetoid("earth", Coordinates::new(500.0, 0.0));
+    earth.may_be_landed_on.as_mut().unwrap().features = Some(
+        serde_json::from_value(json!({
+            "market": [
+                {"commodity": "food", "buy_price": 100, "sell_price": 80, "supply": 50},
+                {"commodity": "metal", "buy_price": 300, "sell_price": 250, "supply": 5}
+            ],
+            "shipyard": ["shuttle", "courier"],
+            "outfitter": ["cargo_pod", "railgun_turret"]
+        }))
+        .unwrap(),
+    );
+
+    let mut mars = test_planetoid("mars", Coordinates::new(300.0, 300.0));
+    mars.faction = Some("martians".to_string());
+
+    let planetoids = vec![earth, mars];
+
+    let factions = vec![
+        serde_json::from_value(json!({
+            "faction_reference": "merchants",
+            "faction_display_name": "Merchants Guild"
+        }))
+        .unwrap(),
+        serde_json::from_value(json!({
+            "faction_reference": "martians",
+            "faction_display_name": "Martian Republic",
+            "minimum_landing_reputation": 0,
+            "ally_reputation": 10,
+            "ship_destroyed_reputation": -10,
+            "mission_completed_reputation": 5
+        }))
+        .unwrap(),
+    ];
+
+    let missions = vec![
+        serde_json::from_value(json!({
+            "mission_reference": "earth_to_mars",
+            "mission_display_name": "Food for Mars",
+            "description": "Deliver food to Mars",
+            "origin": "earth",
+            "destination": "mars",
+            "cargo": {"commodity": "food", "quantity": 10},
+            "reward": 3000,
+            "faction": "martians",
+            "deadline_seconds": 600
+        }))
+        .unwrap(),
+        serde_json::from_value(json!({
+            "mission_reference": "mars_follow_up",
+            "mission_display_name": "More Food for Mars",
+            "description": "Deliver even more food to Mars",
+            "origin": "earth",
+            "destination": "mars",
+            "reward": 1000,
+            "offer_conditions": {"completed_missions": ["earth_to_mars"]}
+        }))
+        .unwrap(),
+    ];
+
+    let star_systems = vec![
+        serde_json::from_value(json!({
+            "star_system_reference": "sol",
+            "star_system_display_name": "Sol",
+            "map_x": 0,
+            "map_y": 0,
+            "planetoids": ["earth"],
+            "hyperlanes": ["alpha"],
+            "player_starting_system": true,
+            "traffic": traffic
+        }))
+        .unwrap(),
+        serde_json::from_value(json!({
+            "star_system_reference": "alpha",
+            "star_system_display_name": "Alpha Centauri",
+            "map_x": 100,
+            "map_y": 50,
+            "planetoids": ["mars"]
+        }))
+        .unwrap(),
+    ];
+
+    DefinitionFileCache::from_records(DefinitionRecords {
+        commodities,
+        factions,
+        missions,
+        outfits,
+        planetoids,
+        ships,
+        star_systems,
+        weapons,
+    })
+    .unwrap()
+}
+
+pub fn test_planetoid(planetoid_reference: &str, position: Coordinates) -> PlanetoidRecord {
+    serde_json::from_value(json!({
+        "planetoid_reference": planetoid_reference,
+        "planetoid_display_name": planetoid_reference,
+        "display_asset": "planet_image",
+        "display_radius": 200,
+        "x": position.x,
+        "y": position.y,
+        "may_be_landed_on": {
+            "landing_radius": 250,
+            "backdrop_image_asset": "backdrop_image",
+            "text_description_asset": "earth_description",
+            "features": {}
+        }
+    }))
+    .unwrap()
+}
+
+// Stands in for a connected client, keeping the session alive so it is not cleaned up out from under the test
+pub struct TestSession {
+    pub entity: Entity,
+    session: Arc<PlayerSession>,
+    attachment: Option<OutboundAttachment>, // Stands in for the websocket forwarding messages, without which no snapshots are queued
+}
+
+impl TestSession {
+    // The websocket connects after logging in, once the simulation may already have sent to the session
+    pub fn attach(&mut self) {
+        self.attachment = Some(self.session.clone_outbound_receiver().attach());
+    }
+
+    pub fn profile(&self) -> Arc<PlayerProfile> {
+        self.session.player_profile.clone()
+    }
+
+    pub fn send(&self, message: ClientServerMessage) {
+        self.session
+            .clone_inbound_sender()
+            .send_blocking(message)
+            .unwrap();
+    }
+
+    // Everything the server has sent since this was last called
+    pub fn received(&self) -> Vec<ServerClientMessage> {
+        let receiver = self.session.clone_outbound_receiver();
+        let mut messages = Vec::new();
+
+        while let Ok(queued) = receiver.try_recv() {
+            messages.push(queued.message);
+        }
+
+        messages
+    }
+}
+
+// Runs the real server schedule without any networking, one deterministic tick at a time
+pub struct TestHarness {
+    pub world: World,
+    schedule: Schedule,
+    _command_service: EcsCommunicationService,
+    profile_saves: async_channel::Receiver<StoredPlayerProfile>,
+}
+
+impl TestHarness {
+    pub fn new() -> Self {
+        Self::with_definitions(test_definitions())
+    }
+
+    pub fn with_definitions(definitions: DefinitionFileCache) -> Self {
+        let asset_index = Arc::new(AssetIndex::new(
+            definitions
+                .get_required_asset_list()
+                .iter()
+                .map(|asset| asset.0)
+                .cloned(),
+        ));
+
+        let (command_service, command_queue) = EcsCommunicationService::create();
+        let (profile_save_sender, profile_saves) = async_channel::unbounded();
+
+        let (world, schedule) =
+            WorldBuilder::new(Arc::new(definitions), asset_index, command_queue)
+                .with_tick_durations(TEST_TICK_DURATION, TEST_TICK_DURATION)
+                .with_profile_save_queue(ProfileSaveQueueResource::new(profile_save_sender))
+                .build();
+
+        Self {
+            world,
+            schedule,
+            _command_service: command_service,
+            profile_saves,
+        }
+    }
+
+    pub fn step(&mut self) {
+        self.schedule.run(&mut self.world);
+    }
+
+    pub fn step_for(&mut self, ticks: usize) {
+        for _ in 0..ticks {
+            self.step();
+        }
+    }
+
+    fn asset_index(&self) -> Arc<AssetIndex> {
+        self.world
+            .resource::<crate::AssetIndexResource>()
+            .asset_index
+            .clone()
+    }
+
+    pub fn spawn_ship(
+        &mut self,
+        ship_reference: &str,
+        position: Coordinates,
+        velocity: Velocity,
+        star_system: Option<&str>,
+    ) -> Entity {
+        let definitions = self
+            .world
+            .resource::<DefinitionsResource>()
+            .definitions
+            .clone();
+        let ship_record = definitions
+            .get_ships()
+            .get_ship_record(ship_reference)
+            .unwrap();
+
+        let ship = ShipBundle::new(
+            ship_record,
+            position,
+            Some(velocity),
+            None,
+            None,
+            &self.asset_index(),
+        )
+        .unwrap();
+
+        let mut spawned = self.world.spawn((
+            ship,
+            SemiNewtonianPhysicsComponent::new(Speed::new(ship_record.maximum_speed)),
+        ));
+
+        if let Some(star_system) = star_system {
+            spawned.insert(StarSystemComponent {
+                star_system: definitions
+                    .get_star_systems()
+                    .get_star_system_id(star_system)
+                    .unwrap(),
+            });
+        }
+
+        spawned.id()
+    }
+
+    // Planetoids from the definitions are already spawned by the world builder, this is for any extra ones
+    pub fn spawn_planetoid(
+        &mut self,
+        record: &PlanetoidRecord,
+        star_system: Option<&str>,
+    ) -> Entity {
+        let definitions = self
+            .world
+            .resource::<DefinitionsResource>()
+            .definitions
+            .clone();
+        let planetoid = PlanetoidBundle::new(record, &self.asset_index()).unwrap();
+        let mut spawned = self.world.spawn(planetoid);
+
+        if let Some(physical) = PlanetoidBundle::physical_collision_marker(record) {
+            spawned.insert(physical);
+        }
+
+        if let Some(star_system) = star_system {
+            spawned.insert(StarSystemComponent {
+                star_system: definitions
+                    .get_star_systems()
+                    .get_star_system_id(star_system)
+                    .unwrap(),
+            });
+        }
+
+        spawned.id()
+    }
+
+    pub fn find_planetoid(&mut self, planetoid_reference: &str) -> Option<Entity> {
+        self.world
+            .query::<(Entity, &PlanetoidComponent)>()
+            .iter(&self.world)
+            .find(|(_, planetoid)| planetoid.planetoid_reference == planetoid_reference)
+            .map(|(entity, _)| entity)
+    }
+
+    // The ship the player is currently flying, once it has been spawned
+    pub fn player_ship(&self, session: &TestSession) -> Entity {
+        self.world
+            .get::<PlayerSessionComponent>(session.entity)
+            .unwrap()
+            .should_follow
+            .unwrap()
+    }
+
+    // Moves the player's ship to a stop over the given planetoid and lands on it
+    pub fn land_player(&mut self, session: &TestSession, planetoid_reference: &str) {
+        self.step_for(3);
+
+        let planetoid = self.find_planetoid(planetoid_reference).unwrap();
+        let position = self.world.get::<PositionComponent>(planetoid).unwrap().position;
+        let star_system = *self.world.get::<StarSystemComponent>(planetoid).unwrap();
+
+        let ship = self.player_ship(session);
+        self.world.get_mut::<PositionComponent>(ship).unwrap().position = position;
+        self.world.get_mut::<VelocityComponent>(ship).unwrap().velocity = Velocity::zero();
+        self.world.entity_mut(ship).insert(star_system);
+
+        session.send(ClientServerMessage::ControlInput {
+            input: ControlInput::Land,
+            pressed: true,
+        });
+        self.step_for(2);
+
+        // Released again so taking off doesn't immediately land the player right back down
+        session.send(ClientServerMessage::ControlInput {
+            input: ControlInput::Land,
+            pressed: false,
+        });
+        self.step();
+    }
+
+    // Connects a player the same way logging in does, minus the websocket
+    pub fn connect_player(&mut self) -> TestSession {
+        let mut session = self.log_in_player();
+        session.attach();
+        session
+    }
+
+    // Logged in, but the websocket has yet to connect
+    pub fn log_in_player(&mut self) -> TestSession {
+        self.log_in_player_with(PlayerCredentials::BasicToken {
+            token: uuid::Uuid::new_v4().to_string(),
+        })
+    }
+
+    // A player with a username, whose profile is persistent and so gets saved
+    pub fn connect_registered_player(&mut self, username: &str) -> TestSession {
+        let mut session = self.log_in_player_with(PlayerCredentials::UsernameAndPassword {
+            username: username.to_string(),
+            password_hash: String::new(),
+        });
+        session.attach();
+        session
+    }
+
+    fn log_in_player_with(&mut self, credentials: PlayerCredentials) -> TestSession {
+        let profile = Arc::new(PlayerProfile::new(credentials, Duration::from_secs(60)));
+
+        let (inbound_sender, inbound_receiver) = async_channel::unbounded::<ClientServerMessage>();
+        let (outbound_sender, outbound_receiver) =
+            outbound_queue(ServerConfigurationFile::default().maximum_queued_bytes);
+
+        let session = Arc::new(PlayerSession::new(
+            profile,
+            uuid::Uuid::new_v4().to_string(),
+            inbound_sender,
+            outbound_receiver,
+        ));
+
+        let entity = self
+            .world
+            .spawn(PlayerSessionComponent::new(
+                Arc::downgrade(&session),
+                inbound_receiver,
+                outbound_sender,
+            ))
+            .id();
+
+        TestSession {
+            entity,
+            session,
+            attachment: None,
+        }
+    }
+
+    // Every profile the simulation has queued for saving since this was last called
+    pub fn saved_profiles(&self) -> Vec<StoredPlayerProfile> {
+        let mut saved = Vec::new();
+
+        while let Ok(stored) = self.profile_saves.try_recv() {
+            saved.push(stored);
+        }
+
+        saved
+    }
+}
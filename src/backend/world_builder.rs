@@ -0,0 +1,371 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+use std::{sync::Arc, time::Duration};
+
+use bevy_ecs::{
+    event::Events,
+    schedule::{IntoScheduleConfigs, Schedule},
+    world::World,
+};
+
+use crate::{
+    AssetIndexResource,
+    backend::{
+        components::session::player_session_component::process_input_messages_system,
+        configuration_file_loaders::definition_file_cache::DefinitionFileCache,
+        events::{
+            collision_event::{CollisionEvent, clear_old_collision_events, send_collision_events},
+            collision_layer_events::{
+                CollisionEnded, CollisionOngoing, CollisionStarted,
+                clear_old_collision_layer_events,
+            },
+            ship_destroyed_event::{ShipDestroyedEvent, clear_old_ship_destroyed_events},
+        },
+        resources::{
+            definitions_resource::DefinitionsResource,
+            delta_t_resource::{DeltaTResource, increment_time},
+            profile_save_queue_resource::ProfileSaveQueueResource,
+            saved_player_ships_resource::SavedPlayerShipsResource,
+            traffic_spawner_resource::TrafficSpawnerResource,
+            viewport_settings_resource::ViewportSettingsResource,
+        },
+        spatial_optimizer::{
+            collision_optimizer::{CollisionOptimizer, collision_system},
+            hash_sized::HashSized,
+        },
+        systems::{
+            apply_collision_responses::apply_collision_responses,
+            apply_npc_control::apply_npc_control,
+            apply_player_control::apply_player_control,
+            apply_projectile_damage::apply_projectile_damage,
+            faction_reputation::apply_ship_destroyed_reputation,
+            fire_weapons::fire_weapons,
+            hyperspace_jump::{begin_hyperspace_jumps, progress_hyperspace_jumps},
+            outfit_player_ships::outfit_player_ships,
+            player_landing::{land_player_ships, take_off_player_ships},
+            player_missions::process_player_missions,
+            player_session_cleanup::player_session_cleanup,
+            player_spawn_system::spawn_player_ship_and_viewports,
+            player_targeting::{select_player_targets, send_target_info},
+            send_ship_status::send_ship_status,
+            spawn_traffic::{spawn_traffic_ships, tick_traffic_spawners},
+            submit_command::{EcsCommandQueue, process_external_commands},
+            trade_commodities::trade_commodities,
+            update_collisions_with_position::update_collisions_with_position,
+            update_collisions_with_rotation::update_collisions_with_rotation,
+            update_positions_with_velocity::update_positions_with_velocity,
+            update_rotations_with_angular_velocity::update_rotations_with_angular_velocity,
+            update_swept_collisions::update_swept_collisions,
+            update_velocities_with_semi_newtonian_physics::update_velocities_with_semi_newtonian_physics,
+            world_persistence::restore_world_save,
+        },
+        world_objects::{
+            components::{
+                energy_component::regenerate_energy, faction_component::FactionComponent,
+                health_component::regenerate_shields,
+                market_component::MarketComponent, physical_component::Physical,
+                planetoid_component::LandablePlanetoidComponent, projectile_component::Damaging,
+                semi_newtonian_physics_component::SemiNewtonianPhysicsComponent,
+                shipyard_component::ShipyardComponent, star_system_component::StarSystemComponent,
+                timeout_component::check_despawn_times,
+            },
+            planetoid::PlanetoidBundle,
+            server_viewport::{Displayable, tick_viewport, update_viewport_visibility},
+        },
+        world_save_file::WorldSaveFile,
+    },
+    configuration_file_structures::server_configuration_file::{
+        ServerConfigurationFile, ViewLayerRadii,
+    },
+    connectivity::asset_index::AssetIndex,
+    shared_types::Radius,
+};
+
+// Assembles the simulation world and the schedule which ticks it, so the server and tests run the exact same systems
+pub struct WorldBuilder {
+    definitions: Arc<DefinitionFileCache>,
+    asset_index: Arc<AssetIndex>,
+    command_queue: EcsCommandQueue,
+    minimum_tick_duration: Duration,
+    maximum_tick_duration: Duration,
+    viewport_radius: Radius,
+    view_layer_radii: ViewLayerRadii,
+    maximum_snapshot_objects: usize,
+    world_save: Option<WorldSaveFile>,
+    profile_save_queue: ProfileSaveQueueResource,
+}
+
+impl WorldBuilder {
+    pub fn new(
+        definitions: Arc<DefinitionFileCache>,
+        asset_index: Arc<AssetIndex>,
+        command_queue: EcsCommandQueue,
+    ) -> Self {
+        let defaults = ServerConfigurationFile::default();
+
+        Self {
+            definitions,
+            asset_index,
+            command_queue,
+            minimum_tick_duration: defaults.get_minimum_tick_duration(),
+            maximum_tick_duration: defaults.get_maximum_tick_duration(),
+            viewport_radius: Radius::new(defaults.viewport_radius),
+            view_layer_radii: defaults.view_layer_radii,
+            maximum_snapshot_objects: defaults.maximum_snapshot_objects,
+            world_save: None,
+            profile_save_queue: ProfileSaveQueueResource::default(),
+        }
+    }
+
+    // Setting both durations to the same value makes every tick advance time by exactly that much
+    pub fn with_tick_durations(mut self, minimum: Duration, maximum: Duration) -> Self {
+        self.minimum_tick_duration = minimum;
+        self.maximum_tick_duration = maximum;
+        self
+    }
+
+    pub fn with_viewport_radius(mut self, viewport_radius: Radius) -> Self {
+        self.viewport_radius = viewport_radius;
+        self
+    }
+
+    pub fn with_view_layer_radii(mut self, view_layer_radii: ViewLayerRadii) -> Self {
+        self.view_layer_radii = view_layer_radii;
+        self
+    }
+
+    pub fn with_maximum_snapshot_objects(mut self, maximum_snapshot_objects: usize) -> Self {
+        self.maximum_snapshot_objects = maximum_snapshot_objects;
+        self
+    }
+
+    pub fn with_world_save(mut self, world_save: Option<WorldSaveFile>) -> Self {
+        self.world_save = world_save;
+        self
+    }
+
+    pub fn with_profile_save_queue(mut self, profile_save_queue: ProfileSaveQueueResource) -> Self {
+        self.profile_save_queue = profile_save_queue;
+        self
+    }
+
+    pub fn build(self) -> (World, Schedule) {
+        let mut world = World::new();
+
+        for planetoid in self.definitions.get_planetoids() {
+            let mut spawned =
+                world.spawn(PlanetoidBundle::new(planetoid, &self.asset_index).unwrap());
+
+            if let Some(landable) = LandablePlanetoidComponent::new(planetoid, &self.asset_index) {
+                spawned.insert(landable.unwrap());
+            }
+
+            if let Some(physical) = PlanetoidBundle::physical_collision_marker(planetoid) {
+                spawned.insert(physical);
+            }
+
+            if let Some(market) = MarketComponent::new(planetoid) {
+                spawned.insert(market);
+            }
+
+            if let Some(shipyard) = ShipyardComponent::new(planetoid) {
+                spawned.insert(shipyard);
+            }
+
+            if let Some(faction) = &planetoid.faction {
+                spawned.insert(FactionComponent {
+                    faction: faction.clone(),
+                });
+            }
+
+            if let Some(star_system) = self
+                .definitions
+                .get_star_systems()
+                .get_star_system_of_planetoid(&planetoid.planetoid_reference)
+            {
+                spawned.insert(StarSystemComponent { star_system });
+            }
+        }
+        world.insert_resource(DeltaTResource::new(
+            self.minimum_tick_duration,
+            self.maximum_tick_duration,
+        ));
+        world.insert_resource(ViewportSettingsResource {
+            viewport_radius: self.viewport_radius,
+            view_layer_radii: self.view_layer_radii,
+            maximum_snapshot_objects: self.maximum_snapshot_objects,
+        });
+        world.insert_resource(AssetIndexResource {
+            asset_index: self.asset_index,
+        });
+        world.insert_resource(self.command_queue);
+        world.insert_resource(DefinitionsResource {
+            definitions: self.definitions,
+        });
+        world.insert_resource(TrafficSpawnerResource::default());
+        world.insert_resource(self.profile_save_queue);
+
+        match self.world_save {
+            Some(save) => restore_world_save(&mut world, save),
+            None => world.insert_resource(SavedPlayerShipsResource::default()),
+        }
+
+        let mut schedule = Schedule::default();
+
+        schedule.add_systems(
+            (
+                player_session_cleanup,
+                process_external_commands,
+                process_input_messages_system,
+                pre_collision_checkpoint,
+            )
+                .chain(),
+        );
+
+        schedule.add_systems(
+            (
+                increment_time,
+                update_rotations_with_angular_velocity,
+                update_velocities_with_semi_newtonian_physics,
+                update_positions_with_velocity,
+                post_collision_checkpoint,
+            )
+                .chain()
+                .after(pre_collision_checkpoint),
+        );
+
+        build_collision_phase::<Displayable>(&mut schedule, &mut world);
+        build_collision_phase::<Damaging>(&mut schedule, &mut world);
+        build_collision_phase::<Physical>(&mut schedule, &mut world);
+
+        world.init_resource::<Events<CollisionEvent>>();
+        world.init_resource::<Events<ShipDestroyedEvent>>();
+
+        schedule
+            .add_systems(
+                (
+                    tick_viewport,
+                    (tick_traffic_spawners, spawn_traffic_ships).chain(),
+                    check_despawn_times,
+                    spawn_player_ship_and_viewports,
+                )
+                    .after(post_collision_checkpoint),
+            )
+            .add_systems(
+                update_viewport_visibility
+                    .after(post_collision_checkpoint)
+                    .before(tick_viewport),
+            )
+            .add_systems(
+                apply_player_control::<SemiNewtonianPhysicsComponent>
+                    .after(post_collision_checkpoint),
+            )
+            .add_systems(apply_npc_control.after(post_collision_checkpoint))
+            .add_systems(
+                (
+                    (
+                        clear_old_ship_destroyed_events,
+                        apply_projectile_damage,
+                        apply_ship_destroyed_reputation,
+                    )
+                        .chain(),
+                    (regenerate_energy, regenerate_shields).chain(),
+                )
+                    .after(post_collision_checkpoint)
+                    .after(tick_viewport),
+            )
+            .add_systems(
+                (select_player_targets, send_target_info)
+                    .chain()
+                    .after(tick_viewport)
+                    .after(apply_projectile_damage),
+            )
+            .add_systems(
+                send_ship_status
+                    .after(regenerate_shields)
+                    .after(apply_projectile_damage)
+                    .after(fire_weapons),
+            )
+            .add_systems(
+                (
+                    clear_old_collision_events,
+                    send_collision_events,
+                    apply_collision_responses,
+                )
+                    .chain()
+                    .after(post_collision_checkpoint)
+                    .after(apply_projectile_damage),
+            )
+            .add_systems(
+                (
+                    land_player_ships,
+                    process_player_missions,
+                    outfit_player_ships,
+                    trade_commodities,
+                    take_off_player_ships,
+                )
+                    .chain()
+                    .after(tick_viewport)
+                    .before(spawn_player_ship_and_viewports),
+            )
+            .add_systems(
+                (begin_hyperspace_jumps, progress_hyperspace_jumps)
+                    .chain()
+                    .after(apply_player_control::<SemiNewtonianPhysicsComponent>)
+                    .after(apply_npc_control)
+                    .after(tick_viewport),
+            )
+            .add_systems(
+                fire_weapons
+                    .after(apply_player_control::<SemiNewtonianPhysicsComponent>)
+                    .after(progress_hyperspace_jumps),
+            );
+
+        (world, schedule)
+    }
+}
+
+fn build_collision_phase<T: Send + Sync + HashSized + 'static>(
+    schedule: &mut Schedule,
+    world: &mut World,
+) {
+    world.insert_resource(CollisionOptimizer::<T>::new());
+    world.init_resource::<Events<CollisionStarted<T>>>();
+    world.init_resource::<Events<CollisionOngoing<T>>>();
+    world.init_resource::<Events<CollisionEnded<T>>>();
+
+    schedule
+        .add_systems(clear_old_collision_layer_events::<T>)
+        .add_systems(
+            update_collisions_with_rotation::<T>.after(update_rotations_with_angular_velocity),
+        )
+        .add_systems(update_collisions_with_position::<T>.after(update_positions_with_velocity))
+        .add_systems(
+            update_swept_collisions::<T>
+                .after(update_collisions_with_position::<T>)
+                .after(update_collisions_with_rotation::<T>),
+        )
+        .add_systems(
+            collision_system::<T>
+                .after(clear_old_collision_layer_events::<T>)
+                .after(update_swept_collisions::<T>)
+                .before(post_collision_checkpoint),
+        );
+}
+
+fn pre_collision_checkpoint() {}
+fn post_collision_checkpoint() {}
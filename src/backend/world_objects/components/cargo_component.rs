@@ -17,5 +17,7 @@
 
 use bevy_ecs::component::Component;
 
-#[derive(Debug, Component)]
-pub struct RandomShipSpawnPlaceholderComponent {}
+#[derive(Component)]
+pub struct CargoComponent {
+    pub capacity: u32, // Tons
+}
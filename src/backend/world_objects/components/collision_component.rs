@@ -18,9 +18,8 @@
 use std::marker::PhantomData;
 
 use bevy_ecs::prelude::*;
-use dashmap::DashSet;
 
-use crate::backend::{shape::Shape, shrink_storage::*};
+use crate::backend::shape::Shape;
 
 #[derive(Component)]
 pub struct CollisionMarker<T: Send + Sync + Sized + 'static> {
@@ -37,31 +36,17 @@ impl<T: Send + Sync + Sized + 'static> CollisionMarker<T> {
     }
 }
 
+// Receives collision events on layer T for every CollisionMarker<T> it overlaps
 #[derive(Component)]
 pub struct CollidableComponent<T: Send + Sync + Sized + 'static> {
     _phantom: PhantomData<T>,
-    pub list: DashSet<Entity>,
     pub shape: Shape,
 }
 
-pub fn clear_old_collisions<T: Send + Sync + Sized>(collidables: Query<&CollidableComponent<T>>) {
-    collidables.par_iter().for_each(|x| x.clear());
-}
-
 impl<T: Send + Sync + Sized + 'static> CollidableComponent<T> {
-    pub fn clear(&self) {
-        self.list.shrink_storage();
-        self.list.clear();
-    }
-
-    pub fn add_to_collision_list(&self, collided_object: Entity) {
-        self.list.insert(collided_object);
-    }
-
     pub fn new(shape: Shape) -> CollidableComponent<T> {
         CollidableComponent {
             _phantom: PhantomData,
-            list: DashSet::new(),
             shape: shape,
         }
     }
@@ -0,0 +1,52 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use bevy_ecs::{
+    component::Component,
+    system::{Query, Res},
+};
+
+use crate::backend::resources::delta_t_resource::DeltaTResource;
+
+#[derive(Component)]
+pub struct EnergyComponent {
+    pub energy: f32,
+    pub maximum_energy: f32,
+    pub regeneration: f32, // Energy points per second
+}
+
+impl EnergyComponent {
+    // Spends the amount only if all of it is there to spend
+    pub fn spend(&mut self, amount: f32) -> bool {
+        if self.energy < amount {
+            return false;
+        }
+
+        self.energy -= amount;
+        true
+    }
+}
+
+pub fn regenerate_energy(mut energies: Query<&mut EnergyComponent>, time: Res<DeltaTResource>) {
+    let delta_t = time.get_last_tick_duration().as_secs_f32();
+    energies.par_iter_mut().for_each(|mut energy| {
+        if energy.energy < energy.maximum_energy {
+            energy.energy =
+                (energy.energy + energy.regeneration * delta_t).min(energy.maximum_energy);
+        }
+    });
+}
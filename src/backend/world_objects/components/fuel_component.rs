@@ -0,0 +1,35 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use bevy_ecs::component::Component;
+
+// Ships leave every planetoid with a full tank, since they are rebuilt from their definition on takeoff
+#[derive(Component)]
+pub struct FuelComponent {
+    pub fuel: f32,
+    pub maximum_fuel: f32,
+    pub jump_fuel: f32, // Used by each hyperspace jump
+}
+
+impl FuelComponent {
+    // Takes as much of the amount as there is, returning false if there was not enough to cover all of it
+    pub fn burn(&mut self, amount: f32) -> bool {
+        let enough = self.fuel >= amount;
+        self.fuel = (self.fuel - amount).max(0.0);
+        enough
+    }
+}
@@ -0,0 +1,127 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use bevy_ecs::{
+    component::Component,
+    system::{Query, Res},
+};
+
+use crate::backend::{
+    resources::delta_t_resource::DeltaTResource,
+    world_objects::components::energy_component::EnergyComponent,
+};
+
+#[derive(Debug, Component)]
+pub struct HealthComponent {
+    pub hull: f32,
+    pub maximum_hull: f32,
+    pub shield: f32,
+    pub maximum_shield: f32,
+    pub shield_regeneration: f32,
+    pub shield_regeneration_energy: f32, // Energy spent per shield point regenerated, when there is energy to draw on
+}
+
+impl HealthComponent {
+    pub fn new(
+        maximum_hull: f32,
+        maximum_shield: f32,
+        shield_regeneration: f32,
+        shield_regeneration_energy: f32,
+    ) -> Self {
+        Self {
+            hull: maximum_hull,
+            maximum_hull,
+            shield: maximum_shield,
+            maximum_shield,
+            shield_regeneration,
+            shield_regeneration_energy,
+        }
+    }
+
+    // Shields absorb their share of damage first, and whatever part of the hit they couldn't absorb carries through to the hull
+    pub fn apply_damage(&mut self, shield_damage: f32, hull_damage: f32) {
+        let penetrating = match self.shield > 0.0 {
+            true if shield_damage > 0.0 => {
+                let absorbed = self.shield.min(shield_damage);
+                self.shield -= absorbed;
+                1.0 - absorbed / shield_damage
+            }
+            true => 0.0, // Nothing which can't touch shields gets through them
+            false => 1.0,
+        };
+
+        self.hull = (self.hull - hull_damage * penetrating).max(0.0);
+    }
+
+    pub fn is_destroyed(&self) -> bool {
+        self.hull <= 0.0
+    }
+}
+
+pub fn regenerate_shields(
+    mut healths: Query<(&mut HealthComponent, Option<&mut EnergyComponent>)>,
+    time: Res<DeltaTResource>,
+) {
+    let delta_t = time.get_last_tick_duration().as_secs_f32();
+    healths.par_iter_mut().for_each(|(mut health, energy)| {
+        if health.shield >= health.maximum_shield {
+            return;
+        }
+
+        let wanted =
+            (health.shield_regeneration * delta_t).min(health.maximum_shield - health.shield);
+
+        // Shields only regenerate as far as the energy to pay for them goes
+        let regenerated = match energy {
+            Some(mut energy) if health.shield_regeneration_energy > 0.0 => {
+                let affordable = wanted.min(energy.energy / health.shield_regeneration_energy);
+                energy.energy -= affordable * health.shield_regeneration_energy;
+                affordable
+            }
+            _ => wanted,
+        };
+
+        health.shield += regenerated;
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HealthComponent;
+
+    #[test]
+    fn damage_beyond_the_shield_carries_through_to_the_hull() {
+        let mut health = HealthComponent::new(100.0, 10.0, 0.0, 0.0);
+
+        // A quarter of the hit is absorbed, the rest lands on the hull
+        health.apply_damage(40.0, 40.0);
+        assert_eq!(health.shield, 0.0);
+        assert_eq!(health.hull, 70.0);
+
+        health.apply_damage(40.0, 40.0);
+        assert_eq!(health.hull, 30.0);
+    }
+
+    #[test]
+    fn shields_which_hold_protect_the_hull() {
+        let mut health = HealthComponent::new(100.0, 50.0, 0.0, 0.0);
+
+        health.apply_damage(20.0, 20.0);
+        assert_eq!(health.shield, 30.0);
+        assert_eq!(health.hull, 100.0);
+    }
+}
@@ -0,0 +1,39 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::time::Duration;
+
+use bevy_ecs::component::Component;
+
+use crate::{
+    backend::configuration_file_loaders::definition_caches::star_system_definition_cache::StarSystemId,
+    shared_types::Rotation,
+};
+
+pub enum HyperspaceJumpPhase {
+    Departing,
+    Arriving,
+}
+
+// Ships with this component are mid-jump and no longer under the control of their pilot
+#[derive(Component)]
+pub struct HyperspaceJumpComponent {
+    pub destination: StarSystemId,
+    pub heading: Rotation,
+    pub phase: HyperspaceJumpPhase,
+    pub remaining: Duration,
+}
@@ -0,0 +1,32 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use bevy_ecs::component::Component;
+
+use crate::{
+    configuration_file_structures::ship_configuration_file::{AfterburnerRecord, ReverseThrustMode},
+    shared_types::{AccelerationScalar, AngularVelocity, Speed},
+};
+
+#[derive(Debug, Component)]
+pub struct ManeuverabilityComponent {
+    pub thrust: AccelerationScalar,
+    pub turn_rate: AngularVelocity,
+    pub maximum_speed: Speed, // Without the afterburner
+    pub reverse_thrust: ReverseThrustMode,
+    pub afterburner: Option<AfterburnerRecord>,
+}
@@ -0,0 +1,101 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use bevy_ecs::component::Component;
+
+use crate::{
+    configuration_file_structures::reference_types::CommodityReference,
+    connectivity::player_info::player_profile::PlayerHoldings,
+};
+
+#[derive(Debug)]
+pub struct MarketListing {
+    pub commodity: CommodityReference,
+    pub buy_price: u32,
+    pub sell_price: u32,
+    pub stock: u32, // Tons available for players to buy
+}
+
+// Trades are all or nothing, so a rejected trade never leaves a player with part of what they asked for
+#[derive(Debug, Component)]
+pub struct MarketComponent {
+    pub listings: Vec<MarketListing>,
+}
+
+impl MarketComponent {
+    fn get_listing_mut(&mut self, commodity: &str) -> Option<&mut MarketListing> {
+        self.listings
+            .iter_mut()
+            .find(|listing| listing.commodity == commodity)
+    }
+
+    pub fn buy(
+        &mut self,
+        holdings: &mut PlayerHoldings,
+        commodity: &str,
+        quantity: u32,
+        cargo_capacity: u32,
+    ) -> Result<(), ()> {
+        let listing = match self.get_listing_mut(commodity) {
+            Some(has) => has,
+            None => return Err(()),
+        };
+
+        let cost = listing.buy_price as u64 * quantity as u64;
+
+        if quantity == 0
+            || listing.stock < quantity
+            || holdings.credits < cost
+            || holdings.cargo_used().saturating_add(quantity) > cargo_capacity
+        {
+            return Err(());
+        }
+
+        listing.stock -= quantity;
+        holdings.credits -= cost;
+        *holdings.cargo.entry(listing.commodity.clone()).or_insert(0) += quantity;
+        Ok(())
+    }
+
+    pub fn sell(
+        &mut self,
+        holdings: &mut PlayerHoldings,
+        commodity: &str,
+        quantity: u32,
+    ) -> Result<(), ()> {
+        let listing = match self.get_listing_mut(commodity) {
+            Some(has) => has,
+            None => return Err(()), // Markets only take what they trade in
+        };
+
+        let held = match holdings.cargo.get_mut(commodity) {
+            Some(has) if quantity > 0 && *has >= quantity => has,
+            _ => return Err(()),
+        };
+
+        *held -= quantity;
+        if *held == 0 {
+            holdings.cargo.remove(commodity);
+        }
+
+        listing.stock = listing.stock.saturating_add(quantity);
+        holdings.credits = holdings
+            .credits
+            .saturating_add(listing.sell_price as u64 * quantity as u64);
+        Ok(())
+    }
+}
@@ -16,11 +16,30 @@
 */
 
 pub mod angular_velocity_component;
+pub mod cargo_component;
+pub mod cloak_component;
 pub mod collision_component;
+pub mod energy_component;
+pub mod faction_component;
+pub mod fuel_component;
+pub mod health_component;
+pub mod hyperspace_jump_component;
+pub mod maneuverability_component;
+pub mod market_component;
+pub mod npc_controller_component;
+pub mod physical_component;
+pub mod planetoid_component;
 pub mod player_controlled_component;
 pub mod position_component;
-pub mod random_ship_spawn_placeholder;
+pub mod projectile_component;
 pub mod rotation_component;
 pub mod semi_newtonian_physics_component;
+pub mod ship_type_component;
+pub mod shipyard_component;
+pub mod star_system_component;
+pub mod swept_collision_component;
+pub mod target_component;
 pub mod timeout_component;
+pub mod traffic_component;
 pub mod velocity_component;
+pub mod weapons_component;
@@ -0,0 +1,49 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+use bevy_ecs::{component::Component, entity::Entity};
+
+use crate::shared_types::{Coordinates, Distance};
+
+pub enum NpcBehavior {
+    // Coast along with engines off
+    Idle,
+    // Visit each planetoid in turn, looping back to the first once the last is reached
+    Patrol {
+        waypoints: Vec<Entity>,
+        next_waypoint: usize,
+    },
+    // Fly to a point and come to a stop there, then go idle
+    TravelTo { destination: Coordinates },
+    // Stay within a distance of another object
+    Follow { target: Entity, distance: Distance },
+    // Run from another object until at least a distance away
+    Flee { target: Entity, distance: Distance },
+    // Fly to a planetoid and land on it, leaving the world
+    Land { planetoid: Entity },
+}
+
+// Ships with this component are flown by the server instead of a player
+#[derive(Component)]
+pub struct NpcControllerComponent {
+    pub behavior: NpcBehavior,
+}
+
+impl NpcControllerComponent {
+    pub fn new(behavior: NpcBehavior) -> Self {
+        Self { behavior }
+    }
+}
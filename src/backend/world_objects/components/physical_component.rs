@@ -0,0 +1,30 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+use bevy_ecs::component::Component;
+
+use crate::backend::spatial_optimizer::hash_sized::HashSized;
+
+// Collision layer for anything solid which objects bounce off of
+pub struct Physical;
+
+impl HashSized for Physical {}
+
+// Objects which can be pushed around by collisions, anything solid without one is immovable
+#[derive(Component)]
+pub struct MassComponent {
+    pub mass: f32,
+}
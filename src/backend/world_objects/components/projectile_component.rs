@@ -0,0 +1,32 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use bevy_ecs::{component::Component, entity::Entity};
+
+use crate::backend::spatial_optimizer::hash_sized::HashSized;
+
+// Collision layer for anything which deals or receives weapon damage
+pub struct Damaging;
+
+impl HashSized for Damaging {}
+
+#[derive(Component)]
+pub struct ProjectileComponent {
+    pub owner: Entity,
+    pub shield_damage: f32,
+    pub hull_damage: f32,
+}
@@ -22,7 +22,8 @@ use crate::{backend::systems::apply_player_control::PlayerControllablePhysics, s
 #[derive(Component)]
 pub struct SemiNewtonianPhysicsComponent {
     pub maximum_speed: Speed,
-    pub thrust: AccelerationScalar
+    pub thrust: AccelerationScalar,
+    pub braking: AccelerationScalar // Slows the ship along its velocity, whichever way it faces
 }
 
 impl SemiNewtonianPhysicsComponent {
@@ -31,7 +32,8 @@ impl SemiNewtonianPhysicsComponent {
     ) -> SemiNewtonianPhysicsComponent {
         SemiNewtonianPhysicsComponent {
             maximum_speed,
-            thrust: AccelerationScalar::default()
+            thrust: AccelerationScalar::default(),
+            braking: AccelerationScalar::default()
         }
     }
 }
@@ -40,4 +42,12 @@ impl PlayerControllablePhysics for SemiNewtonianPhysicsComponent {
     fn set_acceleration(&mut self, acceleration: AccelerationScalar) {
         self.thrust = acceleration;
     }
+
+    fn set_maximum_speed(&mut self, maximum_speed: Speed) {
+        self.maximum_speed = maximum_speed;
+    }
+
+    fn set_braking(&mut self, deceleration: AccelerationScalar) {
+        self.braking = deceleration;
+    }
 }
@@ -0,0 +1,33 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use bevy_ecs::component::Component;
+
+use crate::backend::configuration_file_loaders::definition_caches::star_system_definition_cache::StarSystemId;
+
+// Entities without this component are all considered to share one unnamed space, separate from every star system
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub struct StarSystemComponent {
+    pub star_system: StarSystemId,
+}
+
+pub fn in_same_star_system(
+    first: Option<&StarSystemComponent>,
+    second: Option<&StarSystemComponent>,
+) -> bool {
+    first.map(|x| x.star_system) == second.map(|x| x.star_system)
+}
@@ -0,0 +1,39 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::marker::PhantomData;
+
+use bevy_ecs::component::Component;
+
+use crate::backend::shape::Shape;
+
+// Opts an entity into continuous collision on layer T, so its collision shape on that layer covers the whole path it moved along each tick
+// rather than only where it ended up, and it can't pass straight through anything small between ticks
+#[derive(Component)]
+pub struct SweptCollisionComponent<T: Send + Sync + Sized + 'static> {
+    _phantom: PhantomData<T>,
+    pub shape: Shape, // The entity's own shape before sweeping, only its size matters
+}
+
+impl<T: Send + Sync + Sized + 'static> SweptCollisionComponent<T> {
+    pub fn new(shape: Shape) -> Self {
+        Self {
+            _phantom: PhantomData,
+            shape,
+        }
+    }
+}
@@ -0,0 +1,26 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+use bevy_ecs::component::Component;
+
+use crate::backend::configuration_file_loaders::definition_caches::star_system_definition_cache::StarSystemId;
+
+// Marks a ship as spawned by a star system's traffic, so it counts toward that traffic's population cap
+#[derive(Component)]
+pub struct TrafficComponent {
+    pub star_system: StarSystemId,
+    pub traffic_index: usize, // Index into the star system's list of traffic
+}
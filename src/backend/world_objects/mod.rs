@@ -17,5 +17,6 @@
 
 pub mod components;
 pub mod planetoid;
+pub mod projectile;
 pub mod server_viewport;
 pub mod ship;
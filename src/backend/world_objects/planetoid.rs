@@ -26,7 +26,14 @@ use crate::{
 };
 
 use super::{
-    components::{collision_component::CollisionMarker, position_component::PositionComponent},
+    components::{
+        collision_component::CollisionMarker,
+        market_component::{MarketComponent, MarketListing},
+        physical_component::Physical,
+        planetoid_component::{LandablePlanetoidComponent, PlanetoidComponent},
+        position_component::PositionComponent,
+        shipyard_component::ShipyardComponent,
+    },
     server_viewport::Displayable,
 };
 
@@ -35,6 +42,7 @@ pub struct PlanetoidBundle {
     pub displayable: Displayable,
     pub displayable_collision_marker: CollisionMarker<Displayable>,
     pub position: PositionComponent,
+    pub planetoid: PlanetoidComponent,
 }
 
 impl PlanetoidBundle {
@@ -66,6 +74,91 @@ impl PlanetoidBundle {
                 },
             )),
             position: PositionComponent { position: position },
+            planetoid: PlanetoidComponent {
+                planetoid_reference: record.planetoid_reference.clone(),
+                planetoid_display_name: record.planetoid_display_name.clone(),
+            },
+        })
+    }
+
+    // Solid planetoids are part of the physical collision layer, but never pushed around by it
+    pub fn physical_collision_marker(record: &PlanetoidRecord) -> Option<CollisionMarker<Physical>> {
+        let collision_radius = record.collision_radius?;
+
+        Some(CollisionMarker::<Physical>::new(Shape::Circle(CircleData {
+            location: Point2D::new(record.x, record.y),
+            radius: Radius::new(collision_radius as f64),
+        })))
+    }
+}
+
+impl LandablePlanetoidComponent {
+    pub fn new(record: &PlanetoidRecord, asset_index: &AssetIndex) -> Option<Result<Self, ()>> {
+        let may_be_landed_on = record.may_be_landed_on.as_ref()?;
+
+        let lookup = |asset_name: &str| match asset_index.lookup_asset_by_name(asset_name) {
+            Some(has) => Ok(*has),
+            None => {
+                tracing::warn!(
+                    "Attempted to create landable planetoid {} with asset index {} which has an id which does not exist",
+                    record.planetoid_reference,
+                    asset_name
+                );
+                Err(())
+            }
+        };
+
+        let backdrop_image_asset = match lookup(&may_be_landed_on.backdrop_image_asset) {
+            Ok(has) => has,
+            Err(()) => return Some(Err(())),
+        };
+
+        let text_description_asset = match lookup(&may_be_landed_on.text_description_asset) {
+            Ok(has) => has,
+            Err(()) => return Some(Err(())),
+        };
+
+        Some(Ok(Self {
+            landing_radius: may_be_landed_on.landing_radius,
+            backdrop_image_asset,
+            text_description_asset,
+        }))
+    }
+}
+
+impl MarketComponent {
+    pub fn new(record: &PlanetoidRecord) -> Option<Self> {
+        let market = &record.may_be_landed_on.as_ref()?.features.as_ref()?.market;
+
+        if market.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            listings: market
+                .iter()
+                .map(|listing| MarketListing {
+                    commodity: listing.commodity.clone(),
+                    buy_price: listing.buy_price,
+                    sell_price: listing.sell_price,
+                    stock: listing.supply,
+                })
+                .collect(),
+        })
+    }
+}
+
+impl ShipyardComponent {
+    pub fn new(record: &PlanetoidRecord) -> Option<Self> {
+        let features = record.may_be_landed_on.as_ref()?.features.as_ref()?;
+
+        if features.shipyard.is_empty() && features.outfitter.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            ships: features.shipyard.clone(),
+            outfits: features.outfitter.clone(),
         })
     }
 }
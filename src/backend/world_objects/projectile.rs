@@ -0,0 +1,105 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::time::Duration;
+
+use bevy_ecs::{bundle::Bundle, entity::Entity};
+
+use crate::{
+    backend::shape::{CircleData, PointData, Shape},
+    configuration_file_structures::weapon_configuration_file::WeaponRecord,
+    connectivity::{asset_index::AssetIndex, view_layers::ViewLayers},
+    shared_types::{Coordinates, Radius, Rotation, Velocity},
+};
+
+use super::{
+    components::{
+        collision_component::CollisionMarker,
+        position_component::PositionComponent,
+        projectile_component::{Damaging, ProjectileComponent},
+        rotation_component::RotationComponent,
+        swept_collision_component::SweptCollisionComponent,
+        timeout_component::TimeoutComponent,
+        velocity_component::VelocityComponent,
+    },
+    server_viewport::Displayable,
+};
+
+#[derive(Bundle)]
+pub struct ProjectileBundle {
+    pub displayable: Displayable,
+    pub displayable_collision_marker: CollisionMarker<Displayable>,
+    pub damaging_collision_marker: CollisionMarker<Damaging>,
+    pub damaging_sweep: SweptCollisionComponent<Damaging>, // Projectiles are fast and small enough to skip over ships between ticks
+    pub projectile: ProjectileComponent,
+    pub position: PositionComponent,
+    pub velocity: VelocityComponent,
+    pub rotation: RotationComponent,
+    pub timeout: TimeoutComponent,
+}
+
+impl ProjectileBundle {
+    pub fn new(
+        record: &WeaponRecord,
+        owner: Entity,
+        position: Coordinates,
+        rotation: Rotation,
+        owner_velocity: Velocity,
+        asset_index: &AssetIndex,
+    ) -> Result<Self, ()> {
+        let display_asset = match asset_index.lookup_asset_by_name(&record.projectile_asset) {
+            Some(has) => *has,
+            None => {
+                tracing::warn!(
+                    "Attempted to create projectile for weapon {} with asset index {} which has an id which does not exist",
+                    record.weapon_reference,
+                    record.projectile_asset
+                );
+                return Err(());
+            }
+        };
+
+        let velocity =
+            owner_velocity + Velocity::from_angle_and_length(rotation, record.projectile_speed);
+        let damaging_shape = Shape::Circle(CircleData {
+            location: position,
+            radius: Radius::new(record.projectile_collision_radius as f64),
+        });
+
+        Ok(Self {
+            displayable: Displayable {
+                display_radius: record.projectile_display_radius,
+                object_asset: display_asset,
+                view_layer: ViewLayers::Weapons,
+            },
+            displayable_collision_marker: CollisionMarker::<Displayable>::new(Shape::Point(
+                PointData { point: position },
+            )),
+            damaging_collision_marker: CollisionMarker::<Damaging>::new(damaging_shape),
+            damaging_sweep: SweptCollisionComponent::<Damaging>::new(damaging_shape),
+            projectile: ProjectileComponent {
+                owner,
+                shield_damage: record.shield_damage,
+                hull_damage: record.hull_damage,
+            },
+            position: PositionComponent { position },
+            velocity: VelocityComponent { velocity },
+            rotation: RotationComponent { rotation },
+            timeout: TimeoutComponent::new(Duration::from_secs_f32(record.projectile_lifetime)),
+        })
+    }
+}
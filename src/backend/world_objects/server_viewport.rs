@@ -15,23 +15,32 @@
     along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use std::collections::HashSet;
 use std::sync::Mutex;
 
 use crate::backend::components::session::player_session_component::PlayerSessionComponent;
+use crate::backend::events::collision_layer_events::{CollisionEnded, CollisionStarted};
 use crate::backend::shrink_storage::ImmutableShrinkable;
 use crate::backend::spatial_optimizer::hash_sized::HashSized;
 use crate::backend::world_objects::components::collision_component::*;
 use crate::configuration_file_structures::reference_types::AssetIndexReference;
 use crate::connectivity::controllable_object_message_data::ViewportFollowData;
 use crate::connectivity::dynamic_object_message_data::*;
+use crate::connectivity::player_info::player_profile::PlayerProfile;
 use crate::connectivity::server_client_message::*;
 use crate::connectivity::view_layers::ViewLayers;
 use crate::shared_types::Coordinates;
 use bevy_ecs::prelude::*;
-use dashmap::DashSet;
+use bevy_ecs::system::SystemParam;
+use dashmap::DashMap;
 use tracing::warn;
 
 use super::components::angular_velocity_component::AngularVelocityComponent;
+use super::components::cloak_component::CloakComponent;
+use super::components::faction_component::FactionComponent;
+use crate::backend::resources::definitions_resource::DefinitionsResource;
+use crate::backend::resources::delta_t_resource::DeltaTResource;
+use crate::backend::resources::viewport_settings_resource::ViewportSettingsResource;
 use super::components::position_component::PositionComponent;
 use super::components::rotation_component::RotationComponent;
 use super::components::velocity_component::VelocityComponent;
@@ -89,8 +98,28 @@ impl ViewportUpdated {
     }
 }
 
+// The state of an object as the client was last told it
+#[derive(Clone, Copy, PartialEq)]
+struct SentObjectState {
+    position: (f64, f64),
+    rotation: Option<f32>,
+    velocity: Option<(f32, f32)>,
+    angular_velocity: Option<f32>,
+}
+
+// Only values which differ from what the client was last sent need sending again
+fn changed_since<T: PartialEq + Copy>(previous: Option<T>, current: T) -> Option<T> {
+    if previous == Some(current) {
+        None
+    } else {
+        Some(current)
+    }
+}
+
 struct ServerViewportData {
-    last_tick_ids: DashSet<Entity>,
+    last_sent: DashMap<Entity, SentObjectState>,
+    visible: HashSet<Entity>, // Everything currently overlapping the viewport, kept up to date from collision events
+    deferred: HashSet<Entity>, // Objects left out of the last snapshot for being over budget, which go ahead of the rest next time
     tracking_mode: ViewportUpdated,
 }
 
@@ -103,7 +132,9 @@ impl ServerViewport {
     pub fn new() -> ServerViewport {
         return ServerViewport {
             data: ServerViewportData {
-                last_tick_ids: DashSet::new(),
+                last_sent: DashMap::new(),
+                visible: HashSet::new(),
+                deferred: HashSet::new(),
                 tracking_mode: ViewportUpdated {
                     updated: false,
                     tracking_mode: ViewportTrackingMode::Static(Coordinates::new(0.0, 0.0)),
@@ -115,7 +146,7 @@ impl ServerViewport {
 
     pub fn refresh_for_client(&self) {
         let mut locked = self.data.lock().unwrap();
-        locked.last_tick_ids.clear();
+        locked.last_sent.clear();
         locked.tracking_mode.set_unupdated();
     }
 
@@ -123,6 +154,72 @@ impl ServerViewport {
         let mut locked = self.data.lock().unwrap();
         locked.tracking_mode.set_tracking_mode(new_tracking_mode);
     }
+
+    // Whether the client has been sent this entity, and so could have picked it out
+    pub fn can_see(&self, entity: Entity) -> bool {
+        let locked = self.data.lock().unwrap();
+        locked.last_sent.contains_key(&entity)
+    }
+}
+
+type Motion<'a> = (
+    Option<&'a VelocityComponent>,
+    Option<&'a RotationComponent>,
+    Option<&'a AngularVelocityComponent>,
+);
+
+// Everything needed to decide whether a viewer can see through a cloak
+#[derive(SystemParam)]
+pub struct Concealment<'w, 's> {
+    cloaks: Query<'w, 's, (Option<&'static CloakComponent>, Option<&'static FactionComponent>)>,
+    definitions: Res<'w, DefinitionsResource>,
+}
+
+impl Concealment<'_, '_> {
+    // Cloaked ships are only revealed to their own pilot and to players their faction counts as allies
+    fn is_revealed_to(
+        &self,
+        entity: Entity,
+        viewer_ship: Option<Entity>,
+        viewer: Option<&PlayerProfile>,
+    ) -> bool {
+        match self.cloaks.get(entity) {
+            Ok((Some(_cloak), faction)) => {
+                Some(entity) == viewer_ship
+                    || faction
+                        .and_then(|faction| {
+                            self.definitions
+                                .definitions
+                                .get_factions()
+                                .get_faction_record(&faction.faction)
+                        })
+                        .is_some_and(|faction| {
+                            viewer.is_some_and(|profile| {
+                                profile.get_holdings().is_allied_with(faction)
+                            })
+                        })
+            }
+            _ => true,
+        }
+    }
+}
+
+pub fn update_viewport_visibility(
+    mut viewports: Query<&mut ServerViewport>,
+    mut started: EventReader<CollisionStarted<Displayable>>,
+    mut ended: EventReader<CollisionEnded<Displayable>>,
+) {
+    for event in ended.read() {
+        if let Ok(mut viewport) = viewports.get_mut(event.receiver) {
+            viewport.data.get_mut().unwrap().visible.remove(&event.sender);
+        }
+    }
+
+    for event in started.read() {
+        if let Ok(mut viewport) = viewports.get_mut(event.receiver) {
+            viewport.data.get_mut().unwrap().visible.insert(event.sender);
+        }
+    }
 }
 
 pub fn tick_viewport(
@@ -136,10 +233,11 @@ pub fn tick_viewport(
         &PositionComponent,
         &Displayable,
     )>,
-    optional_velocity: Query<&VelocityComponent>,
-    optional_rotation: Query<&RotationComponent>,
-    optional_angular_velocity: Query<&AngularVelocityComponent>,
+    motion: Query<Motion<'_>>,
+    concealment: Concealment,
     sessions: Query<&PlayerSessionComponent>,
+    time: Res<DeltaTResource>,
+    settings: Res<ViewportSettingsResource>,
 ) {
     for (mut viewport, mut collide_with, parent) in all_viewports.iter_mut() {
         let parent = match sessions.get(parent.parent()) {
@@ -149,7 +247,7 @@ pub fn tick_viewport(
             }
         };
 
-        let outbound_messages = parent.command_queue_outbound.clone();
+        let outbound_messages = &parent.command_queue_outbound;
 
         let viewport = viewport.data.get_mut().unwrap();
 
@@ -166,7 +264,7 @@ pub fn tick_viewport(
             };
 
             let _ = outbound_messages
-                .send_blocking(ServerClientMessage::ViewportFollow(tracking_message_data)); // Nothing we can do about send errors for users disconnected
+                .send(ServerClientMessage::ViewportFollow(tracking_message_data)); // Nothing we can do about send errors for users disconnected
 
             viewport.tracking_mode.set_updated();
         }
@@ -194,7 +292,23 @@ pub fn tick_viewport(
             }
         };
 
-        for collision in collide_with.list.iter().map(|x| x.key().clone()) {
+        let mut snapshot = WorldSnapshotData {
+            tick: time.get_tick(),
+            created: Vec::new(),
+            updated: Vec::new(),
+            destroyed: Vec::new(),
+        };
+
+        let center = collide_with.shape.center();
+        let viewer_ship = parent.should_follow;
+        let viewer_session = parent.session.upgrade();
+        let viewer = viewer_session
+            .as_ref()
+            .map(|session| session.player_profile.as_ref());
+
+        let mut shown = Vec::new();
+
+        for collision in viewport.visible.iter().copied() {
             // Theoretically we could get an entity in the collision list that doesn't match the query, we should just ignore them
             let (_collided_hitbox, position, displayable) = match displayables.get(collision) {
                 Ok(x) => x,
@@ -207,81 +321,440 @@ pub fn tick_viewport(
                 }
             };
 
-            // Send a creation frame for each object not previously within the viewport's range
-            match viewport.last_tick_ids.contains(&collision) {
-                true => {}
-                false => {
-                    let _ = outbound_messages.send_blocking(
-                        ServerClientMessage::DynamicObjectCreation(DynamicObjectCreationData {
-                            id: collision.to_bits(),
-                            object_asset: displayable.object_asset,
-                            view_layer: displayable.view_layer,
-                            display_radius: displayable.display_radius,
-                        }),
-                    ); // Nothing we can do about send errors for users disconnected
-                }
+            let distance = position.position.distance_to(center);
+
+            // Each layer only reaches so far, measured to the nearest edge of the object like the viewport itself
+            if settings
+                .view_layer_radii
+                .radius_of(displayable.view_layer)
+                .is_some_and(|radius| distance - displayable.display_radius as f64 > radius)
+            {
+                continue;
             }
 
-            let velocity = match optional_velocity.get(collision) {
-                Ok(has) => Some(VelocityMessage {
-                    vx: has.velocity.x,
-                    vy: has.velocity.y,
-                }),
-                Err(_) => None,
-            };
+            if !concealment.is_revealed_to(collision, viewer_ship, viewer) {
+                continue;
+            }
+
+            shown.push((collision, position, displayable, distance));
+        }
 
-            let rotation = match optional_rotation.get(collision) {
-                Ok(has) => Some(RotationMessage {
-                    rotation: has.rotation.get(),
-                }),
-                Err(_) => None,
+        // The player's own ship goes first, then anything which has already waited, then the most important layers, then whatever is nearest
+        shown.sort_by(|x, y| {
+            let key = |object: &(Entity, &PositionComponent, &Displayable, f64)| {
+                (
+                    Some(object.0) != viewer_ship,
+                    !viewport.deferred.contains(&object.0),
+                    std::cmp::Reverse(object.2.view_layer.priority()),
+                )
             };
+            key(x).cmp(&key(y)).then(x.3.total_cmp(&y.3))
+        });
 
-            let angular_velocity = match optional_angular_velocity.get(collision) {
-                Ok(has) => Some(AngularVelocityMessage {
-                    angular_velocity: has.angular_velocity.get(),
-                }),
-                Err(_) => None,
+        let mut budget = settings.maximum_snapshot_objects;
+        let mut deferred = HashSet::new();
+
+        for (collision, position, displayable, _) in shown.iter().copied() {
+            let (velocity, rotation, angular_velocity) = motion.get(collision).unwrap_or_default();
+
+            let current = SentObjectState {
+                position: (position.position.x, position.position.y),
+                rotation: rotation.map(|x| x.rotation.get()),
+                velocity: velocity.map(|x| (x.velocity.x, x.velocity.y)),
+                angular_velocity: angular_velocity.map(|x| x.angular_velocity.get()),
             };
 
-            // Send an update frame for each object moved which has been within the viewport for at least one frame
-            let _ = outbound_messages.send_blocking(ServerClientMessage::DynamicObjectUpdate(
-                DynamicObjectUpdateData {
+            let previous = viewport.last_sent.get(&collision).map(|x| *x);
+
+            // Objects which have not changed since the last tick, such as planetoids, are left out entirely
+            if previous == Some(current) {
+                continue;
+            }
+
+            // Anything over budget waits for a later tick, still shown as the client was last told
+            if budget == 0 {
+                deferred.insert(collision);
+                continue;
+            }
+            budget -= 1;
+
+            viewport.last_sent.insert(collision, current);
+
+            // Send a creation for each object not previously within the viewport's range
+            if previous.is_none() {
+                snapshot.created.push(DynamicObjectCreationData {
                     id: collision.to_bits(),
-                    x: position.position.x,
-                    y: position.position.y,
-                    rotation: rotation,
-                    velocity: velocity,
-                    angular_velocity: angular_velocity,
-                },
-            )); // Nothing we can do about send errors for users disconnected
-        }
+                    object_asset: displayable.object_asset,
+                    view_layer: displayable.view_layer,
+                    display_radius: displayable.display_radius,
+                });
+            }
 
-        // Find all entities in the previous frame which no longer are within the viewport for the current frame
-        let removed = viewport
-            .last_tick_ids
-            .iter()
-            .map(|x| *x)
-            .filter(|x| !collide_with.list.contains(&x));
-
-        // Send a destruction frame for all removed entities to guarantee no stale entities remain on the client
-        for remove in removed {
-            let _ = outbound_messages.send_blocking(ServerClientMessage::DynamicObjectDestruction(
-                DynamicObjectDestructionData {
-                    id: remove.to_bits(),
-                },
-            )); // Nothing we can do about send errors for users disconnected
+            snapshot.updated.push(DynamicObjectUpdateData {
+                id: collision.to_bits(),
+                position: changed_since(previous.map(|x| x.position), current.position)
+                    .map(|(x, y)| PositionMessage { x, y }),
+                rotation: changed_since(previous.map(|x| x.rotation), current.rotation)
+                    .flatten()
+                    .map(|rotation| RotationMessage { rotation }),
+                velocity: changed_since(previous.map(|x| x.velocity), current.velocity)
+                    .flatten()
+                    .map(|(vx, vy)| VelocityMessage { vx, vy }),
+                angular_velocity: changed_since(
+                    previous.map(|x| x.angular_velocity),
+                    current.angular_velocity,
+                )
+                .flatten()
+                .map(|angular_velocity| AngularVelocityMessage { angular_velocity }),
+            });
         }
 
-        // Clear the entities we consider for the last tick
-        viewport.last_tick_ids.clear();
+        let shown: HashSet<Entity> = shown.into_iter().map(|x| x.0).collect();
+        viewport.deferred = deferred;
+
+        // Send a destruction for all entities which are no longer within the viewport to guarantee no stale entities remain on the client
+        viewport.last_sent.retain(|entity, _| {
+            if shown.contains(entity) {
+                true
+            } else {
+                snapshot.destroyed.push(DynamicObjectDestructionData {
+                    id: entity.to_bits(),
+                });
+                false
+            }
+        });
 
-        // Add all entities for this tick to the last tick counter
-        for x in collide_with.list.iter() {
-            viewport.last_tick_ids.insert(*x);
+        if !snapshot.created.is_empty()
+            || !snapshot.updated.is_empty()
+            || !snapshot.destroyed.is_empty()
+        {
+            let _ = outbound_messages.send(ServerClientMessage::WorldSnapshot(snapshot)); // Nothing we can do about send errors for users disconnected
         }
 
         // Make sure the viewport last tick storage doesn't have a huge amount of excess capacity
-        viewport.last_tick_ids.shrink_storage();
+        viewport.last_sent.shrink_storage();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backend::{
+            components::session::player_session_component::PlayerSessionComponent,
+            resources::viewport_settings_resource::ViewportSettingsResource,
+            test_harness::{TestHarness, TestSession, test_planetoid},
+            world_objects::components::{
+                cloak_component::CloakComponent, faction_component::FactionComponent,
+            },
+        },
+        connectivity::{
+            client_server_message::{ClientServerMessage, ControlInput},
+            controllable_object_message_data::ViewportFollowData,
+            dynamic_object_message_data::WorldSnapshotData,
+            server_client_message::ServerClientMessage,
+        },
+        shared_types::{Coordinates, Velocity},
+    };
+
+    fn snapshots(messages: Vec<ServerClientMessage>) -> Vec<WorldSnapshotData> {
+        messages
+            .into_iter()
+            .filter_map(|message| match message {
+                ServerClientMessage::WorldSnapshot(snapshot) => Some(snapshot),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn followed_id(messages: &[ServerClientMessage]) -> Option<u64> {
+        messages.iter().rev().find_map(|message| match message {
+            ServerClientMessage::ViewportFollow(ViewportFollowData::Entity { id }) => Some(*id),
+            _ => None,
+        })
+    }
+
+    fn was_created(snapshots: &[WorldSnapshotData], id: u64) -> bool {
+        snapshots
+            .iter()
+            .any(|snapshot| snapshot.created.iter().any(|x| x.id == id))
+    }
+
+    fn was_updated(snapshots: &[WorldSnapshotData], id: u64) -> bool {
+        snapshots
+            .iter()
+            .any(|snapshot| snapshot.updated.iter().any(|x| x.id == id))
+    }
+
+    // Spawns the player's ship and viewport, and lets the viewport pick up everything around it
+    fn connect_and_settle(harness: &mut TestHarness) -> (TestSession, Vec<ServerClientMessage>) {
+        let session = harness.connect_player();
+        harness.step_for(3);
+        let messages = session.received();
+        (session, messages)
+    }
+
+    #[test]
+    fn new_player_follows_their_ship_and_sees_nearby_objects() {
+        let mut harness = TestHarness::new();
+        let earth = harness.find_planetoid("earth").unwrap();
+        let (session, messages) = connect_and_settle(&mut harness);
+
+        let player = harness
+            .world
+            .get::<PlayerSessionComponent>(session.entity)
+            .unwrap();
+        assert!(player.primary_viewport.is_some());
+
+        let ship_id = followed_id(&messages).expect("viewport should follow the new ship");
+        assert!(messages
+            .iter()
+            .any(|x| matches!(x, ServerClientMessage::StarSystemEntered(data) if data.star_system_reference == "sol")));
+
+        let snapshots = snapshots(messages);
+        assert!(was_created(&snapshots, ship_id));
+        assert!(was_created(&snapshots, earth.to_bits()));
+    }
+
+    #[test]
+    fn players_whose_connection_attaches_late_still_learn_where_they_are() {
+        let mut harness = TestHarness::new();
+        let mut session = harness.log_in_player();
+        harness.step_for(3);
+
+        session.attach();
+        session.send(ClientServerMessage::Refresh);
+        harness.step_for(2);
+        let messages = session.received();
+
+        assert!(messages
+            .iter()
+            .any(|x| matches!(x, ServerClientMessage::StarSystemEntered(data) if data.star_system_reference == "sol")));
+        let ship_id = followed_id(&messages).expect("viewport should follow the new ship");
+        assert!(was_created(&snapshots(messages), ship_id));
+    }
+
+    #[test]
+    fn unchanged_objects_are_not_sent_again() {
+        let mut harness = TestHarness::new();
+        let earth = harness.find_planetoid("earth").unwrap();
+        let (session, _) = connect_and_settle(&mut harness);
+
+        harness.step_for(5);
+        let snapshots = snapshots(session.received());
+
+        assert!(!was_created(&snapshots, earth.to_bits()));
+        assert!(!was_updated(&snapshots, earth.to_bits()));
+    }
+
+    #[test]
+    fn moving_objects_only_send_changed_values() {
+        let mut harness = TestHarness::new();
+        let drifting = harness.spawn_ship(
+            "shuttle",
+            Coordinates::new(100.0, 100.0),
+            Velocity::new(50.0, 0.0),
+            Some("sol"),
+        );
+        let (session, _) = connect_and_settle(&mut harness);
+
+        harness.step();
+        let snapshots = snapshots(session.received());
+        assert_eq!(snapshots.len(), 1);
+
+        let update = snapshots[0]
+            .updated
+            .iter()
+            .find(|x| x.id == drifting.to_bits())
+            .expect("drifting ship should be updated every tick");
+        assert!(update.position.is_some());
+        assert!(update.velocity.is_none());
+        assert!(update.rotation.is_none());
+    }
+
+    #[test]
+    fn objects_in_other_star_systems_are_not_visible() {
+        let mut harness = TestHarness::new();
+        let elsewhere = harness.spawn_ship(
+            "shuttle",
+            Coordinates::new(100.0, 0.0),
+            Velocity::zero(),
+            Some("alpha"),
+        );
+        let (_session, messages) = connect_and_settle(&mut harness);
+
+        assert!(!was_created(&snapshots(messages), elsewhere.to_bits()));
+    }
+
+    #[test]
+    fn objects_beyond_the_viewport_radius_are_not_visible() {
+        let mut harness = TestHarness::new();
+        let nearby = harness.spawn_planetoid(
+            &test_planetoid("nearby", Coordinates::new(0.0, 1000.0)),
+            Some("sol"),
+        );
+        let distant = harness.spawn_planetoid(
+            &test_planetoid("distant", Coordinates::new(0.0, 20000.0)),
+            Some("sol"),
+        );
+        let (_session, messages) = connect_and_settle(&mut harness);
+
+        let snapshots = snapshots(messages);
+        assert!(was_created(&snapshots, nearby.to_bits()));
+        assert!(!was_created(&snapshots, distant.to_bits()));
+    }
+
+    #[test]
+    fn despawned_objects_are_destroyed_on_the_client() {
+        let mut harness = TestHarness::new();
+        let doomed = harness.spawn_ship(
+            "shuttle",
+            Coordinates::new(-100.0, 0.0),
+            Velocity::zero(),
+            Some("sol"),
+        );
+        let (session, messages) = connect_and_settle(&mut harness);
+        assert!(was_created(&snapshots(messages), doomed.to_bits()));
+
+        harness.world.despawn(doomed);
+        harness.step_for(2);
+
+        assert!(
+            snapshots(session.received())
+                .iter()
+                .any(|snapshot| snapshot.destroyed.iter().any(|x| x.id == doomed.to_bits()))
+        );
+    }
+
+    #[test]
+    fn control_input_moves_the_player_ship() {
+        let mut harness = TestHarness::new();
+        let (session, messages) = connect_and_settle(&mut harness);
+        let ship_id = followed_id(&messages).unwrap();
+
+        session.send(ClientServerMessage::ControlInput {
+            input: ControlInput::Forward,
+            pressed: true,
+        });
+        harness.step_for(10);
+
+        let snapshots = snapshots(session.received());
+        let last_velocity = snapshots
+            .iter()
+            .flat_map(|snapshot| snapshot.updated.iter())
+            .filter(|x| x.id == ship_id)
+            .filter_map(|x| x.velocity.as_ref())
+            .next_back()
+            .expect("thrusting ship should report a new velocity");
+        assert!(last_velocity.vx > 0.0);
+    }
+
+    #[test]
+    fn each_view_layer_is_only_shown_within_its_own_radius() {
+        let mut harness = TestHarness::new();
+        harness
+            .world
+            .resource_mut::<ViewportSettingsResource>()
+            .view_layer_radii
+            .planetoids = Some(300.0);
+        let nearby = harness.spawn_planetoid(
+            &test_planetoid("nearby", Coordinates::new(0.0, 1000.0)),
+            Some("sol"),
+        );
+        let ship = harness.spawn_ship(
+            "shuttle",
+            Coordinates::new(0.0, 1000.0),
+            Velocity::zero(),
+            Some("sol"),
+        );
+        let (_session, messages) = connect_and_settle(&mut harness);
+
+        let snapshots = snapshots(messages);
+        assert!(was_created(&snapshots, ship.to_bits()));
+        assert!(!was_created(&snapshots, nearby.to_bits()));
+    }
+
+    #[test]
+    fn cloaked_ships_are_hidden_from_other_players_but_not_their_pilot() {
+        let mut harness = TestHarness::new();
+        let cloaked = harness.spawn_ship(
+            "shuttle",
+            Coordinates::new(100.0, 0.0),
+            Velocity::zero(),
+            Some("sol"),
+        );
+        harness.world.entity_mut(cloaked).insert(CloakComponent {});
+        let (session, messages) = connect_and_settle(&mut harness);
+        let ship_id = followed_id(&messages).unwrap();
+        assert!(!was_created(&snapshots(messages), cloaked.to_bits()));
+
+        let ship = harness.player_ship(&session);
+        harness.world.entity_mut(ship).insert(CloakComponent {});
+        session.send(ClientServerMessage::Refresh);
+        harness.step_for(2);
+        assert!(was_created(&snapshots(session.received()), ship_id));
+    }
+
+    #[test]
+    fn snapshots_over_budget_send_the_players_ship_first_and_the_rest_later() {
+        let mut harness = TestHarness::new();
+        harness
+            .world
+            .resource_mut::<ViewportSettingsResource>()
+            .maximum_snapshot_objects = 2;
+        let earth = harness.find_planetoid("earth").unwrap();
+        let others: Vec<_> = [100.0, 200.0, 300.0]
+            .into_iter()
+            .map(|x| {
+                harness.spawn_ship(
+                    "shuttle",
+                    Coordinates::new(x, 0.0),
+                    Velocity::zero(),
+                    Some("sol"),
+                )
+            })
+            .collect();
+        let (session, messages) = connect_and_settle(&mut harness);
+        let ship_id = followed_id(&messages).unwrap();
+
+        harness.step_for(5);
+        let sent: Vec<_> = snapshots(messages)
+            .into_iter()
+            .chain(snapshots(session.received()))
+            .collect();
+        assert!(
+            sent.iter()
+                .all(|snapshot| snapshot.updated.len() <= 2)
+        );
+        assert_eq!(sent[0].created[0].id, ship_id);
+
+        for id in others.iter().chain([&earth]).map(|x| x.to_bits()) {
+            assert!(was_created(&sent, id));
+        }
+    }
+
+    #[test]
+    fn cloaked_ships_are_revealed_to_players_allied_with_their_faction() {
+        let mut harness = TestHarness::new();
+        let cloaked = harness.spawn_ship(
+            "shuttle",
+            Coordinates::new(100.0, 0.0),
+            Velocity::zero(),
+            Some("sol"),
+        );
+        harness.world.entity_mut(cloaked).insert((
+            CloakComponent {},
+            FactionComponent {
+                faction: "martians".to_string(),
+            },
+        ));
+        let (session, messages) = connect_and_settle(&mut harness);
+        assert!(!was_created(&snapshots(messages), cloaked.to_bits()));
+
+        session.profile().modify_holdings(|holdings| {
+            holdings.reputation.insert("martians".to_string(), 10);
+        });
+        session.send(ClientServerMessage::Refresh);
+        harness.step_for(2);
+        assert!(was_created(&snapshots(session.received()), cloaked.to_bits()));
     }
 }
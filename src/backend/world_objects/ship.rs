@@ -18,16 +18,27 @@
 use bevy_ecs::bundle::Bundle;
 
 use crate::{
-    backend::shape::{PointData, Shape},
+    configuration_file_structures::ship_configuration_file::ShipRecord,
     connectivity::{asset_index::AssetIndex, view_layers::ViewLayers},
-    shared_types::{AngularVelocity, Coordinates, Rotation, Velocity},
+    shared_types::{AccelerationScalar, AngularVelocity, Coordinates, Rotation, Speed, Velocity},
 };
 
 use super::{
     components::{
-        angular_velocity_component::AngularVelocityComponent, collision_component::CollisionMarker,
-        position_component::PositionComponent, rotation_component::RotationComponent,
+        angular_velocity_component::AngularVelocityComponent,
+        cargo_component::CargoComponent,
+        collision_component::{CollidableComponent, CollisionMarker},
+        energy_component::EnergyComponent,
+        fuel_component::FuelComponent,
+        health_component::HealthComponent,
+        maneuverability_component::ManeuverabilityComponent,
+        physical_component::{MassComponent, Physical},
+        position_component::PositionComponent,
+        projectile_component::Damaging,
+        rotation_component::RotationComponent,
+        ship_type_component::ShipTypeComponent,
         velocity_component::VelocityComponent,
+        weapons_component::WeaponsComponent,
     },
     server_viewport::Displayable,
 };
@@ -40,48 +51,104 @@ pub struct ShipBundle {
     pub velocity: VelocityComponent,
     pub rotation: RotationComponent,
     pub angular_velocity: AngularVelocityComponent,
+    pub maneuverability: ManeuverabilityComponent,
+    pub fuel: FuelComponent,
+    pub energy: EnergyComponent,
+    pub cargo: CargoComponent,
+    pub health: HealthComponent,
+    pub weapons: WeaponsComponent,
+    pub damaging_collidable: CollidableComponent<Damaging>,
+    pub physical_collision_marker: CollisionMarker<Physical>,
+    pub physical_collidable: CollidableComponent<Physical>,
+    pub mass: MassComponent,
+    pub ship_type: ShipTypeComponent,
 }
 
+// Mass of ships which do not define their own
+const DEFAULT_SHIP_MASS: f32 = 100.0;
+
 impl ShipBundle {
     pub fn new(
+        record: &ShipRecord,
         position: Coordinates,
         velocity: Option<Velocity>,
         rotation: Option<Rotation>,
         angular_velocity: Option<AngularVelocity>,
         asset_index: &AssetIndex,
     ) -> Result<Self, ()> {
-        // Ship records do not exist yet in this build so just use a fixed asset name for now
-        let asset_name = "default_asset";
-        let display_asset = match asset_index.lookup_asset_by_name(asset_name) {
+        let display_asset = match asset_index.lookup_asset_by_name(&record.display_asset) {
             Some(has) => *has,
             None => {
                 tracing::warn!(
-                    "Attempted to create planetoid with asset index {} which has an id which does not exist",
-                    asset_name
+                    "Attempted to create ship {} with asset index {} which has an id which does not exist",
+                    record.ship_reference,
+                    record.display_asset
                 );
                 return Err(());
             }
         };
 
+        let rotation = rotation.unwrap_or_default();
+
         Ok(Self {
             displayable: Displayable {
-                display_radius: 25.0,
+                display_radius: record.display_radius,
                 object_asset: display_asset,
                 view_layer: ViewLayers::Ships
             },
-            displayable_collision_marker: CollisionMarker::<Displayable>::new(Shape::Point(
-                PointData { point: position },
-            )),
+            displayable_collision_marker: CollisionMarker::<Displayable>::new(
+                record.collision_shape.to_shape(position, rotation),
+            ),
             position: PositionComponent { position: position },
             velocity: VelocityComponent {
                 velocity: velocity.unwrap_or_default(),
             },
-            rotation: RotationComponent {
-                rotation: rotation.unwrap_or_default(),
-            },
+            rotation: RotationComponent { rotation },
             angular_velocity: AngularVelocityComponent {
                 angular_velocity: angular_velocity.unwrap_or_default(),
             },
+            maneuverability: ManeuverabilityComponent {
+                thrust: AccelerationScalar::new(record.thrust),
+                turn_rate: AngularVelocity::radians(record.turn_rate),
+                maximum_speed: Speed::new(record.maximum_speed),
+                reverse_thrust: record.reverse_thrust,
+                afterburner: record.afterburner,
+            },
+            fuel: FuelComponent {
+                fuel: record.fuel,
+                maximum_fuel: record.fuel,
+                jump_fuel: record.jump_fuel,
+            },
+            energy: EnergyComponent {
+                energy: record.energy,
+                maximum_energy: record.energy,
+                regeneration: record.energy_regeneration,
+            },
+            cargo: CargoComponent {
+                capacity: record.cargo_capacity,
+            },
+            health: HealthComponent::new(
+                record.hull,
+                record.shield,
+                record.shield_regeneration,
+                record.shield_regeneration_energy,
+            ),
+            weapons: WeaponsComponent::new(record.weapons.iter().cloned()),
+            damaging_collidable: CollidableComponent::<Damaging>::new(
+                record.collision_shape.to_shape(position, rotation),
+            ),
+            physical_collision_marker: CollisionMarker::<Physical>::new(
+                record.collision_shape.to_shape(position, rotation),
+            ),
+            physical_collidable: CollidableComponent::<Physical>::new(
+                record.collision_shape.to_shape(position, rotation),
+            ),
+            mass: MassComponent {
+                mass: record.mass.unwrap_or(DEFAULT_SHIP_MASS),
+            },
+            ship_type: ShipTypeComponent {
+                ship_reference: record.ship_reference.clone(),
+            },
         })
     }
 }
@@ -0,0 +1,108 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::configuration_file_structures::reference_types::{
+    PlanetoidReference, ShipReference, StarSystemReference,
+};
+
+// A ship which outlives a server restart, stored by definition reference so definition changes are picked up on load
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SavedShipRecord {
+    pub ship_reference: ShipReference,
+    pub owner: Option<String>, // Identifier of the player profile flying this ship, if any
+    pub star_system: Option<StarSystemReference>,
+    pub position_x: f64,
+    pub position_y: f64,
+    pub velocity_x: f32,
+    pub velocity_y: f32,
+    pub rotation: f32,
+    pub angular_velocity: f32,
+    #[serde(default)]
+    pub landed_on: Option<PlanetoidReference>, // Players saved while landed come back on this planetoid instead of in flight
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct WorldSaveFile {
+    #[serde(default)]
+    pub ships: Vec<SavedShipRecord>,
+}
+
+impl WorldSaveFile {
+    // A missing save file is not an error, it just means the world starts fresh
+    pub async fn load(path: &Path) -> Result<Option<Self>, ()> {
+        let contents = match tokio::fs::read(path).await {
+            Ok(has) => has,
+            Err(read_error) => {
+                if read_error.kind() == std::io::ErrorKind::NotFound {
+                    return Ok(None);
+                }
+
+                tracing::error!(
+                    "Could not read world save file {}: {}",
+                    path.to_string_lossy(),
+                    read_error
+                );
+                return Err(());
+            }
+        };
+
+        match serde_json::de::from_slice::<Self>(&contents) {
+            Ok(parsed) => Ok(Some(parsed)),
+            Err(invalid_format) => {
+                tracing::error!(
+                    "World save file {} is invalid format: {}",
+                    path.to_string_lossy(),
+                    invalid_format
+                );
+                Err(())
+            }
+        }
+    }
+
+    // Blocking, since this is only done by the simulation thread once it has stopped ticking
+    pub fn save(&self, path: &Path) -> Result<(), ()> {
+        // Serialization of these structures can only fail on a programming error
+        let serialized = serde_json::ser::to_vec_pretty(self).unwrap();
+
+        // Write to a temporary file first so a crash mid-write never leaves a truncated save behind
+        let temporary_path = path.with_extension("tmp");
+
+        if let Err(write_error) = std::fs::write(&temporary_path, serialized) {
+            tracing::error!(
+                "Could not write world save file {}: {}",
+                temporary_path.to_string_lossy(),
+                write_error
+            );
+            return Err(());
+        }
+
+        match std::fs::rename(&temporary_path, path) {
+            Ok(()) => Ok(()),
+            Err(rename_error) => {
+                tracing::error!(
+                    "Could not replace world save file {}: {}",
+                    path.to_string_lossy(),
+                    rename_error
+                );
+                Err(())
+            }
+        }
+    }
+}
@@ -0,0 +1,42 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::Deserialize;
+
+use super::reference_types::FactionReference;
+
+// A group which planetoids and NPC ships can belong to, and which players build up a reputation with
+#[derive(Deserialize)]
+pub struct FactionRecord {
+    pub faction_reference: FactionReference,
+    pub faction_display_name: String,
+    #[serde(default)]
+    pub starting_reputation: i32, // Reputation of players who have never dealt with the faction
+    #[serde(default)]
+    pub minimum_landing_reputation: Option<i32>, // Players below this are refused landing on the faction's planetoids, everyone may land without one
+    #[serde(default)]
+    pub ally_reputation: Option<i32>, // Players at or above this see the faction's cloaked ships, no one does without one
+    #[serde(default)]
+    pub ship_destroyed_reputation: i32, // Change for players who destroy one of the faction's ships, usually negative
+    #[serde(default)]
+    pub mission_completed_reputation: i32, // Change for players who complete a mission flown for the faction
+}
+
+#[derive(Deserialize)]
+pub struct FactionConfigurationFile {
+    pub definitions: Vec<FactionRecord>,
+}
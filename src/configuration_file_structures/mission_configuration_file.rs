@@ -0,0 +1,60 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::Deserialize;
+
+use super::reference_types::{
+    CommodityReference, FactionReference, MissionReference, PlanetoidReference,
+};
+
+#[derive(Deserialize)]
+pub struct MissionCargoRecord {
+    pub commodity: CommodityReference,
+    pub quantity: u32, // Tons, which take up the player's cargo space until the mission ends
+}
+
+#[derive(Deserialize, Default)]
+pub struct MissionOfferConditions {
+    #[serde(default)]
+    pub completed_missions: Vec<MissionReference>, // Missions which have to be completed before this one is offered
+    #[serde(default)]
+    pub repeatable: bool, // Offered again once completed
+}
+
+// Offered to players landed on the origin, and completed by landing on the destination
+#[derive(Deserialize)]
+pub struct MissionRecord {
+    pub mission_reference: MissionReference,
+    pub mission_display_name: String,
+    pub description: String,
+    pub origin: PlanetoidReference,
+    pub destination: PlanetoidReference,
+    #[serde(default)]
+    pub cargo: Option<MissionCargoRecord>,
+    pub reward: u32, // Credits
+    #[serde(default)]
+    pub faction: Option<FactionReference>, // Completing the mission changes the player's reputation with this faction
+    #[serde(default)]
+    pub deadline_seconds: Option<u64>, // Time from accepting to landing on the destination before the mission fails
+    #[serde(default)]
+    pub offer_conditions: MissionOfferConditions,
+}
+
+#[derive(Deserialize)]
+pub struct MissionConfigurationFile {
+    pub definitions: Vec<MissionRecord>,
+}
@@ -16,6 +16,14 @@
 */
 
 pub mod asset_definition_file;
+pub mod commodity_configuration_file;
+pub mod faction_configuration_file;
 pub mod load_order_file;
+pub mod mission_configuration_file;
+pub mod outfit_configuration_file;
 pub mod planetoid_configuration_file;
 pub mod reference_types;
+pub mod server_configuration_file;
+pub mod ship_configuration_file;
+pub mod star_system_configuration_file;
+pub mod weapon_configuration_file;
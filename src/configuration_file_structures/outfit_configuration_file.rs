@@ -0,0 +1,48 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::Deserialize;
+
+use super::reference_types::{OutfitReference, WeaponReference};
+
+// Outfits add their stats on top of the ship they are fitted to
+#[derive(Deserialize)]
+pub struct OutfitRecord {
+    pub outfit_reference: OutfitReference,
+    pub outfit_display_name: String,
+    pub price: u32,
+    pub mass: f32, // Tons of the ship's outfit space taken up
+    #[serde(default)]
+    pub weapon: Option<WeaponReference>, // Mounting a weapon also takes up one of the ship's weapon slots
+    #[serde(default)]
+    pub thrust: f32,
+    #[serde(default)]
+    pub maximum_speed: f32,
+    #[serde(default)]
+    pub turn_rate: f32, // Radians per second
+    #[serde(default)]
+    pub shield: f32,
+    #[serde(default)]
+    pub shield_regeneration: f32, // Shield points per second
+    #[serde(default)]
+    pub cargo_capacity: u32, // Tons
+}
+
+#[derive(Deserialize)]
+pub struct OutfitConfigurationFile {
+    pub definitions: Vec<OutfitRecord>,
+}
@@ -21,7 +21,10 @@ use crate::backend::configuration_file_loaders::definition_caches::list_required
 
 use super::{
     asset_definition_file::AssetType,
-    reference_types::{AssetReference, PlanetoidReference},
+    reference_types::{
+        AssetReference, CommodityReference, FactionReference, OutfitReference, PlanetoidReference,
+        ShipReference,
+    },
 };
 
 #[derive(Deserialize)]
@@ -30,7 +33,6 @@ pub struct PlanetoidMayBeLandedOn {
     pub backdrop_image_asset: AssetReference,
     pub text_description_asset: AssetReference,
     pub features: Option<PlanetoidFeatures>,
-    pub opinion: Option<String>, // Eventually will describe the "politics" of a planet and will decide if you are allowed to land, default to yes
 }
 
 impl ListRequiredAssets for PlanetoidMayBeLandedOn {
@@ -44,7 +46,21 @@ impl ListRequiredAssets for PlanetoidMayBeLandedOn {
 
 #[derive(Deserialize)]
 pub struct PlanetoidFeatures {
-    // Eventually should be string identifiers for ship features, like pre-defined shipyards, outfitters, bar, BBS, etc which can be modified by quests or other events and are potentially reusable
+    // Eventually should also hold string identifiers for ship features, like a bar, BBS, etc which can be modified by quests or other events and are potentially reusable
+    #[serde(default)]
+    pub market: Vec<MarketListingRecord>,
+    #[serde(default)]
+    pub shipyard: Vec<ShipReference>, // Ships for sale
+    #[serde(default)]
+    pub outfitter: Vec<OutfitReference>, // Outfits for sale
+}
+
+#[derive(Deserialize)]
+pub struct MarketListingRecord {
+    pub commodity: CommodityReference,
+    pub buy_price: u32,  // Credits per ton the player pays
+    pub sell_price: u32, // Credits per ton the player is paid
+    pub supply: u32,     // Tons in stock when the server starts
 }
 
 #[derive(Deserialize)]
@@ -56,6 +72,10 @@ pub struct PlanetoidRecord {
     pub x: f64,
     pub y: f64,
     pub may_be_landed_on: Option<PlanetoidMayBeLandedOn>,
+    #[serde(default)]
+    pub collision_radius: Option<f32>, // Planetoids with one are solid and bounce ships off, others can be flown over
+    #[serde(default)]
+    pub faction: Option<FactionReference>, // Decides who is allowed to land, planetoids without one welcome everyone
 }
 
 impl ListRequiredAssets for PlanetoidRecord {
@@ -28,3 +28,24 @@ pub type AssetIndexReference = u64;
 
 // Id type for objects
 pub type ObjectId = u64;
+
+// Reference to a ship
+pub type ShipReference = String;
+
+// Reference to a weapon
+pub type WeaponReference = String;
+
+// Reference to a star system
+pub type StarSystemReference = String;
+
+// Reference to a faction
+pub type FactionReference = String;
+
+// Reference to a tradeable commodity
+pub type CommodityReference = String;
+
+// Reference to a ship outfit
+pub type OutfitReference = String;
+
+// Reference to a mission
+pub type MissionReference = String;
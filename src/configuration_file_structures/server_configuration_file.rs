@@ -0,0 +1,318 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::Deserialize;
+
+use crate::connectivity::view_layers::ViewLayers;
+
+// Anything faster than this is almost certainly a typo, and would spin the simulation thread for no benefit
+const TICK_RATE_LIMIT: u32 = 1000;
+
+// Anything smaller would disconnect players over a single busy snapshot
+const MINIMUM_QUEUED_BYTES: usize = 64 * 1024;
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfiguration {
+    pub certificate_path: PathBuf,
+    pub private_key_path: PathBuf,
+}
+
+// How far from the center of a viewport each view layer is shown, layers without one are shown as far as the viewport reaches
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(default, deny_unknown_fields)]
+pub struct ViewLayerRadii {
+    pub background: Option<f64>,
+    pub planetoids: Option<f64>,
+    pub ships: Option<f64>,
+    pub weapons: Option<f64>,
+}
+
+impl Default for ViewLayerRadii {
+    fn default() -> Self {
+        Self {
+            background: None,
+            planetoids: None,
+            ships: None,
+            weapons: Some(3000.0), // Projectiles are numerous and short lived, so are only worth sending close by
+        }
+    }
+}
+
+impl ViewLayerRadii {
+    pub fn radius_of(&self, layer: ViewLayers) -> Option<f64> {
+        match layer {
+            ViewLayers::Background => self.background,
+            ViewLayers::Planetoids => self.planetoids,
+            ViewLayers::Ships => self.ships,
+            ViewLayers::Weapons => self.weapons,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfigurationFile {
+    pub bind_address: IpAddr,
+    pub port: u16,
+    pub tls: Option<TlsConfiguration>,
+    pub minimum_tick_rate: u32, // Ticks per second below which the simulation slows down rather than taking larger steps
+    pub maximum_tick_rate: u32, // Ticks per second the simulation will never run faster than
+    pub viewport_radius: f64,
+    pub view_layer_radii: ViewLayerRadii,
+    pub maximum_snapshot_objects: usize, // Objects created or updated in one snapshot, anything more waits for the following ticks
+    pub maximum_queued_bytes: usize, // Encoded size of unsent messages a player may fall behind by before they are disconnected
+    pub session_timeout_seconds: u64,
+    pub authorization_timeout_seconds: u64,
+}
+
+impl Default for ServerConfigurationFile {
+    fn default() -> Self {
+        Self {
+            bind_address: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            port: 2718,
+            tls: None,
+            minimum_tick_rate: 20,
+            maximum_tick_rate: 60,
+            viewport_radius: 6000.0,
+            view_layer_radii: ViewLayerRadii::default(),
+            maximum_snapshot_objects: 1000,
+            maximum_queued_bytes: 4 * 1024 * 1024,
+            session_timeout_seconds: 15,
+            authorization_timeout_seconds: 5,
+        }
+    }
+}
+
+impl ServerConfigurationFile {
+    pub async fn load(path: &Path) -> Result<Self, ()> {
+        let contents = match tokio::fs::read(path).await {
+            Ok(has) => has,
+            Err(read_error) => {
+                tracing::error!(
+                    "Could not read server configuration file {}: {}",
+                    path.to_string_lossy(),
+                    read_error
+                );
+                return Err(());
+            }
+        };
+
+        match serde_json::de::from_slice::<Self>(&contents) {
+            Ok(parsed) => Ok(parsed),
+            Err(invalid_format) => {
+                tracing::error!(
+                    "Server configuration file {} is invalid: {}",
+                    path.to_string_lossy(),
+                    invalid_format
+                );
+                Err(())
+            }
+        }
+    }
+
+    // Reports every problem at once rather than making the operator fix them one restart at a time
+    pub fn validate(&self) -> Result<(), ()> {
+        let mut invalid = false;
+
+        if self.minimum_tick_rate == 0 {
+            tracing::error!("Server configuration minimum_tick_rate must be at least 1");
+            invalid = true;
+        }
+
+        if self.maximum_tick_rate < self.minimum_tick_rate {
+            tracing::error!(
+                "Server configuration maximum_tick_rate {} is lower than minimum_tick_rate {}",
+                self.maximum_tick_rate,
+                self.minimum_tick_rate
+            );
+            invalid = true;
+        }
+
+        if self.maximum_tick_rate > TICK_RATE_LIMIT {
+            tracing::error!(
+                "Server configuration maximum_tick_rate {} is above the limit of {}",
+                self.maximum_tick_rate,
+                TICK_RATE_LIMIT
+            );
+            invalid = true;
+        }
+
+        if !self.viewport_radius.is_finite() || self.viewport_radius <= 0.0 {
+            tracing::error!(
+                "Server configuration viewport_radius {} must be a positive number",
+                self.viewport_radius
+            );
+            invalid = true;
+        }
+
+        for layer in [
+            ViewLayers::Background,
+            ViewLayers::Planetoids,
+            ViewLayers::Ships,
+            ViewLayers::Weapons,
+        ] {
+            let radius = match self.view_layer_radii.radius_of(layer) {
+                Some(has) => has,
+                None => continue,
+            };
+
+            // Nothing beyond the viewport is ever seen, so a larger layer radius is a mistake rather than a request
+            if !radius.is_finite() || radius <= 0.0 || radius > self.viewport_radius {
+                tracing::error!(
+                    "Server configuration view layer radius {} for {:?} must be positive and no larger than viewport_radius",
+                    radius,
+                    layer
+                );
+                invalid = true;
+            }
+        }
+
+        if self.maximum_snapshot_objects == 0 {
+            tracing::error!("Server configuration maximum_snapshot_objects must be at least 1");
+            invalid = true;
+        }
+
+        // Smaller than this and a single busy snapshot would disconnect a player who is keeping up fine
+        if self.maximum_queued_bytes < MINIMUM_QUEUED_BYTES {
+            tracing::error!(
+                "Server configuration maximum_queued_bytes {} is below the minimum of {}",
+                self.maximum_queued_bytes,
+                MINIMUM_QUEUED_BYTES
+            );
+            invalid = true;
+        }
+
+        if self.session_timeout_seconds == 0 {
+            tracing::error!("Server configuration session_timeout_seconds must be at least 1");
+            invalid = true;
+        }
+
+        if self.authorization_timeout_seconds == 0 {
+            tracing::error!(
+                "Server configuration authorization_timeout_seconds must be at least 1"
+            );
+            invalid = true;
+        }
+
+        if let Some(tls) = &self.tls {
+            if !tls.certificate_path.is_file() {
+                tracing::error!(
+                    "TLS certificate file {} does not exist",
+                    tls.certificate_path.to_string_lossy()
+                );
+                invalid = true;
+            }
+
+            if !tls.private_key_path.is_file() {
+                tracing::error!(
+                    "TLS private key file {} does not exist",
+                    tls.private_key_path.to_string_lossy()
+                );
+                invalid = true;
+            }
+        }
+
+        if invalid { Err(()) } else { Ok(()) }
+    }
+
+    pub fn get_socket_address(&self) -> SocketAddr {
+        SocketAddr::new(self.bind_address, self.port)
+    }
+
+    // The shortest a tick may take, which is the inverse of the fastest tick rate
+    pub fn get_minimum_tick_duration(&self) -> Duration {
+        Duration::from_secs(1) / self.maximum_tick_rate
+    }
+
+    // The longest a tick may be simulated as, which is the inverse of the slowest tick rate
+    pub fn get_maximum_tick_duration(&self) -> Duration {
+        Duration::from_secs(1) / self.minimum_tick_rate
+    }
+
+    pub fn get_session_timeout(&self) -> Duration {
+        Duration::from_secs(self.session_timeout_seconds)
+    }
+
+    pub fn get_authorization_timeout(&self) -> Duration {
+        Duration::from_secs(self.authorization_timeout_seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::ServerConfigurationFile;
+
+    #[test]
+    fn defaults_are_valid() {
+        let defaults = ServerConfigurationFile::default();
+
+        assert!(defaults.validate().is_ok());
+        assert_eq!(defaults.get_socket_address().port(), 2718);
+    }
+
+    #[test]
+    fn files_only_need_the_settings_they_change() {
+        let configuration: ServerConfigurationFile = serde_json::from_str(
+            r#"{ "port": 8080, "minimum_tick_rate": 10, "maximum_tick_rate": 50 }"#,
+        )
+        .unwrap();
+
+        assert_eq!(configuration.port, 8080);
+        assert_eq!(configuration.session_timeout_seconds, 15);
+        assert_eq!(configuration.get_minimum_tick_duration(), Duration::from_millis(20));
+        assert_eq!(configuration.get_maximum_tick_duration(), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn misspelled_settings_are_rejected() {
+        assert!(serde_json::from_str::<ServerConfigurationFile>(r#"{ "prot": 8080 }"#).is_err());
+    }
+
+    #[test]
+    fn nonsensical_settings_fail_validation() {
+        let inverted_tick_rates = ServerConfigurationFile {
+            minimum_tick_rate: 60,
+            maximum_tick_rate: 20,
+            ..Default::default()
+        };
+        assert!(inverted_tick_rates.validate().is_err());
+
+        let no_viewport = ServerConfigurationFile {
+            viewport_radius: -1.0,
+            ..Default::default()
+        };
+        assert!(no_viewport.validate().is_err());
+
+        let missing_certificate = ServerConfigurationFile {
+            tls: Some(super::TlsConfiguration {
+                certificate_path: "/nonexistent/certificate.pem".into(),
+                private_key_path: "/nonexistent/key.pem".into(),
+            }),
+            ..Default::default()
+        };
+        assert!(missing_certificate.validate().is_err());
+    }
+}
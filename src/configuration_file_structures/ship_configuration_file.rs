@@ -0,0 +1,127 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::Deserialize;
+
+use crate::{
+    backend::{
+        configuration_file_loaders::definition_caches::list_required_assets::ListRequiredAssets,
+        shape::{CircleData, RoundedTubeData, Shape},
+    },
+    shared_types::{Coordinates, Distance, Radius, Rotation},
+};
+
+use super::{
+    asset_definition_file::AssetType,
+    reference_types::{AssetReference, ShipReference, WeaponReference},
+};
+
+#[derive(Clone, Deserialize)]
+pub enum ShipCollisionShape {
+    Circle { radius: f32 },
+    RoundedTube { length: f32, radius: f32 },
+}
+
+impl ShipCollisionShape {
+    pub fn to_shape(&self, position: Coordinates, rotation: Rotation) -> Shape {
+        match self {
+            ShipCollisionShape::Circle { radius } => Shape::Circle(CircleData {
+                location: position,
+                radius: Radius::new(*radius as f64),
+            }),
+            ShipCollisionShape::RoundedTube { length, radius } => {
+                Shape::RoundedTube(RoundedTubeData {
+                    center: position,
+                    rotation,
+                    length: Distance::new(*length as f64),
+                    radius: Radius::new(*radius as f64),
+                })
+            }
+        }
+    }
+}
+
+// What holding backward does for a ship
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub enum ReverseThrustMode {
+    #[default]
+    RetroBurn, // Turns the ship to face against its velocity, ready to thrust to a stop
+    Brake,     // Slows the ship down at its own thrust without turning it
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct AfterburnerRecord {
+    pub thrust: f32,        // Used instead of the ship's own thrust while burning
+    pub maximum_speed: f32, // Used instead of the ship's own maximum speed while burning
+    pub fuel_per_second: f32,
+}
+
+#[derive(Deserialize)]
+pub struct ShipRecord {
+    pub ship_reference: ShipReference,
+    pub ship_display_name: String,
+    pub display_asset: AssetReference,
+    pub display_radius: f32,
+    pub maximum_speed: f32,
+    pub thrust: f32,
+    pub turn_rate: f32, // Radians per second
+    #[serde(default)]
+    pub reverse_thrust: ReverseThrustMode,
+    #[serde(default)]
+    pub afterburner: Option<AfterburnerRecord>,
+    #[serde(default)]
+    pub fuel: f32, // Fuel capacity, ships are built with a full tank
+    #[serde(default)]
+    pub jump_fuel: f32, // Fuel used by each hyperspace jump
+    #[serde(default)]
+    pub energy: f32, // Energy capacity, ships are built fully charged
+    #[serde(default)]
+    pub energy_regeneration: f32, // Energy points per second
+    #[serde(default)]
+    pub cargo_capacity: u32, // Tons
+    pub collision_shape: ShipCollisionShape,
+    #[serde(default)]
+    pub mass: Option<f32>, // How hard the ship is to push around in collisions, relative to other ships
+    pub hull: f32,
+    #[serde(default)]
+    pub shield: f32,
+    #[serde(default)]
+    pub shield_regeneration: f32, // Shield points per second
+    #[serde(default)]
+    pub shield_regeneration_energy: f32, // Energy spent per shield point regenerated
+    #[serde(default)]
+    pub weapons: Vec<WeaponReference>,
+    #[serde(default)]
+    pub price: u32, // Credits a shipyard charges for the ship
+    #[serde(default)]
+    pub outfit_space: f32, // Tons of outfits which can be fitted
+    #[serde(default)]
+    pub weapon_slots: u32, // Weapon outfits which can be mounted on top of the built in weapons
+    #[serde(default)]
+    pub player_starting_ship: bool, // Ship given to new players, the first loaded ship is used if no ship is marked
+}
+
+impl ListRequiredAssets for ShipRecord {
+    fn get_required_asset_list(&self) -> Vec<(&AssetReference, AssetType)> {
+        vec![(&self.display_asset, AssetType::Image)]
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ShipConfigurationFile {
+    pub definitions: Vec<ShipRecord>,
+}
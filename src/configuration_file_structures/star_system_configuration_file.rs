@@ -0,0 +1,62 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::Deserialize;
+
+use super::reference_types::{FactionReference, PlanetoidReference, ShipReference, StarSystemReference};
+
+#[derive(Deserialize)]
+pub struct StarSystemRecord {
+    pub star_system_reference: StarSystemReference,
+    pub star_system_display_name: String,
+    pub map_x: f64, // Position on the galaxy map, used for the direction ships leave and arrive in when jumping
+    pub map_y: f64,
+    #[serde(default)]
+    pub planetoids: Vec<PlanetoidReference>,
+    #[serde(default)]
+    pub hyperlanes: Vec<StarSystemReference>, // Hyperlanes may be listed on either or both ends, and are always travelable both ways
+    #[serde(default)]
+    pub player_starting_system: bool,
+    #[serde(default)]
+    pub traffic: Vec<TrafficRecord>,
+}
+
+// Where traffic ships appear when they are spawned
+#[derive(Deserialize)]
+pub enum TrafficSpawnLocation {
+    Planetoids, // Taking off from a random planetoid in the system
+    SystemEdge { distance: f64 }, // Arriving from hyperspace this far out from the center of the system
+}
+
+// A kind of NPC traffic which is kept flowing through a star system
+#[derive(Deserialize)]
+pub struct TrafficRecord {
+    pub ships: Vec<ShipReference>, // One is picked at random for each ship spawned
+    #[serde(default)]
+    pub faction: Option<FactionReference>,
+    #[serde(default)]
+    pub cloaked: bool, // Hidden from players, except those the faction counts as allies
+    pub spawn_interval_seconds: f64, // Simulation time between spawns while below the population cap
+    pub maximum_population: usize,
+    pub spawn_location: TrafficSpawnLocation,
+    pub lifetime_seconds: f64, // Ships that have not landed by then are removed, making room for new traffic
+}
+
+#[derive(Deserialize)]
+pub struct StarSystemConfigurationFile {
+    pub definitions: Vec<StarSystemRecord>,
+}
@@ -0,0 +1,53 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::Deserialize;
+
+use crate::backend::configuration_file_loaders::definition_caches::list_required_assets::ListRequiredAssets;
+
+use super::{
+    asset_definition_file::AssetType,
+    reference_types::{AssetReference, WeaponReference},
+};
+
+#[derive(Deserialize)]
+pub struct WeaponRecord {
+    pub weapon_reference: WeaponReference,
+    pub projectile_asset: AssetReference,
+    pub projectile_display_radius: f32,
+    pub projectile_collision_radius: f32,
+    pub projectile_speed: f32,
+    pub projectile_lifetime: f32, // Seconds
+    pub reload_time: f32,         // Seconds
+    pub shield_damage: f32,
+    pub hull_damage: f32,
+    #[serde(default)]
+    pub energy_cost: f32, // Energy spent per shot
+    #[serde(default)]
+    pub turret: bool, // Fires at the ship's target rather than straight ahead, when it has one
+}
+
+impl ListRequiredAssets for WeaponRecord {
+    fn get_required_asset_list(&self) -> Vec<(&AssetReference, AssetType)> {
+        vec![(&self.projectile_asset, AssetType::Image)]
+    }
+}
+
+#[derive(Deserialize)]
+pub struct WeaponConfigurationFile {
+    pub definitions: Vec<WeaponRecord>,
+}
@@ -18,6 +18,8 @@
 use serde::Deserialize;
 use ts_rs::TS;
 
+use crate::configuration_file_structures::reference_types::ObjectId;
+
 #[derive(Clone, Deserialize, Debug, TS)]
 pub enum ControlInput {
     Forward,
@@ -25,6 +27,9 @@ pub enum ControlInput {
     Left,
     Right,
     Fire,
+    Land,
+    Afterburner,
+    CycleTarget,
 }
 
 pub type ControlInputStatus = bool;
@@ -42,4 +47,31 @@ pub enum ClientServerMessage {
         pressed: ControlInputStatus,
     },
     Refresh,
+    TakeOff,
+    Jump {
+        star_system: String,
+    },
+    BuyCommodity {
+        commodity: String,
+        quantity: u32,
+    },
+    SellCommodity {
+        commodity: String,
+        quantity: u32,
+    },
+    BuyShip {
+        ship: String,
+    },
+    BuyOutfit {
+        outfit: String,
+    },
+    SellOutfit {
+        outfit: String,
+    },
+    AcceptMission {
+        mission: String,
+    },
+    SelectTarget {
+        id: Option<ObjectId>, // Clears the target when missing
+    },
 }
@@ -14,6 +14,8 @@
     You should have received a copy of the GNU General Public License
     along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
 */
+use std::collections::{HashMap, HashSet};
+
 use serde::Serialize;
 use ts_rs::TS;
 
@@ -21,6 +23,12 @@ use crate::configuration_file_structures::reference_types::{AssetIndexReference,
 
 use super::view_layers::ViewLayers;
 
+#[derive(Serialize, Debug, TS)]
+pub struct PositionMessage {
+    pub x: f64,
+    pub y: f64,
+}
+
 #[derive(Serialize, Debug, TS)]
 pub struct VelocityMessage {
     pub vx: f32,
@@ -37,13 +45,21 @@ pub struct AngularVelocityMessage {
     pub angular_velocity: f32,
 }
 
+// Fields are only present when they have changed since the client was last sent this object
 #[derive(Serialize, Debug, TS)]
 #[ts(export)]
 pub struct DynamicObjectUpdateData {
-    pub x: f64,
-    pub y: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub position: Option<PositionMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
     pub rotation: Option<RotationMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
     pub velocity: Option<VelocityMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
     pub angular_velocity: Option<AngularVelocityMessage>,
     pub id: ObjectId,
 }
@@ -62,3 +78,77 @@ pub struct DynamicObjectCreationData {
 pub struct DynamicObjectDestructionData {
     pub id: ObjectId,
 }
+
+// Everything which changed within a viewport over a single tick, applied by the client in the order created, updated, destroyed
+#[derive(Serialize, Debug, Default, TS)]
+#[ts(export)]
+pub struct WorldSnapshotData {
+    pub tick: u64,
+    pub created: Vec<DynamicObjectCreationData>,
+    pub updated: Vec<DynamicObjectUpdateData>,
+    pub destroyed: Vec<DynamicObjectDestructionData>,
+}
+
+impl DynamicObjectUpdateData {
+    // Fields left out of the newer update are still as the older one described them
+    fn absorb(&mut self, newer: DynamicObjectUpdateData) {
+        if newer.position.is_some() {
+            self.position = newer.position;
+        }
+        if newer.rotation.is_some() {
+            self.rotation = newer.rotation;
+        }
+        if newer.velocity.is_some() {
+            self.velocity = newer.velocity;
+        }
+        if newer.angular_velocity.is_some() {
+            self.angular_velocity = newer.angular_velocity;
+        }
+    }
+}
+
+impl WorldSnapshotData {
+    // An object destroyed and then created again cannot be expressed in one snapshot, as the client destroys last
+    pub fn can_absorb(&self, newer: &WorldSnapshotData) -> bool {
+        let destroyed: HashSet<ObjectId> = self.destroyed.iter().map(|gone| gone.id).collect();
+        !newer
+            .created
+            .iter()
+            .any(|created| destroyed.contains(&created.id))
+    }
+
+    // Folds a newer snapshot into this unsent one, so the client ends up in the same state from a single message
+    pub fn absorb(&mut self, newer: WorldSnapshotData) {
+        self.tick = newer.tick;
+
+        for gone in newer.destroyed {
+            let created_here = self.created.iter().any(|created| created.id == gone.id);
+            self.created.retain(|created| created.id != gone.id);
+            self.updated.retain(|updated| updated.id != gone.id);
+
+            // The client never heard of an object which came and went between sends
+            if !created_here {
+                self.destroyed.push(gone);
+            }
+        }
+
+        self.created.extend(newer.created);
+
+        let mut existing: HashMap<ObjectId, usize> = self
+            .updated
+            .iter()
+            .enumerate()
+            .map(|(index, updated)| (updated.id, index))
+            .collect();
+
+        for update in newer.updated {
+            match existing.get(&update.id) {
+                Some(index) => self.updated[*index].absorb(update),
+                None => {
+                    existing.insert(update.id, self.updated.len());
+                    self.updated.push(update);
+                }
+            }
+        }
+    }
+}
@@ -52,10 +52,7 @@ pub async fn send_message(
                 .upgrade()
             {
                 Some(existing_session) => {
-                    let username = existing_session
-                        .player_profile
-                        .authentication
-                        .get_username();
+                    let username = existing_session.player_profile.get_username();
                     chat_service.send_message(&message.message, username);
                     StatusCode::NO_CONTENT
                 }
@@ -15,11 +15,13 @@
     along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use axum::{extract::State, http::StatusCode, Json};
+use axum::{extract::State, http::{header::AUTHORIZATION, HeaderMap, StatusCode}, Json};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
+use crate::connectivity::player_info::credential_policy::CredentialError;
 use crate::connectivity::player_info::player_profiles::PlayerProfiles;
+use crate::connectivity::player_info::player_sessions::PlayerSessions;
 
 #[derive(Serialize, TS)]
 #[ts(export, export_to = "players/")]
@@ -34,7 +36,7 @@ pub async fn create_new_ephemeral_player(
         Ok(created) => {
             Ok(EphemeralPlayerResponse{id: created}.into())
         },
-        Err(()) => Err(StatusCode::CONFLICT),
+        Err(_) => Err(StatusCode::CONFLICT),
     }
 }
 
@@ -48,11 +50,39 @@ pub struct CreateUsernamePlayerRequest {
 pub async fn create_new_username_player(
     State(state): State<PlayerProfiles>,
     Json(request): Json<CreateUsernamePlayerRequest>
-) -> StatusCode {
+) -> Result<StatusCode, (StatusCode, Json<CredentialError>)> {
     match state.create_player_with_username_and_password(&request.username, &request.password).await {
         Ok(_username) => {
-            StatusCode::OK
+            Ok(StatusCode::OK)
         },
-        Err(()) => StatusCode::CONFLICT,
+        Err(credential_error) => Err((credential_error.status_code(), credential_error.into())),
+    }
+}
+
+#[derive(Deserialize, TS)]
+#[ts(export, export_to = "players/")]
+pub struct ChangePasswordRequest {
+    current_password: String,
+    new_password: String
+}
+
+pub async fn change_password(
+    State((player_profiles, player_sessions)): State<(PlayerProfiles, PlayerSessions)>,
+    headers: HeaderMap,
+    Json(request): Json<ChangePasswordRequest>
+) -> Result<StatusCode, (StatusCode, Json<CredentialError>)> {
+    let session = match headers.get(AUTHORIZATION).map(|x| x.to_str()) {
+        Some(Ok(auth_header_string)) => player_sessions.get_session(auth_header_string).await.upgrade(),
+        _ => None,
+    };
+
+    match session {
+        Some(existing_session) => {
+            match player_profiles.change_password(&existing_session.player_profile, &request.current_password, &request.new_password).await {
+                Ok(()) => Ok(StatusCode::NO_CONTENT),
+                Err(credential_error) => Err((credential_error.status_code(), credential_error.into())),
+            }
+        },
+        None => Err((StatusCode::UNAUTHORIZED, CredentialError::NotLoggedIn.into())),
     }
 }
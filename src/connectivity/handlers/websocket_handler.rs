@@ -16,11 +16,11 @@
 */
 
 use crate::connectivity::client_server_message::*;
+use crate::connectivity::outbound_queue::{OutboundAttachment, OutboundReceiver};
 use crate::connectivity::player_info::player_profile::PlayerProfile;
 use crate::connectivity::player_info::player_sessions::PlayerSessions;
-use crate::connectivity::server_client_message::*;
 use crate::utility::cancel_flag::CancelFlag;
-use async_channel::{Receiver, Sender, unbounded};
+use async_channel::{Receiver, Sender, bounded, unbounded};
 use axum::extract::ws::{Message, WebSocket};
 use axum::extract::{ConnectInfo, State, WebSocketUpgrade};
 use axum::response::IntoResponse;
@@ -37,18 +37,20 @@ use tracing::debug;
 use tracing::info;
 
 const WEBSOCKET_TIMEOUT: Duration = Duration::from_secs(1);
-const AUTHORIZATION_TIMEOUT: Duration = Duration::from_secs(5);
+// Messages already taken from the session queue but not yet written to the socket, kept small so backlogs stay where they are budgeted
+const WEBSOCKET_OUTBOUND_BUFFER: usize = 4;
 
 struct WebsocketConnection {
     pub cancel: CancelFlag,
     pub inbound: Receiver<ClientServerMessage>,
-    pub outbound: Sender<ServerClientMessage>,
+    pub outbound: Sender<Bytes>,
     pub remote_address: SocketAddr,
 }
 
 #[derive(Clone)]
 pub struct HandlerState {
     pub sessions: PlayerSessions,
+    pub authorization_timeout: Duration,
 }
 
 pub async fn websocket_handler(
@@ -64,7 +66,7 @@ async fn handle_socket(socket: WebSocket, who: SocketAddr, state: HandlerState)
     let (mut sender, mut receiver) = socket.split();
 
     let (outbound_messages_sender, outbound_messages_receiver) =
-        unbounded::<ServerClientMessage>();
+        bounded::<Bytes>(WEBSOCKET_OUTBOUND_BUFFER);
     let (inbound_messages_sender, inbound_messages_receiver) = unbounded::<ClientServerMessage>();
     let canceled = CancelFlag::default();
 
@@ -93,12 +95,8 @@ async fn handle_socket(socket: WebSocket, who: SocketAddr, state: HandlerState)
             let message_to_send = outbound_messages_receiver.recv().await;
             match message_to_send {
                 Ok(outgoing_message) => {
-                    let mut serialized = Vec::<u8>::new();
-                    // It would be very difficult for a serialization to fail, and would likely be a programming issue on the server
-                    ciborium::into_writer(&outgoing_message, &mut serialized).unwrap();
-
                     if sender
-                        .send(Message::Binary(Bytes::from(serialized)))
+                        .send(Message::Binary(outgoing_message))
                         .await
                         .is_err()
                     {
@@ -216,8 +214,8 @@ async fn wait_for_websocket_login_message(
     connection: WebsocketConnection,
 ) {
     let auth_start = Instant::now();
-    while let Ok(Ok(message)) = timeout(AUTHORIZATION_TIMEOUT, connection.inbound.recv()).await {
-        if auth_start.elapsed() > AUTHORIZATION_TIMEOUT {
+    while let Ok(Ok(message)) = timeout(state.authorization_timeout, connection.inbound.recv()).await {
+        if auth_start.elapsed() > state.authorization_timeout {
             info!(
                 "Websocket authorization timeout exceeded from {}",
                 connection.remote_address
@@ -234,14 +232,19 @@ async fn wait_for_websocket_login_message(
                             connection.remote_address
                         );
 
+                        // Attached before any inbound message is forwarded, so the reply to the client's refresh is queued
+                        let outbound_receiver = valid_session.clone_outbound_receiver();
+                        let attachment = outbound_receiver.attach();
+
                         tokio::task::spawn(channel_forwarding(
                             connection.inbound,
                             valid_session.clone_inbound_sender(),
                             connection.cancel.clone(),
                         ));
 
-                        tokio::task::spawn(channel_forwarding(
-                            valid_session.clone_outbound_receiver(),
+                        tokio::task::spawn(outbound_forwarding(
+                            outbound_receiver,
+                            attachment,
                             connection.outbound,
                             connection.cancel.clone(),
                         ));
@@ -307,3 +310,30 @@ async fn channel_forwarding<T>(
         }
     }
 }
+
+// Hands messages to the socket as fast as it will take them, anything slower backs up in the session queue
+// The session stops queueing snapshots once this returns and drops the attachment
+async fn outbound_forwarding(
+    receiver: OutboundReceiver,
+    _attachment: OutboundAttachment,
+    sender: async_channel::Sender<Bytes>,
+    cancel: CancelFlag,
+) {
+    loop {
+        let val = match receiver.recv().await {
+            Ok(received) => received.encoded(), // We're all good
+            Err(()) => {
+                cancel.cancel();
+                return;
+            } // The session is gone or this connection fell too far behind
+        };
+
+        match sender.send(val).await {
+            Ok(()) => (), // We're all good
+            Err(_e) => {
+                cancel.cancel();
+                return;
+            } // This websocket instance is done
+        }
+    }
+}
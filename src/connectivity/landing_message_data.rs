@@ -0,0 +1,38 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::configuration_file_structures::reference_types::{AssetIndexReference, ObjectId};
+
+#[derive(Serialize, Debug, TS)]
+#[ts(export)]
+pub struct LandedData {
+    pub planetoid_id: ObjectId,
+    pub planetoid_name: String,
+    pub backdrop_image_asset: AssetIndexReference,
+    pub text_description_asset: AssetIndexReference,
+}
+
+#[derive(Serialize, Debug, TS)]
+#[ts(export)]
+pub struct LandingRefusedData {
+    pub planetoid_id: ObjectId,
+    pub planetoid_name: String,
+    pub reason: String,
+}
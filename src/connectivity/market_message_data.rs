@@ -0,0 +1,50 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::configuration_file_structures::reference_types::{CommodityReference, ObjectId};
+
+#[derive(Serialize, Debug, TS)]
+#[ts(export)]
+pub struct MarketListingData {
+    pub commodity_reference: CommodityReference,
+    pub commodity_name: String,
+    pub buy_price: u32,
+    pub sell_price: u32,
+    pub stock: u32,
+}
+
+#[derive(Serialize, Debug, TS)]
+#[ts(export)]
+pub struct CargoData {
+    pub commodity_reference: CommodityReference,
+    pub commodity_name: String,
+    pub quantity: u32,
+}
+
+// The market of the planetoid a player is landed on, along with what they have to trade with
+#[derive(Serialize, Debug, TS)]
+#[ts(export)]
+pub struct MarketData {
+    pub planetoid_id: ObjectId,
+    pub credits: u64,
+    pub cargo_capacity: u32,
+    pub listings: Vec<MarketListingData>,
+    pub cargo: Vec<CargoData>,
+}
@@ -0,0 +1,55 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::configuration_file_structures::reference_types::{MissionReference, ObjectId};
+
+#[derive(Serialize, Debug, TS)]
+#[ts(export)]
+pub struct MissionOfferData {
+    pub mission_reference: MissionReference,
+    pub mission_name: String,
+    pub description: String,
+    pub destination_name: String,
+    pub reward: u32,
+    pub cargo_quantity: u32,
+    pub deadline_seconds: Option<u64>,
+}
+
+#[derive(Serialize, Debug, TS)]
+#[ts(export)]
+pub struct ActiveMissionData {
+    pub mission_reference: MissionReference,
+    pub mission_name: String,
+    pub destination_name: String,
+    pub reward: u32,
+    pub cargo_quantity: u32,
+    pub seconds_remaining: Option<u64>,
+}
+
+// Missions on offer where a player has landed, along with what they have taken on and what just ended
+#[derive(Serialize, Debug, TS)]
+#[ts(export)]
+pub struct MissionsData {
+    pub planetoid_id: ObjectId,
+    pub offered: Vec<MissionOfferData>,
+    pub active: Vec<ActiveMissionData>,
+    pub completed: Vec<String>, // Names of missions completed by this landing
+    pub failed: Vec<String>,    // Names of missions whose deadline passed
+}
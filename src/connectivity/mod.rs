@@ -22,8 +22,16 @@ pub mod client_server_message;
 pub mod controllable_object_message_data;
 pub mod dynamic_object_message_data;
 pub mod handlers;
+pub mod hyperspace_message_data;
+pub mod landing_message_data;
+pub mod market_message_data;
+pub mod mission_message_data;
+pub mod outbound_queue;
 pub mod models;
 pub mod player_info;
 pub mod server_client_message;
 pub mod services;
+pub mod ship_status_message_data;
+pub mod shipyard_message_data;
+pub mod target_message_data;
 pub mod view_layers;
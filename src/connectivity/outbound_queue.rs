@@ -0,0 +1,492 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use tokio::sync::Notify;
+
+use crate::connectivity::dynamic_object_message_data::WorldSnapshotData;
+use crate::connectivity::server_client_message::ServerClientMessage;
+
+// A message waiting for a connection, encoded as it is queued so the queue knows exactly how far behind it is
+pub struct OutboundMessage {
+    pub message: ServerClientMessage,
+    encoded: Bytes,
+}
+
+impl OutboundMessage {
+    fn new(message: ServerClientMessage) -> Self {
+        let mut serialized = Vec::<u8>::new();
+        // It would be very difficult for a serialization to fail, and would likely be a programming issue on the server
+        ciborium::into_writer(&message, &mut serialized).unwrap();
+
+        Self {
+            message,
+            encoded: Bytes::from(serialized),
+        }
+    }
+
+    pub fn encoded(&self) -> Bytes {
+        self.encoded.clone()
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug)]
+pub struct OutboundQueueStatistics {
+    pub queued_bytes: usize,
+    pub queued_messages: usize,
+    pub coalesced_snapshots: usize,
+    pub lagging_disconnects: usize,
+}
+
+impl OutboundQueueStatistics {
+    pub fn add(&mut self, other: OutboundQueueStatistics) {
+        self.queued_bytes += other.queued_bytes;
+        self.queued_messages += other.queued_messages;
+        self.coalesced_snapshots += other.coalesced_snapshots;
+        self.lagging_disconnects += other.lagging_disconnects;
+    }
+}
+
+#[derive(Default)]
+struct OutboundQueueState {
+    messages: VecDeque<OutboundMessage>,
+    statistics: OutboundQueueStatistics,
+    lagging: bool, // The receiving connection fell too far behind and should be dropped
+    senders: usize,
+    receivers: usize,
+    attachments: usize, // Connections currently forwarding, snapshots are only queued while there is one
+}
+
+impl OutboundQueueState {
+    fn push(&mut self, message: OutboundMessage) {
+        self.statistics.queued_bytes += message.encoded.len();
+        self.statistics.queued_messages += 1;
+        self.messages.push_back(message);
+    }
+
+    // Refreshing the viewport resends the whole world to a new connection, so snapshots left unsent are of no use to it
+    // Everything else is only ever sent once and has to wait for the next connection
+    fn discard_snapshots(&mut self) {
+        self.messages
+            .retain(|queued| !matches!(queued.message, ServerClientMessage::WorldSnapshot(_)));
+        self.statistics.queued_bytes = self.messages.iter().map(|x| x.encoded.len()).sum();
+        self.statistics.queued_messages = self.messages.len();
+    }
+}
+
+struct OutboundQueue {
+    state: Mutex<OutboundQueueState>,
+    notify: Notify,
+    maximum_queued_bytes: usize,
+}
+
+// Messages from the simulation to a player, bounded by bytes rather than count since snapshots vary wildly in size
+pub fn outbound_queue(maximum_queued_bytes: usize) -> (OutboundSender, OutboundReceiver) {
+    let queue = Arc::new(OutboundQueue {
+        state: Mutex::new(OutboundQueueState {
+            senders: 1,
+            receivers: 1,
+            ..Default::default()
+        }),
+        notify: Notify::new(),
+        maximum_queued_bytes,
+    });
+
+    (
+        OutboundSender {
+            queue: queue.clone(),
+        },
+        OutboundReceiver { queue },
+    )
+}
+
+pub struct OutboundSender {
+    queue: Arc<OutboundQueue>,
+}
+
+impl OutboundSender {
+    // Never blocks the simulation, a client which can't keep up is disconnected instead
+    pub fn send(&self, message: ServerClientMessage) -> Result<(), ()> {
+        let mut state = self.queue.state.lock().unwrap();
+
+        if state.receivers == 0 {
+            return Err(());
+        }
+
+        let message = match message {
+            // Clients ask for a refresh once they connect, so there is no snapshot worth keeping until then
+            ServerClientMessage::WorldSnapshot(_) if state.attachments == 0 => None,
+            ServerClientMessage::WorldSnapshot(snapshot) => {
+                match Self::coalesce_snapshot(&mut state, snapshot) {
+                    Ok(()) => None,
+                    Err(snapshot) => Some(ServerClientMessage::WorldSnapshot(snapshot)),
+                }
+            }
+            other => Some(other),
+        };
+
+        if let Some(message) = message {
+            state.push(OutboundMessage::new(message));
+        }
+
+        if state.statistics.queued_bytes > self.queue.maximum_queued_bytes {
+            tracing::info!(
+                "Dropping player connection which fell {} bytes behind in {} messages",
+                state.statistics.queued_bytes,
+                state.statistics.queued_messages
+            );
+
+            state.messages.clear();
+            state.statistics.queued_bytes = 0;
+            state.statistics.queued_messages = 0;
+            state.statistics.lagging_disconnects += 1;
+            state.lagging = true;
+            self.queue.notify.notify_one();
+            return Err(());
+        }
+
+        self.queue.notify.notify_one();
+        Ok(())
+    }
+
+    // Merges into the newest unsent snapshot, as long as only status readouts were queued after it
+    fn coalesce_snapshot(
+        state: &mut OutboundQueueState,
+        snapshot: WorldSnapshotData,
+    ) -> Result<(), WorldSnapshotData> {
+        let mut previous = None;
+        for (index, queued) in state.messages.iter().enumerate().rev() {
+            match &queued.message {
+                ServerClientMessage::WorldSnapshot(_) => {
+                    previous = Some(index);
+                    break;
+                }
+                ServerClientMessage::ShipStatus(_) | ServerClientMessage::TargetInfo(_) => {}
+                _ => break,
+            }
+        }
+
+        let index = match previous {
+            Some(index) => index,
+            None => return Err(snapshot),
+        };
+
+        let queued = &mut state.messages[index];
+        let mut merged = match &mut queued.message {
+            ServerClientMessage::WorldSnapshot(older) if older.can_absorb(&snapshot) => {
+                std::mem::take(older)
+            }
+            _ => return Err(snapshot),
+        };
+        merged.absorb(snapshot);
+
+        let replaced_bytes = queued.encoded.len();
+        *queued = OutboundMessage::new(ServerClientMessage::WorldSnapshot(merged));
+        let merged_bytes = queued.encoded.len();
+        state.statistics.queued_bytes =
+            state.statistics.queued_bytes - replaced_bytes + merged_bytes;
+        state.statistics.coalesced_snapshots += 1;
+        Ok(())
+    }
+}
+
+impl Clone for OutboundSender {
+    fn clone(&self) -> Self {
+        self.queue.state.lock().unwrap().senders += 1;
+        Self {
+            queue: self.queue.clone(),
+        }
+    }
+}
+
+impl Drop for OutboundSender {
+    fn drop(&mut self) {
+        let mut state = self.queue.state.lock().unwrap();
+        state.senders -= 1;
+        if state.senders == 0 {
+            // Wake the connection so it can finish sending what's left and close
+            self.queue.notify.notify_one();
+        }
+    }
+}
+
+pub struct OutboundReceiver {
+    queue: Arc<OutboundQueue>,
+}
+
+impl OutboundReceiver {
+    // Fails once the connection has fallen too far behind, or when nothing will ever be sent again
+    pub async fn recv(&self) -> Result<OutboundMessage, ()> {
+        loop {
+            // Created before checking so a send in between still wakes us
+            let notified = self.queue.notify.notified();
+
+            {
+                let mut state = self.queue.state.lock().unwrap();
+
+                if state.lagging {
+                    state.lagging = false;
+                    return Err(());
+                }
+
+                if let Some(message) = Self::pop(&mut state) {
+                    return Ok(message);
+                }
+
+                if state.senders == 0 {
+                    return Err(());
+                }
+            }
+
+            notified.await;
+        }
+    }
+
+    #[cfg(test)]
+    pub fn try_recv(&self) -> Result<OutboundMessage, ()> {
+        let mut state = self.queue.state.lock().unwrap();
+        match Self::pop(&mut state) {
+            Some(message) => Ok(message),
+            None => Err(()),
+        }
+    }
+
+    fn pop(state: &mut OutboundQueueState) -> Option<OutboundMessage> {
+        let message = state.messages.pop_front()?;
+        state.statistics.queued_bytes -= message.encoded.len();
+        state.statistics.queued_messages -= 1;
+        Some(message)
+    }
+
+    // Starts queueing snapshots for a connection until the returned attachment is dropped
+    // A fresh connection owes nothing for one which was dropped for falling behind
+    pub fn attach(&self) -> OutboundAttachment {
+        let mut state = self.queue.state.lock().unwrap();
+        state.attachments += 1;
+        state.lagging = false;
+
+        OutboundAttachment {
+            queue: self.queue.clone(),
+        }
+    }
+
+    pub fn statistics(&self) -> OutboundQueueStatistics {
+        self.queue.state.lock().unwrap().statistics
+    }
+}
+
+impl Clone for OutboundReceiver {
+    fn clone(&self) -> Self {
+        self.queue.state.lock().unwrap().receivers += 1;
+        Self {
+            queue: self.queue.clone(),
+        }
+    }
+}
+
+impl Drop for OutboundReceiver {
+    fn drop(&mut self) {
+        self.queue.state.lock().unwrap().receivers -= 1;
+    }
+}
+
+// Held for as long as a connection is forwarding messages from the queue
+pub struct OutboundAttachment {
+    queue: Arc<OutboundQueue>,
+}
+
+impl Drop for OutboundAttachment {
+    fn drop(&mut self) {
+        let mut state = self.queue.state.lock().unwrap();
+        state.attachments -= 1;
+
+        if state.attachments == 0 {
+            state.discard_snapshots();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectivity::dynamic_object_message_data::*;
+    use crate::connectivity::view_layers::ViewLayers;
+
+    fn created(id: u64) -> DynamicObjectCreationData {
+        DynamicObjectCreationData {
+            id,
+            object_asset: 0,
+            view_layer: ViewLayers::Ships,
+            display_radius: 1.0,
+        }
+    }
+
+    fn moved(id: u64, x: f64) -> DynamicObjectUpdateData {
+        DynamicObjectUpdateData {
+            position: Some(PositionMessage { x, y: 0.0 }),
+            rotation: None,
+            velocity: None,
+            angular_velocity: None,
+            id,
+        }
+    }
+
+    fn snapshot(
+        tick: u64,
+        created: Vec<DynamicObjectCreationData>,
+        updated: Vec<DynamicObjectUpdateData>,
+        destroyed: Vec<u64>,
+    ) -> ServerClientMessage {
+        ServerClientMessage::WorldSnapshot(WorldSnapshotData {
+            tick,
+            created,
+            updated,
+            destroyed: destroyed
+                .into_iter()
+                .map(|id| DynamicObjectDestructionData { id })
+                .collect(),
+        })
+    }
+
+    fn drain(receiver: &OutboundReceiver) -> Vec<ServerClientMessage> {
+        let mut messages = Vec::new();
+        while let Ok(queued) = receiver.try_recv() {
+            messages.push(queued.message);
+        }
+        messages
+    }
+
+    #[test]
+    fn unsent_snapshots_merge_into_the_latest_state() {
+        let (sender, receiver) = outbound_queue(1024 * 1024);
+        let _attachment = receiver.attach();
+
+        sender
+            .send(snapshot(1, vec![], vec![moved(1, 1.0)], vec![]))
+            .unwrap();
+        sender.send(ServerClientMessage::TargetCleared).unwrap();
+        sender
+            .send(snapshot(2, vec![], vec![moved(1, 2.0)], vec![]))
+            .unwrap();
+        sender
+            .send(snapshot(3, vec![], vec![moved(2, 5.0)], vec![]))
+            .unwrap();
+
+        let messages = drain(&receiver);
+        assert_eq!(
+            messages.len(),
+            3,
+            "snapshots separated by anything but status readouts stay apart"
+        );
+
+        let latest = match &messages[2] {
+            ServerClientMessage::WorldSnapshot(latest) => latest,
+            other => panic!("expected a snapshot, got {:?}", other),
+        };
+        assert_eq!(latest.tick, 3);
+        assert_eq!(latest.updated.len(), 2);
+        assert_eq!(latest.updated[0].position.as_ref().unwrap().x, 2.0);
+        assert_eq!(receiver.statistics().coalesced_snapshots, 1);
+        assert_eq!(receiver.statistics().queued_bytes, 0);
+    }
+
+    #[test]
+    fn objects_which_come_and_go_unsent_are_never_mentioned() {
+        let (sender, receiver) = outbound_queue(1024 * 1024);
+        let _attachment = receiver.attach();
+
+        sender
+            .send(snapshot(1, vec![created(7)], vec![moved(7, 0.0)], vec![]))
+            .unwrap();
+        sender.send(snapshot(2, vec![], vec![], vec![7])).unwrap();
+
+        let messages = drain(&receiver);
+        assert_eq!(messages.len(), 1);
+        match &messages[0] {
+            ServerClientMessage::WorldSnapshot(merged) => {
+                assert!(merged.created.is_empty());
+                assert!(merged.updated.is_empty());
+                assert!(merged.destroyed.is_empty());
+            }
+            other => panic!("expected a snapshot, got {:?}", other),
+        }
+
+        // Destroying then recreating can't be folded, since the client applies destruction last
+        sender.send(snapshot(3, vec![], vec![], vec![8])).unwrap();
+        sender
+            .send(snapshot(4, vec![created(8)], vec![moved(8, 0.0)], vec![]))
+            .unwrap();
+        assert_eq!(drain(&receiver).len(), 2);
+    }
+
+    #[test]
+    fn connections_falling_too_far_behind_are_dropped() {
+        let (sender, receiver) = outbound_queue(64);
+        let attachment = receiver.attach();
+
+        assert!(sender.send(ServerClientMessage::TookOff).is_ok());
+        let updates = (0..10).map(|id| moved(id, 0.0)).collect();
+        assert!(sender.send(snapshot(1, vec![], updates, vec![])).is_err());
+
+        let statistics = receiver.statistics();
+        assert_eq!(statistics.queued_bytes, 0);
+        assert_eq!(statistics.lagging_disconnects, 1);
+        assert!(futures::executor::block_on(receiver.recv()).is_err());
+
+        // A replacement connection picks up from whatever is sent next
+        drop(attachment);
+        let _attachment = receiver.attach();
+        sender.send(ServerClientMessage::TookOff).unwrap();
+        assert!(futures::executor::block_on(receiver.recv()).is_ok());
+
+        drop(sender);
+        assert!(futures::executor::block_on(receiver.recv()).is_err());
+    }
+
+    #[test]
+    fn only_snapshots_are_dropped_while_no_connection_is_attached() {
+        let (sender, receiver) = outbound_queue(1024 * 1024);
+
+        let updates = || (0..10).map(|id| moved(id, 0.0)).collect();
+        assert!(sender.send(snapshot(1, vec![], updates(), vec![])).is_ok());
+        sender.send(ServerClientMessage::TookOff).unwrap();
+        assert_eq!(receiver.statistics().queued_messages, 1);
+
+        // Unsent snapshots from a connection which closed are not held against the next one, everything else is
+        let attachment = receiver.attach();
+        sender.send(snapshot(2, vec![], updates(), vec![])).unwrap();
+        assert_eq!(receiver.statistics().queued_messages, 2);
+        drop(attachment);
+
+        let statistics = receiver.statistics();
+        assert_eq!(statistics.queued_messages, 1);
+        assert_eq!(
+            statistics.queued_bytes,
+            OutboundMessage::new(ServerClientMessage::TookOff)
+                .encoded()
+                .len()
+        );
+        assert!(matches!(
+            drain(&receiver)[..],
+            [ServerClientMessage::TookOff]
+        ));
+        assert_eq!(receiver.statistics().queued_bytes, 0);
+    }
+}
@@ -0,0 +1,185 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::sync::OnceLock;
+
+use argon2::{
+    Argon2,
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng},
+};
+use axum::http::StatusCode;
+use serde::Serialize;
+use ts_rs::TS;
+
+const MINIMUM_USERNAME_LENGTH: usize = 3;
+const MAXIMUM_USERNAME_LENGTH: usize = 32;
+const MINIMUM_PASSWORD_LENGTH: usize = 8;
+const MAXIMUM_PASSWORD_LENGTH: usize = 128;
+
+#[derive(Serialize, Debug, PartialEq, TS)]
+#[ts(export, export_to = "players/")]
+#[serde(tag = "error")]
+pub enum CredentialError {
+    UsernameTooShort { minimum_length: usize },
+    UsernameTooLong { maximum_length: usize },
+    UsernameInvalidCharacters,
+    UsernameTaken,
+    PasswordTooShort { minimum_length: usize },
+    PasswordTooLong { maximum_length: usize },
+    IncorrectPassword,
+    NotLoggedIn,
+    NotPasswordProtected,
+    ServerFailure,
+}
+
+impl CredentialError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            CredentialError::UsernameTaken => StatusCode::CONFLICT,
+            CredentialError::IncorrectPassword | CredentialError::NotLoggedIn => {
+                StatusCode::UNAUTHORIZED
+            }
+            CredentialError::ServerFailure => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+pub fn validate_username(username: &str) -> Result<(), CredentialError> {
+    let length = username.chars().count();
+
+    if length < MINIMUM_USERNAME_LENGTH {
+        Err(CredentialError::UsernameTooShort {
+            minimum_length: MINIMUM_USERNAME_LENGTH,
+        })
+    } else if length > MAXIMUM_USERNAME_LENGTH {
+        Err(CredentialError::UsernameTooLong {
+            maximum_length: MAXIMUM_USERNAME_LENGTH,
+        })
+    } else if !username
+        .chars()
+        .all(|x| x.is_ascii_alphanumeric() || x == '_' || x == '-')
+    {
+        Err(CredentialError::UsernameInvalidCharacters)
+    } else {
+        Ok(())
+    }
+}
+
+pub fn validate_password(password: &str) -> Result<(), CredentialError> {
+    let length = password.chars().count();
+
+    if length < MINIMUM_PASSWORD_LENGTH {
+        Err(CredentialError::PasswordTooShort {
+            minimum_length: MINIMUM_PASSWORD_LENGTH,
+        })
+    } else if length > MAXIMUM_PASSWORD_LENGTH {
+        Err(CredentialError::PasswordTooLong {
+            maximum_length: MAXIMUM_PASSWORD_LENGTH,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+// Hashing is deliberately slow, so these should be run on a blocking thread rather than an async task
+pub fn hash_password(password: &str) -> Result<String, ()> {
+    let salt = SaltString::generate(&mut OsRng);
+    match Argon2::default().hash_password(password.as_bytes(), &salt) {
+        Ok(hashed) => Ok(hashed.to_string()),
+        Err(hash_error) => {
+            tracing::error!("Failed to hash password: {}", hash_error);
+            Err(())
+        }
+    }
+}
+
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    match PasswordHash::new(password_hash) {
+        Ok(parsed) => Argon2::default()
+            .verify_password(password.as_bytes(), &parsed)
+            .is_ok(),
+        Err(parse_error) => {
+            tracing::error!("Stored password hash could not be parsed: {}", parse_error);
+            false
+        }
+    }
+}
+
+// Verifying against a throwaway hash for unknown usernames keeps login timing from revealing which usernames exist
+pub fn verify_password_against_nothing(password: &str) {
+    static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+    let dummy_hash =
+        DUMMY_HASH.get_or_init(|| hash_password("dummy password").unwrap_or_default());
+    let _ = verify_password(password, dummy_hash);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usernames_must_be_a_reasonable_length_of_plain_characters() {
+        assert_eq!(validate_username("kestrel_07"), Ok(()));
+        assert_eq!(
+            validate_username("ab"),
+            Err(CredentialError::UsernameTooShort {
+                minimum_length: MINIMUM_USERNAME_LENGTH
+            })
+        );
+        assert_eq!(
+            validate_username(&"a".repeat(MAXIMUM_USERNAME_LENGTH + 1)),
+            Err(CredentialError::UsernameTooLong {
+                maximum_length: MAXIMUM_USERNAME_LENGTH
+            })
+        );
+        assert_eq!(
+            validate_username("space pilot"),
+            Err(CredentialError::UsernameInvalidCharacters)
+        );
+    }
+
+    #[test]
+    fn passwords_must_be_a_reasonable_length() {
+        assert_eq!(validate_password("correct horse"), Ok(()));
+        assert_eq!(
+            validate_password("short"),
+            Err(CredentialError::PasswordTooShort {
+                minimum_length: MINIMUM_PASSWORD_LENGTH
+            })
+        );
+        assert_eq!(
+            validate_password(&"a".repeat(MAXIMUM_PASSWORD_LENGTH + 1)),
+            Err(CredentialError::PasswordTooLong {
+                maximum_length: MAXIMUM_PASSWORD_LENGTH
+            })
+        );
+    }
+
+    #[test]
+    fn hashed_passwords_only_verify_with_the_original_password() {
+        let hashed = hash_password("correct horse").unwrap();
+
+        assert!(!hashed.contains("correct horse"));
+        assert!(verify_password("correct horse", &hashed));
+        assert!(!verify_password("battery staple", &hashed));
+        assert!(!verify_password("correct horse", "not a hash"));
+
+        // Salted, so the same password never hashes the same way twice
+        assert_ne!(hashed, hash_password("correct horse").unwrap());
+    }
+}
@@ -15,7 +15,9 @@
     along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+pub mod credential_policy;
 pub mod player_profile;
+pub mod player_profile_store;
 pub mod player_profiles;
 pub mod player_session;
 pub mod player_sessions;
@@ -15,10 +15,29 @@
     along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::RwLock,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
-use crate::connectivity::player_info::player_session::PlayerSessionTimeout;
+use crate::{
+    configuration_file_structures::{
+        faction_configuration_file::FactionRecord,
+        reference_types::{
+            CommodityReference, FactionReference, MissionReference, OutfitReference, ShipReference,
+        },
+    },
+    connectivity::player_info::{
+        player_profile_store::StoredPlayerProfile, player_session::PlayerSessionTimeout,
+    },
+};
+
+// Credits every new player starts out with
+pub const STARTING_CREDITS: u64 = 10000;
 
 #[derive(Deserialize, PartialEq, TS)]
 #[ts(export, export_to = "players/")]
@@ -27,31 +46,210 @@ pub enum AuthType {
     UsernameAndPassword { username: String, password: String },
 }
 
-impl AuthType {
+// What is kept to check a login, which unlike AuthType never holds a plain-text password
+#[derive(Clone, Deserialize, Serialize)]
+pub enum PlayerCredentials {
+    BasicToken {
+        token: String,
+    },
+    UsernameAndPassword {
+        username: String,
+        password_hash: String,
+    },
+}
+
+impl PlayerCredentials {
+    pub fn get_identifier(&self) -> &str {
+        match self {
+            PlayerCredentials::BasicToken { token } => token,
+            PlayerCredentials::UsernameAndPassword {
+                username,
+                password_hash: _,
+            } => username,
+        }
+    }
+
     pub fn get_username(&self) -> Option<&str> {
         match self {
-            AuthType::BasicToken { token: _ } => None,
-            AuthType::UsernameAndPassword {
+            PlayerCredentials::BasicToken { token: _ } => None,
+            PlayerCredentials::UsernameAndPassword {
                 username,
-                password: _,
-            } => Some(&username),
+                password_hash: _,
+            } => Some(username),
         }
     }
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ActiveMission {
+    pub mission: MissionReference,
+    pub cargo: u32,            // Tons taken up, kept as accepted even if the definition later changes
+    pub deadline: Option<u64>, // Seconds since the Unix epoch, so deadlines carry on across restarts
+}
+
+// What a player owns and has taken on, kept with the profile rather than their ship since landed ships are removed from the world
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PlayerHoldings {
+    pub credits: u64,
+    pub cargo: BTreeMap<CommodityReference, u32>, // Tons of each commodity carried
+    #[serde(default)]
+    pub ship: Option<ShipReference>, // Players who have never bought a ship fly the starting ship
+    #[serde(default)]
+    pub outfits: BTreeMap<OutfitReference, u32>, // Number of each outfit fitted to the ship
+    #[serde(default)]
+    pub active_missions: Vec<ActiveMission>,
+    #[serde(default)]
+    pub completed_missions: BTreeSet<MissionReference>,
+    #[serde(default)]
+    pub reputation: BTreeMap<FactionReference, i32>, // Only factions the player has dealt with, the rest are at their starting reputation
+}
+
+impl Default for PlayerHoldings {
+    fn default() -> Self {
+        Self {
+            credits: STARTING_CREDITS,
+            cargo: BTreeMap::new(),
+            ship: None,
+            outfits: BTreeMap::new(),
+            active_missions: Vec::new(),
+            completed_missions: BTreeSet::new(),
+            reputation: BTreeMap::new(),
+        }
+    }
+}
+
+impl PlayerHoldings {
+    // Mission cargo takes up space in the hold too, but is never for sale
+    pub fn cargo_used(&self) -> u32 {
+        self.cargo.values().sum::<u32>()
+            + self
+                .active_missions
+                .iter()
+                .map(|mission| mission.cargo)
+                .sum::<u32>()
+    }
+
+    pub fn reputation_with(&self, faction: &FactionRecord) -> i32 {
+        match self.reputation.get(&faction.faction_reference) {
+            Some(has) => *has,
+            None => faction.starting_reputation,
+        }
+    }
+
+    pub fn change_reputation(&mut self, faction: &FactionRecord, change: i32) {
+        let reputation = self.reputation_with(faction).saturating_add(change);
+        self.reputation
+            .insert(faction.faction_reference.clone(), reputation);
+    }
+
+    pub fn is_allied_with(&self, faction: &FactionRecord) -> bool {
+        faction
+            .ally_reputation
+            .is_some_and(|minimum| self.reputation_with(faction) >= minimum)
+    }
+
+    // Why the player would be refused landing on a planetoid of this faction, if they would be
+    pub fn landing_refusal(&self, faction: &FactionRecord) -> Option<String> {
+        let minimum = faction.minimum_landing_reputation?;
+
+        if self.reputation_with(faction) >= minimum {
+            return None;
+        }
+
+        Some(format!(
+            "Your reputation with the {} is too low to land here",
+            faction.faction_display_name
+        ))
+    }
+}
+
 pub struct PlayerProfile {
-    pub authentication: AuthType,
+    credentials: RwLock<PlayerCredentials>,
+    holdings: RwLock<PlayerHoldings>,
+    username: Option<String>,
     pub session: PlayerSessionTimeout,
 }
 
 impl PlayerProfile {
-    pub fn new(auth: AuthType) -> Self {
+    pub fn new(credentials: PlayerCredentials, session_timeout: Duration) -> Self {
+        Self::with_holdings(credentials, PlayerHoldings::default(), session_timeout)
+    }
+
+    fn with_holdings(
+        credentials: PlayerCredentials,
+        holdings: PlayerHoldings,
+        session_timeout: Duration,
+    ) -> Self {
         PlayerProfile {
-            authentication: auth,
-            session: PlayerSessionTimeout::new(None),
+            username: credentials.get_username().map(|x| x.to_string()),
+            credentials: RwLock::new(credentials),
+            holdings: RwLock::new(holdings),
+            session: PlayerSessionTimeout::new(None, session_timeout),
+        }
+    }
+
+    pub fn from_stored(stored: StoredPlayerProfile, session_timeout: Duration) -> Self {
+        Self::with_holdings(stored.credentials, stored.holdings, session_timeout)
+    }
+
+    pub fn to_stored(&self) -> StoredPlayerProfile {
+        StoredPlayerProfile {
+            credentials: self.get_credentials(),
+            holdings: self.get_holdings(),
+        }
+    }
+
+    pub fn get_holdings(&self) -> PlayerHoldings {
+        match self.holdings.read() {
+            Ok(holdings) => holdings.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    // Holds the lock for the whole change, so checking a balance and spending it can't be interleaved
+    pub fn modify_holdings<R>(&self, change: impl FnOnce(&mut PlayerHoldings) -> R) -> R {
+        let mut holdings = match self.holdings.write() {
+            Ok(holdings) => holdings,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        change(&mut holdings)
+    }
+
+    pub fn get_credentials(&self) -> PlayerCredentials {
+        match self.credentials.read() {
+            Ok(credentials) => credentials.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    pub fn set_password_hash(&self, new_password_hash: String) -> Result<(), ()> {
+        let mut credentials = match self.credentials.write() {
+            Ok(credentials) => credentials,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        match &mut *credentials {
+            PlayerCredentials::BasicToken { token: _ } => Err(()),
+            PlayerCredentials::UsernameAndPassword {
+                username: _,
+                password_hash,
+            } => {
+                *password_hash = new_password_hash;
+                Ok(())
+            }
         }
     }
 
+    pub fn get_username(&self) -> Option<&str> {
+        self.username.as_deref()
+    }
+
+    // Token players are anonymous and temporary, so only named players are kept between restarts
+    pub fn is_persistent(&self) -> bool {
+        self.username.is_some()
+    }
+
     pub fn cleanup_expired_sessions(&self) -> bool {
         self.session.retain_if_valid()
     }
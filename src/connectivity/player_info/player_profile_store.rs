@@ -0,0 +1,223 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::path::PathBuf;
+
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::connectivity::player_info::player_profile::{PlayerCredentials, PlayerHoldings};
+
+// Everything about a player profile which outlives a server restart
+#[derive(Serialize, Deserialize)]
+pub struct StoredPlayerProfile {
+    pub credentials: PlayerCredentials,
+    #[serde(default)]
+    pub holdings: PlayerHoldings, // Profiles stored before trading existed start out with the starting credits
+}
+
+pub trait PlayerProfileStore: Send + Sync {
+    fn load_profiles(&self) -> BoxFuture<'_, Result<Vec<StoredPlayerProfile>, ()>>;
+
+    fn save_profile(&self, profile: StoredPlayerProfile) -> BoxFuture<'_, Result<(), ()>>;
+}
+
+// Keeps nothing, so every profile is lost when the server stops
+pub struct MemoryPlayerProfileStore;
+
+impl PlayerProfileStore for MemoryPlayerProfileStore {
+    fn load_profiles(&self) -> BoxFuture<'_, Result<Vec<StoredPlayerProfile>, ()>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+
+    fn save_profile(&self, _profile: StoredPlayerProfile) -> BoxFuture<'_, Result<(), ()>> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+// Stores each profile as its own JSON file within a directory
+pub struct FilePlayerProfileStore {
+    directory: PathBuf,
+}
+
+const PROFILE_FILE_EXTENSION: &str = "json";
+
+impl FilePlayerProfileStore {
+    pub async fn new(directory: PathBuf) -> Result<Self, ()> {
+        match tokio::fs::create_dir_all(&directory).await {
+            Ok(()) => Ok(Self { directory }),
+            Err(create_error) => {
+                tracing::error!(
+                    "Could not create player profile directory {}: {}",
+                    directory.to_string_lossy(),
+                    create_error
+                );
+                Err(())
+            }
+        }
+    }
+
+    // Identifiers are user-provided, so they are hex encoded to always be a valid and unique file name
+    fn profile_path(&self, identifier: &str) -> PathBuf {
+        let encoded: String = identifier.bytes().map(|x| format!("{:02x}", x)).collect();
+        self.directory
+            .join(encoded)
+            .with_extension(PROFILE_FILE_EXTENSION)
+    }
+
+    async fn load_profiles_intern(&self) -> Result<Vec<StoredPlayerProfile>, ()> {
+        let mut directory = match tokio::fs::read_dir(&self.directory).await {
+            Ok(has) => has,
+            Err(read_error) => {
+                tracing::error!(
+                    "Could not read player profile directory {}: {}",
+                    self.directory.to_string_lossy(),
+                    read_error
+                );
+                return Err(());
+            }
+        };
+
+        let mut profiles = Vec::new();
+
+        while let Ok(Some(entry)) = directory.next_entry().await {
+            let path = entry.path();
+
+            if path.extension().and_then(|x| x.to_str()) != Some(PROFILE_FILE_EXTENSION) {
+                continue;
+            }
+
+            let contents = match tokio::fs::read(&path).await {
+                Ok(has) => has,
+                Err(read_error) => {
+                    tracing::error!(
+                        "Could not read player profile {}: {}",
+                        path.to_string_lossy(),
+                        read_error
+                    );
+                    return Err(());
+                }
+            };
+
+            match serde_json::de::from_slice::<StoredPlayerProfile>(&contents) {
+                Ok(profile) => profiles.push(profile),
+                Err(invalid_format) => {
+                    tracing::error!(
+                        "Player profile {} is invalid format: {}",
+                        path.to_string_lossy(),
+                        invalid_format
+                    );
+                    return Err(());
+                }
+            }
+        }
+
+        Ok(profiles)
+    }
+
+    async fn save_profile_intern(&self, profile: StoredPlayerProfile) -> Result<(), ()> {
+        let identifier = match profile.credentials.get_username() {
+            Some(has) => has,
+            None => {
+                tracing::warn!("Attempted to store a player profile which has no username");
+                return Err(());
+            }
+        };
+
+        let path = self.profile_path(identifier);
+
+        // Serialization of these structures can only fail on a programming error
+        let serialized = serde_json::ser::to_vec_pretty(&profile).unwrap();
+
+        // Write to a temporary file first so a crash mid-write never leaves a truncated profile behind
+        // Each write gets its own, so two saves of the same profile can't write into each other's file
+        let temporary_path = path.with_extension(format!("{}.tmp", Uuid::new_v4()));
+
+        if let Err(write_error) = tokio::fs::write(&temporary_path, serialized).await {
+            tracing::error!(
+                "Could not write player profile {}: {}",
+                temporary_path.to_string_lossy(),
+                write_error
+            );
+            return Err(());
+        }
+
+        match tokio::fs::rename(&temporary_path, &path).await {
+            Ok(()) => Ok(()),
+            Err(rename_error) => {
+                tracing::error!(
+                    "Could not replace player profile {}: {}",
+                    path.to_string_lossy(),
+                    rename_error
+                );
+                Err(())
+            }
+        }
+    }
+}
+
+impl PlayerProfileStore for FilePlayerProfileStore {
+    fn load_profiles(&self) -> BoxFuture<'_, Result<Vec<StoredPlayerProfile>, ()>> {
+        Box::pin(self.load_profiles_intern())
+    }
+
+    fn save_profile(&self, profile: StoredPlayerProfile) -> BoxFuture<'_, Result<(), ()>> {
+        Box::pin(self.save_profile_intern(profile))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::connectivity::player_info::player_profile::{PlayerCredentials, PlayerHoldings};
+
+    use super::{FilePlayerProfileStore, PlayerProfileStore, StoredPlayerProfile};
+
+    fn profile_with_credits(credits: u64) -> StoredPlayerProfile {
+        StoredPlayerProfile {
+            credentials: PlayerCredentials::UsernameAndPassword {
+                username: "kestrel".to_string(),
+                password_hash: "hash".to_string(),
+            },
+            holdings: PlayerHoldings {
+                credits,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn saving_the_same_profile_at_once_leaves_one_whole_file() {
+        let directory = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        let store = FilePlayerProfileStore::new(directory.clone())
+            .await
+            .unwrap();
+
+        let results =
+            futures::future::join_all((0..8).map(|x| store.save_profile(profile_with_credits(x))))
+                .await;
+        assert!(results.iter().all(|x| x.is_ok()));
+
+        let profiles = store.load_profiles().await.unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 1);
+
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+}
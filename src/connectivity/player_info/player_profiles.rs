@@ -23,25 +23,63 @@ use std::{
     time::Duration,
 };
 
+use async_channel::Receiver;
 use uuid::Uuid;
 
-use crate::connectivity::player_info::player_profile::{AuthType, PlayerProfile};
+use crate::connectivity::player_info::{
+    credential_policy::{
+        CredentialError, hash_password, validate_password, validate_username, verify_password,
+        verify_password_against_nothing,
+    },
+    player_profile::{AuthType, PlayerCredentials, PlayerProfile},
+    player_profile_store::{PlayerProfileStore, StoredPlayerProfile},
+};
 
 #[derive(Clone)]
 pub struct PlayerProfiles {
     player_list: Arc<tokio::sync::RwLock<HashMap<String, Arc<PlayerProfile>>>>,
+    store: Arc<dyn PlayerProfileStore>,
+    save_lock: Arc<tokio::sync::Mutex<()>>,
+    session_timeout: Duration,
 }
 
 impl PlayerProfiles {
-    pub fn new() -> PlayerProfiles {
-        let list = Arc::default();
+    pub async fn load(
+        store: Arc<dyn PlayerProfileStore>,
+        session_timeout: Duration,
+    ) -> Result<PlayerProfiles, ()> {
+        let mut profiles = HashMap::new();
+
+        for stored in store.load_profiles().await? {
+            let profile = PlayerProfile::from_stored(stored, session_timeout);
+            let identifier = profile.get_credentials().get_identifier().to_string();
+
+            match profiles.entry(identifier) {
+                std::collections::hash_map::Entry::Occupied(duplicate) => {
+                    tracing::error!("Duplicated stored player profile {}", duplicate.key());
+                    return Err(());
+                }
+                std::collections::hash_map::Entry::Vacant(empty) => {
+                    empty.insert(Arc::new(profile));
+                }
+            }
+        }
+
+        tracing::info!("Loaded {} stored player profiles", profiles.len());
+
+        let list = Arc::new(tokio::sync::RwLock::new(profiles));
         tokio::spawn(Self::cleanup_profiles_task(Arc::downgrade(&list)));
-        PlayerProfiles { player_list: list }
+        Ok(PlayerProfiles {
+            player_list: list,
+            store,
+            save_lock: Arc::new(tokio::sync::Mutex::new(())),
+            session_timeout,
+        })
     }
 
-    pub async fn create_player_with_token(&self) -> Result<String, ()> {
+    pub async fn create_player_with_token(&self) -> Result<String, CredentialError> {
         let id = Uuid::new_v4();
-        self.create_player(AuthType::BasicToken {
+        self.create_player(PlayerCredentials::BasicToken {
             token: id.to_string(),
         })
         .await
@@ -51,50 +89,185 @@ impl PlayerProfiles {
         &self,
         desired_username: &str,
         desired_password: &str,
-    ) -> Result<String, ()> {
-        self.create_player(AuthType::UsernameAndPassword {
+    ) -> Result<String, CredentialError> {
+        validate_username(desired_username)?;
+        validate_password(desired_password)?;
+
+        // Checked early as well so a taken username doesn't cost a hash
+        if self.player_list.read().await.contains_key(desired_username) {
+            return Err(CredentialError::UsernameTaken);
+        }
+
+        let password_hash = PlayerProfiles::hash_on_blocking_thread(desired_password).await?;
+
+        self.create_player(PlayerCredentials::UsernameAndPassword {
             username: desired_username.to_string(),
-            password: desired_password.to_string(),
+            password_hash,
         })
         .await
     }
 
-    pub async fn create_player(&self, auth: AuthType) -> Result<String, ()> {
-        let identifier = PlayerProfiles::extract_identifier(&auth).to_string();
+    pub async fn create_player(
+        &self,
+        credentials: PlayerCredentials,
+    ) -> Result<String, CredentialError> {
+        let identifier = credentials.get_identifier().to_string();
 
         let mut player_list = self.player_list.write().await;
         match player_list.entry(identifier.to_string()) {
-            std::collections::hash_map::Entry::Occupied(_already_exists) => Err(()),
+            std::collections::hash_map::Entry::Occupied(_already_exists) => {
+                Err(CredentialError::UsernameTaken)
+            }
             std::collections::hash_map::Entry::Vacant(empty) => {
-                empty.insert(Arc::new(PlayerProfile::new(auth)));
+                let profile = PlayerProfile::new(credentials, self.session_timeout);
+
+                if profile.is_persistent()
+                    && let Err(()) = self.write_profile(&profile).await
+                {
+                    return Err(CredentialError::ServerFailure);
+                }
+
+                empty.insert(Arc::new(profile));
                 Ok(identifier)
             }
         }
     }
 
-    fn extract_identifier(auth: &AuthType) -> &str {
-        match &auth {
-            AuthType::BasicToken { token } => token,
-            AuthType::UsernameAndPassword {
-                username,
-                password: _,
-            } => username,
+    pub async fn validate_login_request(&self, auth: &AuthType) -> Result<Arc<PlayerProfile>, ()> {
+        match auth {
+            AuthType::BasicToken { token } => match self.player_list.read().await.get(token) {
+                Some(valid) => match valid.get_credentials() {
+                    PlayerCredentials::BasicToken { token: _ } => Ok(valid.clone()),
+                    PlayerCredentials::UsernameAndPassword {
+                        username: _,
+                        password_hash: _,
+                    } => Err(()),
+                },
+                None => Err(()),
+            },
+            AuthType::UsernameAndPassword { username, password } => {
+                let maybe_profile = self.player_list.read().await.get(username).cloned();
+
+                let password_hash = match maybe_profile.as_ref().map(|x| x.get_credentials()) {
+                    Some(PlayerCredentials::UsernameAndPassword {
+                        username: _,
+                        password_hash,
+                    }) => Some(password_hash),
+                    _ => None,
+                };
+
+                let password = password.clone();
+                let verified = tokio::task::spawn_blocking(move || match password_hash {
+                    Some(hash) => verify_password(&password, &hash),
+                    None => {
+                        verify_password_against_nothing(&password);
+                        false
+                    }
+                })
+                .await;
+
+                match (verified, maybe_profile) {
+                    (Ok(true), Some(valid)) => Ok(valid),
+                    _ => Err(()),
+                }
+            }
         }
     }
 
-    pub async fn validate_login_request(&self, auth: &AuthType) -> Result<Arc<PlayerProfile>, ()> {
-        let identifier = PlayerProfiles::extract_identifier(auth);
-
-        let player_list = self.player_list.read().await;
-        match player_list.get(identifier) {
-            Some(valid) => {
-                if valid.authentication == *auth {
-                    Ok(valid.clone())
-                } else {
-                    Err(())
+    pub async fn change_password(
+        &self,
+        profile: &PlayerProfile,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<(), CredentialError> {
+        let current_hash = match profile.get_credentials() {
+            PlayerCredentials::BasicToken { token: _ } => {
+                return Err(CredentialError::NotPasswordProtected);
+            }
+            PlayerCredentials::UsernameAndPassword {
+                username: _,
+                password_hash,
+            } => password_hash,
+        };
+
+        let current_password = current_password.to_string();
+        match tokio::task::spawn_blocking(move || verify_password(&current_password, &current_hash))
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => return Err(CredentialError::IncorrectPassword),
+            Err(join_error) => {
+                tracing::error!("Password verification task failed: {}", join_error);
+                return Err(CredentialError::ServerFailure);
+            }
+        }
+
+        validate_password(new_password)?;
+        let new_hash = PlayerProfiles::hash_on_blocking_thread(new_password).await?;
+
+        if let Err(()) = profile.set_password_hash(new_hash) {
+            return Err(CredentialError::NotPasswordProtected);
+        }
+
+        match self.write_profile(profile).await {
+            Ok(()) => Ok(()),
+            Err(()) => Err(CredentialError::ServerFailure),
+        }
+    }
+
+    // The simulation queues a profile whenever it commits a change to its holdings
+    // The queued snapshot may predate a password change, so the live profile is written in its place
+    pub async fn save_queued_profiles(&self, queue: Receiver<StoredPlayerProfile>) {
+        while let Ok(queued) = queue.recv().await {
+            let identifier = queued.credentials.get_identifier().to_string();
+            let profile = self.player_list.read().await.get(&identifier).cloned();
+
+            match profile {
+                Some(profile) => {
+                    if let Err(()) = self.write_profile(&profile).await {
+                        tracing::error!("Could not save queued player profile {}", identifier);
+                    }
                 }
+                None => tracing::warn!("Queued player profile {} does not exist", identifier),
+            }
+        }
+    }
+
+    // Backstop for saves which were still queued or failed, everything is written out once more on shutdown
+    pub async fn save_persistent_profiles(&self) {
+        let profiles: Vec<Arc<PlayerProfile>> =
+            self.player_list.read().await.values().cloned().collect();
+        let mut saved = 0;
+
+        for profile in profiles.iter().filter(|x| x.is_persistent()) {
+            match self.write_profile(profile).await {
+                Ok(()) => saved += 1,
+                Err(()) => tracing::error!(
+                    "Could not save player profile {}",
+                    profile.get_credentials().get_identifier()
+                ),
+            }
+        }
+
+        tracing::info!("Saved {} player profiles", saved);
+    }
+
+    // Every write goes through here one at a time, and the profile is only read once it is this write's turn
+    // That way whichever write lands last always carries the latest credentials and holdings
+    async fn write_profile(&self, profile: &PlayerProfile) -> Result<(), ()> {
+        let _writing = self.save_lock.lock().await;
+        self.store.save_profile(profile.to_stored()).await
+    }
+
+    async fn hash_on_blocking_thread(password: &str) -> Result<String, CredentialError> {
+        let password = password.to_string();
+        match tokio::task::spawn_blocking(move || hash_password(&password)).await {
+            Ok(Ok(hashed)) => Ok(hashed),
+            Ok(Err(())) => Err(CredentialError::ServerFailure),
+            Err(join_error) => {
+                tracing::error!("Password hashing task failed: {}", join_error);
+                Err(CredentialError::ServerFailure)
             }
-            None => Err(()),
         }
     }
 
@@ -113,3 +286,197 @@ impl PlayerProfiles {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use futures::future::BoxFuture;
+
+    use crate::connectivity::player_info::{
+        credential_policy::{CredentialError, verify_password},
+        player_profile::{AuthType, PlayerCredentials},
+        player_profile_store::{MemoryPlayerProfileStore, PlayerProfileStore, StoredPlayerProfile},
+    };
+
+    use super::PlayerProfiles;
+
+    // Keeps the last password hash written for each profile
+    #[derive(Default)]
+    struct RecordingPlayerProfileStore {
+        password_hashes: Mutex<HashMap<String, String>>,
+    }
+
+    impl PlayerProfileStore for RecordingPlayerProfileStore {
+        fn load_profiles(&self) -> BoxFuture<'_, Result<Vec<StoredPlayerProfile>, ()>> {
+            Box::pin(async { Ok(Vec::new()) })
+        }
+
+        fn save_profile(&self, profile: StoredPlayerProfile) -> BoxFuture<'_, Result<(), ()>> {
+            if let PlayerCredentials::UsernameAndPassword {
+                username,
+                password_hash,
+            } = profile.credentials
+            {
+                self.password_hashes
+                    .lock()
+                    .unwrap()
+                    .insert(username, password_hash);
+            }
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    async fn empty_profiles() -> PlayerProfiles {
+        PlayerProfiles::load(Arc::new(MemoryPlayerProfileStore), Duration::from_secs(60))
+            .await
+            .unwrap()
+    }
+
+    fn login(username: &str, password: &str) -> AuthType {
+        AuthType::UsernameAndPassword {
+            username: username.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn players_log_in_with_the_password_they_registered() {
+        let profiles = empty_profiles().await;
+
+        profiles
+            .create_player_with_username_and_password("kestrel", "correct horse")
+            .await
+            .unwrap();
+
+        assert!(
+            profiles
+                .validate_login_request(&login("kestrel", "correct horse"))
+                .await
+                .is_ok()
+        );
+        assert!(
+            profiles
+                .validate_login_request(&login("kestrel", "battery staple"))
+                .await
+                .is_err()
+        );
+        assert!(
+            profiles
+                .validate_login_request(&login("nobody", "correct horse"))
+                .await
+                .is_err()
+        );
+
+        assert_eq!(
+            profiles
+                .create_player_with_username_and_password("kestrel", "another password")
+                .await,
+            Err(CredentialError::UsernameTaken)
+        );
+    }
+
+    #[tokio::test]
+    async fn changing_password_requires_the_current_one() {
+        let profiles = empty_profiles().await;
+
+        profiles
+            .create_player_with_username_and_password("kestrel", "correct horse")
+            .await
+            .unwrap();
+        let profile = profiles
+            .validate_login_request(&login("kestrel", "correct horse"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            profiles
+                .change_password(&profile, "wrong guess", "battery staple")
+                .await,
+            Err(CredentialError::IncorrectPassword)
+        );
+        assert_eq!(
+            profiles
+                .change_password(&profile, "correct horse", "short")
+                .await,
+            Err(CredentialError::PasswordTooShort { minimum_length: 8 })
+        );
+        assert_eq!(
+            profiles
+                .change_password(&profile, "correct horse", "battery staple")
+                .await,
+            Ok(())
+        );
+
+        assert!(
+            profiles
+                .validate_login_request(&login("kestrel", "correct horse"))
+                .await
+                .is_err()
+        );
+        assert!(
+            profiles
+                .validate_login_request(&login("kestrel", "battery staple"))
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn token_players_have_no_password_to_change() {
+        let profiles = empty_profiles().await;
+
+        let token = profiles.create_player_with_token().await.unwrap();
+        let profile = profiles
+            .validate_login_request(&AuthType::BasicToken { token })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            profiles
+                .change_password(&profile, "anything", "battery staple")
+                .await,
+            Err(CredentialError::NotPasswordProtected)
+        );
+    }
+
+    #[tokio::test]
+    async fn queued_saves_never_bring_back_an_old_password() {
+        let store = Arc::new(RecordingPlayerProfileStore::default());
+        let profiles = PlayerProfiles::load(store.clone(), Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        profiles
+            .create_player_with_username_and_password("kestrel", "correct horse")
+            .await
+            .unwrap();
+        let profile = profiles
+            .validate_login_request(&login("kestrel", "correct horse"))
+            .await
+            .unwrap();
+
+        // Queued by the simulation just before the password changed, but only written afterwards
+        let (queue_sender, queue) = async_channel::unbounded();
+        queue_sender.try_send(profile.to_stored()).unwrap();
+        profiles
+            .change_password(&profile, "correct horse", "battery staple")
+            .await
+            .unwrap();
+        queue_sender.close();
+        profiles.save_queued_profiles(queue).await;
+
+        let stored_hash = store
+            .password_hashes
+            .lock()
+            .unwrap()
+            .get("kestrel")
+            .cloned()
+            .unwrap();
+        assert!(verify_password("battery staple", &stored_hash));
+    }
+}
@@ -17,23 +17,21 @@
 
 use std::{
     sync::{Arc, Mutex, MutexGuard, Weak},
-    time::Instant,
+    time::{Duration, Instant},
 };
 
-use async_channel::{Receiver, Sender};
+use async_channel::Sender;
 use tracing::trace;
 
 use crate::{
     connectivity::{
         client_server_message::ClientServerMessage,
-        server_client_message::ServerClientMessage
+        outbound_queue::{OutboundQueueStatistics, OutboundReceiver}
     },
 };
 
 use super::player_profile::PlayerProfile;
 
-const SESSION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
-
 struct PlayerSessionData {
     last_connected_time: Instant,
     player_session: Option<Arc<PlayerSession>>,
@@ -41,22 +39,24 @@ struct PlayerSessionData {
 
 pub struct PlayerSessionTimeout {
     data: Mutex<PlayerSessionData>,
+    timeout: Duration,
 }
 
 impl PlayerSessionTimeout {
-    pub fn new(session: Option<PlayerSession>) -> Self {
+    pub fn new(session: Option<PlayerSession>, timeout: Duration) -> Self {
         Self {
             data: Mutex::new(PlayerSessionData {
                 last_connected_time: std::time::Instant::now(),
                 player_session: session.map(|x| Arc::new(x)),
             }),
+            timeout,
         }
     }
 
     pub fn get_session(&self) -> Weak<PlayerSession> {
         let locked_data = self.data.lock().unwrap();
         if let Some(session) = &locked_data.player_session {
-            if Self::is_within_session_check(&locked_data.last_connected_time) {
+            if self.is_within_session_check(&locked_data.last_connected_time) {
                 Arc::downgrade(&session)
             } else {
                 Weak::new()
@@ -70,25 +70,21 @@ impl PlayerSessionTimeout {
         let mut locked_data = self.data.lock().unwrap();
         let new_session_ref = Arc::new(session);
         locked_data.player_session = Some(new_session_ref.clone());
-        Self::reset_session_timer(&mut locked_data);
+        self.reset_session_timer(&mut locked_data);
         Arc::downgrade(&new_session_ref)
     }
 
-    fn is_within_session_check(time: &std::time::Instant) -> bool {
-        if time.elapsed() > SESSION_TIMEOUT {
-            false
-        } else {
-            true
-        }
+    fn is_within_session_check(&self, time: &std::time::Instant) -> bool {
+        time.elapsed() <= self.timeout
     }
 
     pub fn retain_if_valid(&self) -> bool {
         let mut guard = self.data.lock().unwrap();
-        Self::retain_if_valid_intern(&mut guard)
+        self.retain_if_valid_intern(&mut guard)
     }
 
-    fn retain_if_valid_intern<'a>(guard: &mut MutexGuard<'a, PlayerSessionData>) -> bool {
-        if Self::is_within_session_check(&guard.last_connected_time) {
+    fn retain_if_valid_intern<'a>(&self, guard: &mut MutexGuard<'a, PlayerSessionData>) -> bool {
+        if self.is_within_session_check(&guard.last_connected_time) {
             true
         } else {
             if let Some(session) = &guard.player_session {
@@ -102,29 +98,29 @@ impl PlayerSessionTimeout {
 
     pub fn extend_session<'a>(&self) -> bool {
         let mut guard = self.data.lock().unwrap();
-        if Self::retain_if_valid_intern(&mut guard) {
-            Self::reset_session_timer(&mut guard);
+        if self.retain_if_valid_intern(&mut guard) {
+            self.reset_session_timer(&mut guard);
             true
         } else {
             false
         }
     }
 
-    fn reset_session_timer<'a>(guard: &mut MutexGuard<'a, PlayerSessionData>) -> Instant {
+    fn reset_session_timer<'a>(&self, guard: &mut MutexGuard<'a, PlayerSessionData>) -> Instant {
         guard.last_connected_time = std::time::Instant::now();
-        guard.last_connected_time + SESSION_TIMEOUT
+        guard.last_connected_time + self.timeout
     }
 }
 
 pub struct PlayerSession {
     command_queue_inbound: async_channel::Sender<ClientServerMessage>,
-    command_queue_outbound: async_channel::Receiver<ServerClientMessage>,
+    command_queue_outbound: OutboundReceiver,
     pub player_profile: Arc<PlayerProfile>,
     pub session_id: String
 }
 
 impl PlayerSession {
-    pub fn new(profile: Arc<PlayerProfile>, session_id: String, command_queue_inbound: async_channel::Sender<ClientServerMessage>, command_queue_outbound: async_channel::Receiver<ServerClientMessage>) -> Self {
+    pub fn new(profile: Arc<PlayerProfile>, session_id: String, command_queue_inbound: async_channel::Sender<ClientServerMessage>, command_queue_outbound: OutboundReceiver) -> Self {
         Self {
             command_queue_inbound,
             command_queue_outbound,
@@ -137,7 +133,11 @@ impl PlayerSession {
         self.command_queue_inbound.clone()
     }
 
-    pub fn clone_outbound_receiver(&self) -> Receiver<ServerClientMessage> {
+    pub fn clone_outbound_receiver(&self) -> OutboundReceiver {
         self.command_queue_outbound.clone()
     }
+
+    pub fn outbound_statistics(&self) -> OutboundQueueStatistics {
+        self.command_queue_outbound.statistics()
+    }
 }
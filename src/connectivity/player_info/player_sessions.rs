@@ -16,7 +16,7 @@
 */
 
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     sync::{Arc, Weak},
 };
 
@@ -26,18 +26,26 @@ use crate::{
     backend::components::session::player_session_component::PlayerSessionComponent,
     connectivity::{
         client_server_message::ClientServerMessage,
+        outbound_queue::{OutboundQueueStatistics, outbound_queue},
         player_info::{player_profile::PlayerProfile, player_session::PlayerSession},
-        server_client_message::ServerClientMessage,
         services::ecs_communication_service::EcsCommunicationService,
     },
 };
 
-#[derive(Default, Clone)]
+#[derive(Clone)]
 pub struct PlayerSessions {
     player_logins: Arc<tokio::sync::Mutex<HashMap<String, Weak<PlayerSession>>>>,
+    maximum_queued_bytes: usize, // Per session, past which a connection is considered hopelessly behind
 }
 
 impl PlayerSessions {
+    pub fn new(maximum_queued_bytes: usize) -> Self {
+        Self {
+            player_logins: Arc::default(),
+            maximum_queued_bytes,
+        }
+    }
+
     pub async fn get_session(&self, token: &str) -> Weak<PlayerSession> {
         let mut session_table = self.player_logins.lock().await;
 
@@ -55,6 +63,23 @@ impl PlayerSessions {
         }
     }
 
+    // Totals across every live session, since one player's backlog is easy to miss otherwise
+    pub async fn outbound_statistics(&self) -> (usize, OutboundQueueStatistics) {
+        let session_table = self.player_logins.lock().await;
+
+        // Several login tokens may share one session
+        let mut counted = HashSet::new();
+        let mut totals = OutboundQueueStatistics::default();
+
+        for session in session_table.values().filter_map(Weak::upgrade) {
+            if counted.insert(Arc::as_ptr(&session)) {
+                totals.add(session.outbound_statistics());
+            }
+        }
+
+        (counted.len(), totals)
+    }
+
     pub async fn create_session(
         &self,
         profile: Arc<PlayerProfile>,
@@ -75,7 +100,7 @@ impl PlayerSessions {
             Some(good_existing) => Arc::downgrade(&good_existing),
             None => {
                 let (in_send, in_receive) = async_channel::unbounded::<ClientServerMessage>();
-                let (out_send, out_receive) = async_channel::unbounded::<ServerClientMessage>();
+                let (out_send, out_receive) = outbound_queue(self.maximum_queued_bytes);
 
                 let new_session =
                     PlayerSession::new(profile.clone(), session_id.clone(), in_send, out_receive);
@@ -20,13 +20,31 @@ use ts_rs::TS;
 
 use crate::connectivity::controllable_object_message_data::*;
 use crate::connectivity::dynamic_object_message_data::*;
+use crate::connectivity::hyperspace_message_data::*;
+use crate::connectivity::landing_message_data::*;
+use crate::connectivity::market_message_data::*;
+use crate::connectivity::mission_message_data::*;
+use crate::connectivity::ship_status_message_data::*;
+use crate::connectivity::shipyard_message_data::*;
+use crate::connectivity::target_message_data::*;
 
 #[derive(Serialize, Debug, TS)]
 #[ts(export)]
 #[serde(tag = "type", content = "data")]
 pub enum ServerClientMessage {
     ViewportFollow(ViewportFollowData),
-    DynamicObjectUpdate(DynamicObjectUpdateData),
-    DynamicObjectCreation(DynamicObjectCreationData),
-    DynamicObjectDestruction(DynamicObjectDestructionData),
+    WorldSnapshot(WorldSnapshotData),
+    Landed(LandedData),
+    LandingRefused(LandingRefusedData),
+    Market(MarketData),
+    Missions(MissionsData),
+    Shipyard(ShipyardData),
+    TookOff,
+    StarSystemEntered(StarSystemData),
+    HyperspaceJumpStarted(HyperspaceJumpData),
+    HyperspaceJumpCompleted(HyperspaceJumpData),
+    ShipStatus(ShipStatusData),
+    TargetInfo(TargetInfoData),
+    TargetCleared,
+    ServerShutdown,
 }
@@ -0,0 +1,37 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::configuration_file_structures::reference_types::ObjectId;
+
+// Everything the client HUD shows about the ship a player is flying
+#[derive(Serialize, Debug, Clone, PartialEq, TS)]
+#[ts(export)]
+pub struct ShipStatusData {
+    pub ship_id: ObjectId,
+    pub hull: f32,
+    pub maximum_hull: f32,
+    pub shield: f32,
+    pub maximum_shield: f32,
+    pub fuel: f32,
+    pub maximum_fuel: f32,
+    pub energy: f32,
+    pub maximum_energy: f32,
+    pub cargo_capacity: u32,
+}
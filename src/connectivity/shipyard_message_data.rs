@@ -0,0 +1,58 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::configuration_file_structures::reference_types::{
+    ObjectId, OutfitReference, ShipReference,
+};
+
+#[derive(Serialize, Debug, TS)]
+#[ts(export)]
+pub struct ShipOfferData {
+    pub ship_reference: ShipReference,
+    pub ship_name: String,
+    pub price: u32,
+}
+
+#[derive(Serialize, Debug, TS)]
+#[ts(export)]
+pub struct OutfitOfferData {
+    pub outfit_reference: OutfitReference,
+    pub outfit_name: String,
+    pub price: u32,
+    pub mass: f32,
+    pub fitted: u32, // How many the player's ship already has
+}
+
+// The shipyard and outfitter of the planetoid a player is landed on, along with the ship they would take off in
+#[derive(Serialize, Debug, TS)]
+#[ts(export)]
+pub struct ShipyardData {
+    pub planetoid_id: ObjectId,
+    pub credits: u64,
+    pub ship_reference: ShipReference,
+    pub ship_name: String,
+    pub trade_in_value: u32,
+    pub outfit_space: f32,
+    pub outfit_space_used: f32,
+    pub weapon_slots: u32,
+    pub weapon_slots_used: u32,
+    pub ships: Vec<ShipOfferData>,
+    pub outfits: Vec<OutfitOfferData>,
+}
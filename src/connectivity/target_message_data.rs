@@ -0,0 +1,41 @@
+/*
+    This file is part of Infinite Escape Velocity.
+
+    Infinite Escape Velocity is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Infinite Escape Velocity is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Infinite Escape Velocity.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::configuration_file_structures::reference_types::ObjectId;
+
+#[derive(Serialize, Debug, TS)]
+#[ts(export)]
+pub struct TargetHealthData {
+    pub hull: f32,
+    pub maximum_hull: f32,
+    pub shield: f32,
+    pub maximum_shield: f32,
+}
+
+#[derive(Serialize, Debug, TS)]
+#[ts(export)]
+pub struct TargetInfoData {
+    pub target_id: ObjectId,
+    pub name: String,
+    pub distance: f64,
+    pub relative_vx: f32, // Target's velocity minus the player's ship's
+    pub relative_vy: f32,
+    pub health: Option<TargetHealthData>, // Planetoids can't be damaged
+}
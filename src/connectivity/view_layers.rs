@@ -25,3 +25,15 @@ pub enum ViewLayers {
     Ships = 2,
     Weapons = 3
 }
+
+impl ViewLayers {
+    // Higher priority layers are sent first when a snapshot can't hold everything
+    pub fn priority(&self) -> u8 {
+        match self {
+            ViewLayers::Ships => 3,
+            ViewLayers::Planetoids => 2,
+            ViewLayers::Weapons => 1,
+            ViewLayers::Background => 0,
+        }
+    }
+}
@@ -25,33 +25,22 @@ use axum::http::Uri;
 use axum::http::header::HOST;
 use axum::routing::post;
 use axum::{Router, routing::get};
+use axum_server::tls_rustls::RustlsConfig;
 use backend::configuration_file_loaders::asset_bundle_loader::AssetBundleLoader;
-use backend::resources::delta_t_resource::{DeltaTResource, increment_time};
-use backend::spatial_optimizer::collision_optimizer::{CollisionOptimizer, collision_system};
-use backend::spatial_optimizer::hash_sized::HashSized;
-use backend::world_objects::components::collision_component::clear_old_collisions;
-use backend::world_objects::components::semi_newtonian_physics_component::SemiNewtonianPhysicsComponent;
-use backend::world_objects::components::timeout_component::{
-    TimeoutComponent, check_despawn_times,
-};
-use backend::world_objects::server_viewport::{Displayable, tick_viewport};
-use backend::world_objects::ship::ShipBundle;
-use bevy_ecs::entity::Entity;
-use bevy_ecs::prelude::{Commands, Res, Resource};
-use bevy_ecs::query::With;
-use bevy_ecs::schedule::{IntoScheduleConfigs, Schedule};
-use bevy_ecs::system::Query;
-use bevy_ecs::world::World;
+use backend::resources::delta_t_resource::DeltaTResource;
+use bevy_ecs::prelude::Resource;
+use bevy_ecs::system::RunSystemOnce;
 use clap::Parser;
 use connectivity::handlers::player_profile_handlers::{
-    create_new_ephemeral_player, create_new_username_player,
+    change_password, create_new_ephemeral_player, create_new_username_player,
 };
 use connectivity::handlers::player_session_handlers::login_player;
+use connectivity::player_info::player_profile_store::{
+    FilePlayerProfileStore, MemoryPlayerProfileStore, PlayerProfileStore,
+};
 use connectivity::player_info::player_profiles::PlayerProfiles;
 use connectivity::player_info::player_sessions::PlayerSessions;
-use euclid::Angle;
-use rand::Rng;
-use shared_types::{Coordinates, Speed, Velocity};
+use shared_types::Radius;
 use tokio::time;
 use tower_http::compression::CompressionLayer;
 use tower_http::cors::{AllowOrigin, Any, CorsLayer};
@@ -59,8 +48,9 @@ use tracing::{Level, debug, trace};
 use tracing_subscriber::FmtSubscriber;
 
 use connectivity::handlers::websocket_handler::*;
-use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::future::IntoFuture;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
@@ -68,108 +58,33 @@ use tracing_subscriber::layer::SubscriberExt;
 
 use tower_http::services::ServeDir;
 
-use crate::backend::components::session::player_session_component::process_input_messages_system;
 use crate::backend::configuration_file_loaders::asset_file_cache::AssetFileCache;
 use crate::backend::configuration_file_loaders::definition_caches::list_required_assets::ListRequiredAssets;
 use crate::backend::configuration_file_loaders::definition_file_cache::DefinitionFileCache;
-use crate::backend::resources::delta_t_resource::MINIMUM_TICK_DURATION;
-use crate::backend::systems::apply_player_control::apply_player_control;
-use crate::backend::systems::player_session_cleanup::player_session_cleanup;
-use crate::backend::systems::player_spawn_system::spawn_player_ship_and_viewports;
-use crate::backend::systems::submit_command::process_external_commands;
-use crate::backend::systems::update_collisions_with_position::update_collisions_with_position;
-use crate::backend::systems::update_collisions_with_rotation::update_collisions_with_rotation;
-use crate::backend::systems::update_positions_with_velocity::update_positions_with_velocity;
-use crate::backend::systems::update_rotations_with_angular_velocity::update_rotations_with_angular_velocity;
-use crate::backend::systems::update_velocities_with_semi_newtonian_physics::update_velocities_with_semi_newtonian_physics;
-use crate::backend::world_objects::components::random_ship_spawn_placeholder::RandomShipSpawnPlaceholderComponent;
-use crate::backend::world_objects::planetoid::PlanetoidBundle;
+use crate::backend::resources::profile_save_queue_resource::ProfileSaveQueueResource;
+use crate::backend::systems::world_persistence::{collect_world_save, notify_server_shutdown};
+use crate::backend::world_builder::WorldBuilder;
+use crate::backend::world_save_file::WorldSaveFile;
+use crate::configuration_file_structures::server_configuration_file::{
+    ServerConfigurationFile, TlsConfiguration,
+};
 use crate::connectivity::asset_index::{AssetIndex, AssetIndexState, get_asset_index};
 use crate::connectivity::asset_server::{AssetServerState, asset_by_name};
 use crate::connectivity::handlers::chat_handlers::{send_message, subscribe_message};
 use crate::connectivity::handlers::player_session_handlers::validate_login;
 use crate::connectivity::services::chat_service::ChatService;
 use crate::connectivity::services::ecs_communication_service::EcsCommunicationService;
+use crate::utility::cancel_flag::CancelFlag;
+use crate::utility::shutdown_signal::wait_for_shutdown_signal;
 
-fn plus_or_minus_random(radius: f64) -> f64 {
-    let value = rand::rng().random::<f64>();
-    let range = radius * 2.0;
-    (range * value) - radius
-}
+// How long connections are given to close after the world is saved before the server exits regardless
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(2);
 
 #[derive(Resource)]
 struct AssetIndexResource {
     asset_index: Arc<AssetIndex>,
 }
 
-async fn spawn_a_ship_idk_task(commands: EcsCommunicationService) {
-    loop {
-        tokio::time::sleep(Duration::from_secs(1)).await;
-        let _ = commands
-            .run_command(|commands| -> Result<(), ()> {
-                commands.spawn(RandomShipSpawnPlaceholderComponent {});
-                Ok(())
-            })
-            .await
-            .unwrap()
-            .unwrap();
-    }
-}
-
-fn spawn_a_ship_idk(
-    placeholders: Query<Entity, With<RandomShipSpawnPlaceholderComponent>>,
-    asset_index: Res<AssetIndexResource>,
-    mut commands: Commands,
-) {
-    for spawn in placeholders.iter() {
-        commands.spawn((
-            ShipBundle::new(
-                Coordinates::new(plus_or_minus_random(100.0), plus_or_minus_random(100.0)),
-                Some(Velocity::new(
-                    plus_or_minus_random(100.0) as f32,
-                    plus_or_minus_random(100.0) as f32,
-                )),
-                Some(Angle::radians(
-                    plus_or_minus_random(std::f64::consts::PI) as f32
-                )),
-                Some(Angle::radians(
-                    plus_or_minus_random(std::f64::consts::PI) as f32
-                )),
-                &asset_index.asset_index,
-            )
-            .unwrap(),
-            SemiNewtonianPhysicsComponent::new(Speed::new(50.0)),
-            TimeoutComponent::new(Duration::from_secs(10)),
-        ));
-
-        commands.entity(spawn).despawn();
-    }
-}
-
-fn build_collision_phase<T: Send + Sync + HashSized + 'static>(
-    schedule: &mut Schedule,
-    world: &mut World,
-) {
-    world.insert_resource(CollisionOptimizer::<T>::new());
-
-    schedule
-        .add_systems(clear_old_collisions::<T>)
-        .add_systems(
-            update_collisions_with_rotation::<T>.after(update_rotations_with_angular_velocity),
-        )
-        .add_systems(update_collisions_with_position::<T>.after(update_positions_with_velocity))
-        .add_systems(
-            collision_system::<T>
-                .after(clear_old_collisions::<T>)
-                .after(update_collisions_with_position::<T>)
-                .after(update_collisions_with_rotation::<T>)
-                .before(post_collision_checkpoint),
-        );
-}
-
-fn pre_collision_checkpoint() {}
-fn post_collision_checkpoint() {}
-
 #[derive(Parser, Debug)]
 #[command(version, about)]
 struct Args {
@@ -187,6 +102,124 @@ struct Args {
     /// Verify all required assets are loaded for the definitions
     #[clap(long, action)]
     verify_assets: bool,
+
+    /// Directory to store player profiles in. Defaults to the profiles folder within the data directory.
+    #[arg(long)]
+    profile_directory: Option<PathBuf>,
+
+    /// Keep player profiles in memory only, so all players are lost when the server stops
+    #[clap(long, action)]
+    ephemeral_profiles: bool,
+
+    /// File the world is saved to on shutdown and restored from on startup. Defaults to world.json within the data directory.
+    #[arg(long)]
+    save_file: Option<PathBuf>,
+
+    /// Server configuration file. Defaults to server.json within the data directory if it exists.
+    #[arg(long)]
+    config_file: Option<PathBuf>,
+
+    /// Address to listen on, overriding the configuration file
+    #[arg(long)]
+    bind_address: Option<IpAddr>,
+
+    /// Port to listen on, overriding the configuration file
+    #[arg(long)]
+    port: Option<u16>,
+
+    /// PEM certificate chain to serve TLS with, overriding the configuration file
+    #[arg(long, requires = "tls_private_key")]
+    tls_certificate: Option<PathBuf>,
+
+    /// PEM private key to serve TLS with, overriding the configuration file
+    #[arg(long, requires = "tls_certificate")]
+    tls_private_key: Option<PathBuf>,
+
+    /// Ticks per second below which the simulation slows down, overriding the configuration file
+    #[arg(long)]
+    minimum_tick_rate: Option<u32>,
+
+    /// Ticks per second the simulation never exceeds, overriding the configuration file
+    #[arg(long)]
+    maximum_tick_rate: Option<u32>,
+
+    /// Radius around the player that is sent to their client, overriding the configuration file
+    #[arg(long)]
+    viewport_radius: Option<f64>,
+
+    /// Seconds a disconnected player session is kept alive, overriding the configuration file
+    #[arg(long)]
+    session_timeout: Option<u64>,
+
+    /// Seconds a new websocket has to authorize, overriding the configuration file
+    #[arg(long)]
+    authorization_timeout: Option<u64>,
+}
+
+async fn load_server_configuration(args: &Args, data_directory: &Path) -> ServerConfigurationFile {
+    let mut configuration = match &args.config_file {
+        Some(config_file) => match ServerConfigurationFile::load(config_file).await {
+            Ok(loaded) => loaded,
+            Err(()) => {
+                panic!(
+                    "Could not load server configuration file {}",
+                    config_file.to_string_lossy()
+                );
+            }
+        },
+        None => {
+            let default_config_file = data_directory.join("server.json");
+            match tokio::fs::try_exists(&default_config_file).await {
+                Ok(true) => match ServerConfigurationFile::load(&default_config_file).await {
+                    Ok(loaded) => loaded,
+                    Err(()) => {
+                        panic!(
+                            "Could not load server configuration file {}",
+                            default_config_file.to_string_lossy()
+                        );
+                    }
+                },
+                _ => ServerConfigurationFile::default(),
+            }
+        }
+    };
+
+    if let Some(bind_address) = args.bind_address {
+        configuration.bind_address = bind_address;
+    }
+    if let Some(port) = args.port {
+        configuration.port = port;
+    }
+    if let (Some(certificate_path), Some(private_key_path)) =
+        (&args.tls_certificate, &args.tls_private_key)
+    {
+        configuration.tls = Some(TlsConfiguration {
+            certificate_path: certificate_path.clone(),
+            private_key_path: private_key_path.clone(),
+        });
+    }
+    if let Some(minimum_tick_rate) = args.minimum_tick_rate {
+        configuration.minimum_tick_rate = minimum_tick_rate;
+    }
+    if let Some(maximum_tick_rate) = args.maximum_tick_rate {
+        configuration.maximum_tick_rate = maximum_tick_rate;
+    }
+    if let Some(viewport_radius) = args.viewport_radius {
+        configuration.viewport_radius = viewport_radius;
+    }
+    if let Some(session_timeout) = args.session_timeout {
+        configuration.session_timeout_seconds = session_timeout;
+    }
+    if let Some(authorization_timeout) = args.authorization_timeout {
+        configuration.authorization_timeout_seconds = authorization_timeout;
+    }
+
+    match configuration.validate() {
+        Ok(()) => configuration,
+        Err(()) => {
+            panic!("Server configuration is invalid, see errors above");
+        }
+    }
 }
 
 #[tokio::main(flavor = "multi_thread")]
@@ -206,7 +239,7 @@ async fn main() {
 
     tracing::subscriber::set_global_default(tracing).expect("Failed to initialize trace logging");
 
-    let data_directory = match tokio::fs::canonicalize(args.data_directory).await {
+    let data_directory = match tokio::fs::canonicalize(&args.data_directory).await {
         Ok(canon) => canon,
         Err(canon_error) => {
             panic!("Data directory not found! {}", canon_error.to_string());
@@ -215,6 +248,13 @@ async fn main() {
 
     debug!("Using data directory: {}", data_directory.to_string_lossy());
 
+    let server_configuration = load_server_configuration(&args, &data_directory).await;
+    let minimum_tick_duration = server_configuration.get_minimum_tick_duration();
+    let maximum_tick_duration = server_configuration.get_maximum_tick_duration();
+    let viewport_radius = server_configuration.viewport_radius;
+    let view_layer_radii = server_configuration.view_layer_radii;
+    let maximum_snapshot_objects = server_configuration.maximum_snapshot_objects;
+
     let asset_loader =
         match AssetBundleLoader::load_from_directory(data_directory.join("assets")).await {
             Ok(ok) => ok,
@@ -276,6 +316,13 @@ async fn main() {
         }
     }
 
+    match definition_file_cache.verify_references() {
+        Ok(()) => (),
+        Err(()) => {
+            panic!("Definition bundles currently loaded failed verification");
+        }
+    }
+
     if args.verify_assets {
         let mut loading_error = false;
         for required_asset in definition_file_cache.get_required_asset_list() {
@@ -310,6 +357,8 @@ async fn main() {
         }
     }
 
+    let definition_file_cache = Arc::new(definition_file_cache);
+
     let asset_index = Arc::new(AssetIndex::new(
         definition_file_cache
             .get_required_asset_list()
@@ -333,66 +382,44 @@ async fn main() {
 
     let (web_ecs_command_service, ecs_ecs_command_resource) = EcsCommunicationService::create();
 
-    std::thread::spawn(move || {
-        let mut world = World::new();
-
-        world.spawn_batch(
-            definition_file_cache
-                .get_planetoids()
-                .iter()
-                .map(|planetoid| PlanetoidBundle::new(planetoid, &resource_asset_index).unwrap()),
-        );
-        world.insert_resource(DeltaTResource::new());
-        world.insert_resource(AssetIndexResource {
-            asset_index: resource_asset_index,
-        });
-        world.insert_resource(ecs_ecs_command_resource);
-
-        let mut schedule = Schedule::default();
-
-        schedule.add_systems(
-            (
-                player_session_cleanup,
-                process_external_commands,
-                process_input_messages_system,
-                pre_collision_checkpoint,
-            )
-                .chain(),
-        );
+    let save_file = args
+        .save_file
+        .clone()
+        .unwrap_or_else(|| data_directory.join("world.json"));
 
-        schedule.add_systems(
-            (
-                increment_time,
-                update_rotations_with_angular_velocity,
-                update_velocities_with_semi_newtonian_physics,
-                update_positions_with_velocity,
-                post_collision_checkpoint,
-            )
-                .chain()
-                .after(pre_collision_checkpoint),
-        );
+    let world_save = match WorldSaveFile::load(&save_file).await {
+        Ok(loaded) => loaded,
+        Err(()) => {
+            panic!(
+                "Could not load world save file {}",
+                save_file.to_string_lossy()
+            );
+        }
+    };
 
-        build_collision_phase::<Displayable>(&mut schedule, &mut world);
+    let world_shutdown = CancelFlag::default();
+    let simulation_shutdown = world_shutdown.clone();
+    let (world_stopped_sender, mut world_stopped_receiver) = tokio::sync::oneshot::channel::<()>();
+    let (profile_save_sender, profile_save_receiver) = async_channel::unbounded();
 
-        schedule
-            .add_systems(
-                (
-                    tick_viewport,
-                    spawn_a_ship_idk,
-                    check_despawn_times,
-                    spawn_player_ship_and_viewports,
-                )
-                    .after(post_collision_checkpoint),
-            )
-            .add_systems(
-                apply_player_control::<SemiNewtonianPhysicsComponent>
-                    .after(post_collision_checkpoint),
-            );
+    std::thread::spawn(move || {
+        let (mut world, mut schedule) = WorldBuilder::new(
+            definition_file_cache,
+            resource_asset_index,
+            ecs_ecs_command_resource,
+        )
+        .with_tick_durations(minimum_tick_duration, maximum_tick_duration)
+        .with_viewport_radius(Radius::new(viewport_radius))
+        .with_view_layer_radii(view_layer_radii)
+        .with_maximum_snapshot_objects(maximum_snapshot_objects)
+        .with_world_save(world_save)
+        .with_profile_save_queue(ProfileSaveQueueResource::new(profile_save_sender))
+        .build();
 
         const STATS_INTERVAL: usize = 1000;
         let mut stats_counter: usize = 0;
         let mut average_time: f32 = 0.0;
-        loop {
+        while !simulation_shutdown.is_canceled() {
             let now = time::Instant::now();
             schedule.run(&mut world);
             let duration = time::Instant::now().duration_since(now);
@@ -410,9 +437,32 @@ async fn main() {
                 );
                 stats_counter = 0;
             }
-            let minimum_time = MINIMUM_TICK_DURATION.saturating_sub(duration);
+            let minimum_time = minimum_tick_duration.saturating_sub(duration);
             spin_sleep::sleep(minimum_time);
         }
+
+        if let Err(system_error) = world.run_system_once(notify_server_shutdown) {
+            tracing::error!("Could not notify players of shutdown: {}", system_error);
+        }
+
+        match world.run_system_once(collect_world_save) {
+            Ok(save) => {
+                if save.save(&save_file).is_ok() {
+                    tracing::info!(
+                        "Saved {} ships to {}",
+                        save.ships.len(),
+                        save_file.to_string_lossy()
+                    );
+                }
+            }
+            Err(system_error) => {
+                tracing::error!("Could not collect world state to save: {}", system_error);
+            }
+        }
+
+        // Dropping the world closes every session's message queue, which disconnects their websockets after the shutdown message
+        drop(world);
+        let _ = world_stopped_sender.send(());
     });
 
     let asset_server_state = AssetServerState {
@@ -423,15 +473,65 @@ async fn main() {
         assets: asset_index,
     };
 
-    let player_profile_state = PlayerProfiles::new();
-    let player_session_state = PlayerSessions::default();
+    let profile_store: Arc<dyn PlayerProfileStore> = match args.ephemeral_profiles {
+        true => Arc::new(MemoryPlayerProfileStore),
+        false => {
+            let profile_directory = args
+                .profile_directory
+                .unwrap_or_else(|| data_directory.join("profiles"));
+
+            match FilePlayerProfileStore::new(profile_directory).await {
+                Ok(store) => Arc::new(store),
+                Err(()) => {
+                    panic!("Could not open player profile storage");
+                }
+            }
+        }
+    };
+
+    let player_profile_state = match PlayerProfiles::load(
+        profile_store,
+        server_configuration.get_session_timeout(),
+    )
+    .await
+    {
+        Ok(loaded) => loaded,
+        Err(()) => {
+            panic!("Could not load stored player profiles");
+        }
+    };
+    tokio::spawn({
+        let player_profile_state = player_profile_state.clone();
+        async move {
+            player_profile_state
+                .save_queued_profiles(profile_save_receiver)
+                .await
+        }
+    });
+    let player_session_state = PlayerSessions::new(server_configuration.maximum_queued_bytes);
     let chat_service = ChatService::default();
 
     let websocket_state = HandlerState {
         sessions: player_session_state.clone(),
+        authorization_timeout: server_configuration.get_authorization_timeout(),
     };
 
-    tokio::spawn(spawn_a_ship_idk_task(web_ecs_command_service.clone()));
+    let statistics_sessions = player_session_state.clone();
+    tokio::spawn(async move {
+        const OUTBOUND_STATS_INTERVAL: time::Duration = time::Duration::from_secs(60);
+        loop {
+            tokio::time::sleep(OUTBOUND_STATS_INTERVAL).await;
+            let (sessions, totals) = statistics_sessions.outbound_statistics().await;
+            trace!(
+                "{} sessions have {} bytes in {} messages queued, {} snapshots coalesced and {} connections dropped for falling behind",
+                sessions,
+                totals.queued_bytes,
+                totals.queued_messages,
+                totals.coalesced_snapshots,
+                totals.lagging_disconnects
+            );
+        }
+    });
 
     // Annoyingly overcomplicated same-origin CORS allow
     let cors = CorsLayer::new()
@@ -473,9 +573,11 @@ async fn main() {
         )
         .route("/players/newplayer", post(create_new_username_player))
         .with_state(player_profile_state.clone())
+        .route("/players/change-password", post(change_password))
+        .with_state((player_profile_state.clone(), player_session_state.clone()))
         .route("/players/login", post(login_player))
         .with_state((
-            player_profile_state,
+            player_profile_state.clone(),
             player_session_state.clone(),
             web_ecs_command_service.clone(),
         ))
@@ -490,11 +592,88 @@ async fn main() {
         .with_state((chat_service.clone(), player_session_state.clone()))
         .layer(cors);
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:2718").await.unwrap();
-    axum::serve(
-        listener,
-        app.into_make_service_with_connect_info::<SocketAddr>(),
-    )
-    .await
-    .unwrap();
+    let address = server_configuration.get_socket_address();
+    let mut server = match &server_configuration.tls {
+        Some(tls) => {
+            // Only the ring provider is compiled in, but rustls still requires it to be chosen explicitly
+            let _ = rustls::crypto::ring::default_provider().install_default();
+            let tls_config = match RustlsConfig::from_pem_file(
+                &tls.certificate_path,
+                &tls.private_key_path,
+            )
+            .await
+            {
+                Ok(loaded) => loaded,
+                Err(tls_error) => {
+                    panic!("Could not load TLS certificate or private key: {}", tls_error);
+                }
+            };
+
+            let handle = axum_server::Handle::new();
+            let shutdown_handle = handle.clone();
+            let server_shutdown = world_shutdown.clone();
+            tokio::spawn(async move {
+                wait_for_shutdown_signal().await;
+                server_shutdown.cancel();
+                shutdown_handle.graceful_shutdown(None);
+            });
+
+            tracing::info!("Listening on https://{}", address);
+            tokio::spawn(
+                axum_server::bind_rustls(address, tls_config)
+                    .handle(handle)
+                    .serve(app.into_make_service_with_connect_info::<SocketAddr>()),
+            )
+        }
+        None => {
+            let listener = match tokio::net::TcpListener::bind(address).await {
+                Ok(bound) => bound,
+                Err(bind_error) => {
+                    panic!("Could not listen on {}: {}", address, bind_error);
+                }
+            };
+
+            let server_shutdown = world_shutdown.clone();
+
+            tracing::info!("Listening on http://{}", address);
+            tokio::spawn(
+                axum::serve(
+                    listener,
+                    app.into_make_service_with_connect_info::<SocketAddr>(),
+                )
+                .with_graceful_shutdown(async move {
+                    wait_for_shutdown_signal().await;
+                    server_shutdown.cancel();
+                })
+                .into_future(),
+            )
+        }
+    };
+
+    // Either a shutdown signal stops the world, or the server has failed and the world is stopped so it is still saved
+    let server_finished = tokio::select! {
+        _ = &mut world_stopped_receiver => false,
+        result = &mut server => {
+            match result {
+                Ok(Ok(())) => (),
+                Ok(Err(server_error)) => tracing::error!("Server stopped with error: {}", server_error),
+                Err(join_error) => tracing::error!("Server task failed: {}", join_error),
+            }
+            true
+        }
+    };
+
+    if server_finished {
+        world_shutdown.cancel();
+        let _ = world_stopped_receiver.await;
+    } else {
+        // Players were already told about the shutdown, so give their connections a moment to finish closing
+        match tokio::time::timeout(SHUTDOWN_GRACE_PERIOD, server).await {
+            Ok(_) => tracing::info!("All connections closed"),
+            Err(_) => tracing::info!("Closing connections still open after shutdown"),
+        }
+    }
+
+    // The world has stopped, so no more trades can change what players own
+    player_profile_state.save_persistent_profiles().await;
 }
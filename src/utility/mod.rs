@@ -17,3 +17,4 @@
 
 pub mod async_handle;
 pub mod cancel_flag;
+pub mod shutdown_signal;
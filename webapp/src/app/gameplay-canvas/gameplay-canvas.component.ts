@@ -6,11 +6,20 @@ import { DynamicObjectDestructionData } from 'bindings/DynamicObjectDestructionD
 import { DynamicObjectUpdateData } from 'bindings/DynamicObjectUpdateData';
 import { ServerClientMessage } from 'bindings/ServerClientMessage';
 import { ViewportFollowData } from 'bindings/ViewportFollowData';
+import { WorldSnapshotData } from 'bindings/WorldSnapshotData';
 import Konva from 'konva';
 import { Subject, interval } from 'rxjs';
 import { ENVIRONMENT } from 'src/environments/environment';
 import { StarfieldGenerator } from './starfield-generator';
 import { AssetIndexValue } from 'bindings/AssetIndexValue';
+import { HyperlaneData } from 'bindings/HyperlaneData';
+import { StarSystemData } from 'bindings/StarSystemData';
+import { ShipStatusData } from 'bindings/ShipStatusData';
+import { MarketData } from 'bindings/MarketData';
+import { ShipyardData } from 'bindings/ShipyardData';
+import { MissionsData } from 'bindings/MissionsData';
+import { LandingRefusedData } from 'bindings/LandingRefusedData';
+import { TargetInfoData } from 'bindings/TargetInfoData';
 
 type SendMessageFunction = (input: ControlInput, pressed: boolean) => void;
 
@@ -68,6 +77,7 @@ export class GameplayCanvasComponent {
 
   renderer!: Konva.Stage;
   shipLayer!: Konva.Layer;
+  weaponLayer!: Konva.Layer;
   planetoidLayer!: Konva.Layer;
   renderLoop = interval(16);
   assetCache: Map<BigInt, HTMLImageElement> = new Map();
@@ -78,10 +88,55 @@ export class GameplayCanvasComponent {
   camera_center_entity: BigInt | null = null;
   key_status: Map<String, KeyStatus> = new Map();
   starfield_renderer!: StarfieldGenerator;
+  hyperlanes: HyperlaneData[] = [];
+  next_hyperlane: number = 0;
+  ship_status: ShipStatusData | null = null;
+  market: MarketData | null = null;
+  shipyard: ShipyardData | null = null;
+  missions: MissionsData | null = null;
+  landing_refused: LandingRefusedData | null = null;
+  target: TargetInfoData | null = null;
 
   constructor() {
   }
 
+  relativeSpeed(target: TargetInfoData): number {
+    return Math.hypot(target.relative_vx, target.relative_vy);
+  }
+
+  whole(value: number): number {
+    return Math.round(value);
+  }
+
+  held(commodity_reference: string): number {
+    let cargo = this.market?.cargo.find(x => x.commodity_reference == commodity_reference);
+    return cargo ? cargo.quantity : 0;
+  }
+
+  buy(commodity_reference: string) {
+    this.outgoingMessages.next({ type: 'BuyCommodity', commodity: commodity_reference, quantity: 1 });
+  }
+
+  sell(commodity_reference: string) {
+    this.outgoingMessages.next({ type: 'SellCommodity', commodity: commodity_reference, quantity: 1 });
+  }
+
+  buyShip(ship_reference: string) {
+    this.outgoingMessages.next({ type: 'BuyShip', ship: ship_reference });
+  }
+
+  buyOutfit(outfit_reference: string) {
+    this.outgoingMessages.next({ type: 'BuyOutfit', outfit: outfit_reference });
+  }
+
+  sellOutfit(outfit_reference: string) {
+    this.outgoingMessages.next({ type: 'SellOutfit', outfit: outfit_reference });
+  }
+
+  acceptMission(mission_reference: string) {
+    this.outgoingMessages.next({ type: 'AcceptMission', mission: mission_reference });
+  }
+
   object_offset_x(): number {
     return (this.renderer.width() / 2) - this.camera_center_x;
   }
@@ -129,6 +184,21 @@ export class GameplayCanvasComponent {
     if (typeof button !== 'undefined') {
       button.updateStatus(pressed);
     }
+
+    if (pressed && event.key == 't') {
+      this.outgoingMessages.next({ type: 'TakeOff' });
+    }
+
+    if (pressed && event.key == 'Escape') {
+      this.outgoingMessages.next({ type: 'SelectTarget', id: null });
+    }
+
+    // Cycles through the hyperlanes out of the current star system with each press
+    if (pressed && event.key == 'j' && this.hyperlanes.length > 0) {
+      let destination = this.hyperlanes[this.next_hyperlane % this.hyperlanes.length];
+      this.next_hyperlane += 1;
+      this.outgoingMessages.next({ type: 'Jump', star_system: destination.star_system_reference });
+    }
   }
 
   ngOnInit() {
@@ -148,6 +218,9 @@ export class GameplayCanvasComponent {
     this.shipLayer = new Konva.Layer();
     this.renderer.add(this.shipLayer);
 
+    this.weaponLayer = new Konva.Layer();
+    this.renderer.add(this.weaponLayer);
+
     this.renderLoop.subscribe(() => {
       this.refreshScreen();
     });
@@ -162,107 +235,113 @@ export class GameplayCanvasComponent {
 
 
     let canvas = this;
-    this.incomingMessages.subscribe({
-      next(val) {
-        if (val.type == 'DynamicObjectCreation') {
-          let new_dynamic_object = val.data as DynamicObjectCreationData;
-
-          if (canvas.dynamicObjects.has(new_dynamic_object.id) == false) {
-            if (canvas.assetCache.has(new_dynamic_object.object_asset) == false) {
-              let index_has = canvas.assetIdToName.get(new_dynamic_object.object_asset);
-              if (typeof index_has !== 'undefined') {
-                let new_image = new Image();
-                new_image.src = `${ENVIRONMENT.GAME_SERVER_URL}/assets/name/${index_has}`;
-                canvas.assetCache.set(new_dynamic_object.object_asset, new_image);
-              } else {
-                console.error(`Tried to use missing asset Id ${new_dynamic_object.object_asset}`);
-              }
-            }
 
-            let dynamic_object_image = canvas.assetCache.get(new_dynamic_object.object_asset);
-
-            if (typeof dynamic_object_image !== 'undefined') {
-              let konva_image = new Konva.Image({
-                image: dynamic_object_image,
-                width: new_dynamic_object.display_radius,
-                height: new_dynamic_object.display_radius,
-                visible: false,
-              });
-
-              let setAttributes = () => {
-                konva_image.setAttrs({
-                  offsetX: konva_image.width() / 2,
-                  offsetY: konva_image.height() / 2,
-                })
-              };
-
-              let loadedEventEmitter: Subject<null>;
-              if (typeof (dynamic_object_image as any).onLoadEventHandler === 'undefined') {
-                loadedEventEmitter = new Subject();
-                (dynamic_object_image as any).onLoadEventHandler = loadedEventEmitter;
-                dynamic_object_image.onload = () => {
-                  loadedEventEmitter.next(null);
-                }
-              } else {
-                loadedEventEmitter = (dynamic_object_image as any).onLoadEventHandler;
-              }
-
-              loadedEventEmitter.subscribe(setAttributes);
-
-              setAttributes();
-
-              canvas.dynamicObjects.set(new_dynamic_object.id, new RenderedObject(
-                -10000.0,
-                -10000.0,
-                0.0,
-                konva_image
-              ));
-
-              let rendered_object = <RenderedObject>canvas.dynamicObjects.get(new_dynamic_object.id);
-
-              switch (new_dynamic_object.view_layer) {
-                case 'Background':
-                  break;
-                case 'Planetoids':
-                  canvas.planetoidLayer.add(rendered_object.graphics);
-                  break;
-                case 'Ships':
-                  canvas.shipLayer.add(rendered_object.graphics);
-                  break;
-                case 'Weapons':
-                  break;
-              };
-            }
+    let createObject = (new_dynamic_object: DynamicObjectCreationData) => {
+      if (canvas.dynamicObjects.has(new_dynamic_object.id) == false) {
+        if (canvas.assetCache.has(new_dynamic_object.object_asset) == false) {
+          let index_has = canvas.assetIdToName.get(new_dynamic_object.object_asset);
+          if (typeof index_has !== 'undefined') {
+            let new_image = new Image();
+            new_image.src = `${ENVIRONMENT.GAME_SERVER_URL}/assets/name/${index_has}`;
+            canvas.assetCache.set(new_dynamic_object.object_asset, new_image);
+          } else {
+            console.error(`Tried to use missing asset Id ${new_dynamic_object.object_asset}`);
           }
         }
 
-        else if (val.type == 'DynamicObjectUpdate') {
-          let updated_object = val.data as DynamicObjectUpdateData;
-
-          // Correct rotational coordinates from radians to degrees for Konva rendering
-          if (updated_object.rotation != null) {
-            updated_object.rotation.rotation = updated_object.rotation.rotation * (180 / Math.PI);
+        let dynamic_object_image = canvas.assetCache.get(new_dynamic_object.object_asset);
+
+        if (typeof dynamic_object_image !== 'undefined') {
+          let konva_image = new Konva.Image({
+            image: dynamic_object_image,
+            width: new_dynamic_object.display_radius,
+            height: new_dynamic_object.display_radius,
+            visible: false,
+          });
+
+          let setAttributes = () => {
+            konva_image.setAttrs({
+              offsetX: konva_image.width() / 2,
+              offsetY: konva_image.height() / 2,
+            })
+          };
+
+          let loadedEventEmitter: Subject<null>;
+          if (typeof (dynamic_object_image as any).onLoadEventHandler === 'undefined') {
+            loadedEventEmitter = new Subject();
+            (dynamic_object_image as any).onLoadEventHandler = loadedEventEmitter;
+            dynamic_object_image.onload = () => {
+              loadedEventEmitter.next(null);
+            }
+          } else {
+            loadedEventEmitter = (dynamic_object_image as any).onLoadEventHandler;
           }
 
-          let dynamic_object = canvas.dynamicObjects.get(updated_object.id);
+          loadedEventEmitter.subscribe(setAttributes);
+
+          setAttributes();
+
+          canvas.dynamicObjects.set(new_dynamic_object.id, new RenderedObject(
+            -10000.0,
+            -10000.0,
+            0.0,
+            konva_image
+          ));
+
+          let rendered_object = <RenderedObject>canvas.dynamicObjects.get(new_dynamic_object.id);
+
+          switch (new_dynamic_object.view_layer) {
+            case 'Background':
+              break;
+            case 'Planetoids':
+              canvas.planetoidLayer.add(rendered_object.graphics);
+              break;
+            case 'Ships':
+              canvas.shipLayer.add(rendered_object.graphics);
+              break;
+            case 'Weapons':
+              canvas.weaponLayer.add(rendered_object.graphics);
+              break;
+          };
+        }
+      }
+    };
+
+    // Updates only carry the values which changed since the last snapshot, so anything missing is left as it was
+    let updateObject = (updated_object: DynamicObjectUpdateData) => {
+      let dynamic_object = canvas.dynamicObjects.get(updated_object.id);
 
-          if (typeof dynamic_object !== 'undefined') {
-            dynamic_object.x = updated_object.x;
-            dynamic_object.y = updated_object.y;
-            dynamic_object.rotation = updated_object.rotation?.rotation ?? 0.0;
+      if (typeof dynamic_object !== 'undefined') {
+        if (typeof updated_object.position !== 'undefined') {
+          dynamic_object.x = updated_object.position.x;
+          dynamic_object.y = updated_object.position.y;
+        }
 
-            dynamic_object.graphics.show();
-          }
+        // Correct rotational coordinates from radians to degrees for Konva rendering
+        if (typeof updated_object.rotation !== 'undefined') {
+          dynamic_object.rotation = updated_object.rotation.rotation * (180 / Math.PI);
         }
 
-        else if (val.type == 'DynamicObjectDestruction') {
-          let deleted_object = val.data as DynamicObjectDestructionData;
-          let deleted_dynamic_object = canvas.dynamicObjects.get(deleted_object.id);
+        dynamic_object.graphics.show();
+      }
+    };
 
-          if (typeof deleted_dynamic_object !== 'undefined') {
-            deleted_dynamic_object.graphics.remove();
-            canvas.dynamicObjects.delete(deleted_object.id);
-          }
+    let destroyObject = (deleted_object: DynamicObjectDestructionData) => {
+      let deleted_dynamic_object = canvas.dynamicObjects.get(deleted_object.id);
+
+      if (typeof deleted_dynamic_object !== 'undefined') {
+        deleted_dynamic_object.graphics.remove();
+        canvas.dynamicObjects.delete(deleted_object.id);
+      }
+    };
+
+    this.incomingMessages.subscribe({
+      next(val) {
+        if (val.type == 'WorldSnapshot') {
+          let snapshot = val.data as WorldSnapshotData;
+          snapshot.created.forEach(createObject);
+          snapshot.updated.forEach(updateObject);
+          snapshot.destroyed.forEach(destroyObject);
         }
 
         else if (val.type == 'ViewportFollow') {
@@ -277,6 +356,55 @@ export class GameplayCanvasComponent {
           }
         }
 
+        else if (val.type == 'StarSystemEntered') {
+          let star_system = <StarSystemData>val.data;
+          canvas.hyperlanes = star_system.hyperlanes;
+          canvas.next_hyperlane = 0;
+        }
+
+        else if (val.type == 'ShipStatus') {
+          canvas.ship_status = <ShipStatusData>val.data;
+        }
+
+        else if (val.type == 'Market') {
+          canvas.market = <MarketData>val.data;
+        }
+
+        else if (val.type == 'Shipyard') {
+          canvas.shipyard = <ShipyardData>val.data;
+        }
+
+        else if (val.type == 'Landed') {
+          canvas.landing_refused = null;
+          canvas.target = null;
+        }
+
+        else if (val.type == 'LandingRefused') {
+          canvas.landing_refused = <LandingRefusedData>val.data;
+        }
+
+        else if (val.type == 'Missions') {
+          canvas.missions = <MissionsData>val.data;
+        }
+
+        else if (val.type == 'TargetInfo') {
+          canvas.target = <TargetInfoData>val.data;
+        }
+
+        else if (val.type == 'TargetCleared') {
+          canvas.target = null;
+        }
+
+        else if (val.type == 'TookOff') {
+          canvas.market = null;
+          canvas.shipyard = null;
+          canvas.missions = null;
+        }
+
+        else if (val.type == 'ServerShutdown') {
+          console.warn('Server is shutting down');
+        }
+
       }
     })
 
@@ -289,6 +417,9 @@ export class GameplayCanvasComponent {
     this.key_status.set('ArrowLeft', new KeyStatus('Left', send_message));
     this.key_status.set('ArrowRight', new KeyStatus('Right', send_message));
     this.key_status.set(' ', new KeyStatus('Fire', send_message));
+    this.key_status.set('l', new KeyStatus('Land', send_message));
+    this.key_status.set('Shift', new KeyStatus('Afterburner', send_message));
+    this.key_status.set('r', new KeyStatus('CycleTarget', send_message));
   }
 
   ngAfterViewChecked() {